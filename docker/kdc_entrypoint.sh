@@ -0,0 +1,6 @@
+#!/bin/bash
+set -e
+
+# the KDC for the GSSAPI tests runs alongside the server
+krb5kdc
+exec docker-entrypoint.sh "$@"
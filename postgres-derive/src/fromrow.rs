@@ -0,0 +1,124 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataStruct, DeriveInput, Error, Fields, Member, Path};
+
+use crate::overrides::Overrides;
+
+pub fn expand_derive_fromrow(input: DeriveInput) -> Result<TokenStream, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "#[derive(FromRow)] does not support generic types",
+        ));
+    }
+
+    let overrides = Overrides::extract(&input.attrs)?;
+    overrides.check_crate_only(&input.attrs)?;
+    let krate = overrides.crate_path();
+
+    let fields = match input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => &fields.named,
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(ref fields),
+            ..
+        }) => &fields.unnamed,
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "#[derive(FromRow)] may only be applied to structs",
+            ));
+        }
+    };
+
+    let mut members = vec![];
+    let mut values = vec![];
+    for (i, field) in fields.iter().enumerate() {
+        let overrides = Overrides::extract(&field.attrs)?;
+        let member = match field.ident {
+            Some(ref ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(i.into()),
+        };
+        values.push(field_body(&krate, field, &member, overrides)?);
+        members.push(member);
+    }
+
+    let ident = &input.ident;
+    let out = quote! {
+        impl #krate::FromRow for #ident {
+            fn from_row(
+                row: &#krate::Row,
+            ) -> ::std::result::Result<#ident, #krate::Error> {
+                ::std::result::Result::Ok(#ident {
+                    #(
+                        #members: #values,
+                    )*
+                })
+            }
+        }
+    };
+
+    Ok(out)
+}
+
+fn field_body(
+    krate: &Path,
+    field: &syn::Field,
+    member: &Member,
+    overrides: Overrides,
+) -> Result<TokenStream, Error> {
+    let ty = &field.ty;
+
+    if overrides.crate_.is_some() {
+        return Err(Error::new_spanned(
+            field,
+            "`crate` may only be specified on the type",
+        ));
+    }
+
+    if overrides.flatten {
+        if overrides.name.is_some() || overrides.index.is_some() || overrides.default {
+            return Err(Error::new_spanned(
+                field,
+                "`flatten` cannot be combined with other overrides",
+            ));
+        }
+
+        return Ok(quote! {
+            <#ty as #krate::FromRow>::from_row(row)?
+        });
+    }
+
+    let idx = match (overrides.name, overrides.index, member) {
+        (Some(_), Some(_), _) => {
+            return Err(Error::new_spanned(
+                field,
+                "`name` and `index` cannot both be specified",
+            ));
+        }
+        (Some(name), None, _) => quote!(#name),
+        (None, Some(index), _) => quote!(#index),
+        (None, None, Member::Named(ident)) => {
+            let name = ident.to_string();
+            quote!(#name)
+        }
+        (None, None, Member::Unnamed(index)) => {
+            let index = index.index as usize;
+            quote!(#index)
+        }
+    };
+
+    let body = if overrides.default {
+        quote! {
+            row.__try_get::<_, #ty>(#idx)?.unwrap_or_default()
+        }
+    } else {
+        quote! {
+            row.__get::<_, #ty>(#idx)?
+        }
+    };
+
+    Ok(body)
+}
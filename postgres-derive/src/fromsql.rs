@@ -0,0 +1,183 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataStruct, DeriveInput, Error, Fields, Ident, Path};
+
+use crate::accepts;
+use crate::composites::Field;
+use crate::enums::Variant;
+use crate::overrides::Overrides;
+
+pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "#[derive(FromSql)] does not support generic types",
+        ));
+    }
+
+    let overrides = Overrides::extract(&input.attrs)?;
+    overrides.check_type(&input.attrs)?;
+    let krate = overrides.crate_path();
+    let name = overrides.name.unwrap_or_else(|| input.ident.to_string());
+
+    let (accepts_body, from_sql_body) = match input.data {
+        Data::Enum(ref data) => {
+            let variants = data
+                .variants
+                .iter()
+                .map(Variant::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+            (
+                accepts::enum_body(&krate, &name, &variants),
+                enum_body(&input.ident, &variants),
+            )
+        }
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(ref fields),
+            ..
+        }) if fields.unnamed.len() == 1 => {
+            let field = fields.unnamed.first().unwrap();
+            (
+                domain_accepts_body(&krate, &name, field),
+                domain_body(&krate, &input.ident, field),
+            )
+        }
+        Data::Struct(DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => {
+            let fields = fields
+                .named
+                .iter()
+                .map(Field::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+            (
+                accepts::composite_body(&krate, &name, "FromSql", &fields),
+                composite_body(&krate, &input.ident, &fields),
+            )
+        }
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "#[derive(FromSql)] may only be applied to structs, single field tuple structs, \
+                 and enums",
+            ));
+        }
+    };
+
+    let ident = &input.ident;
+    let out = quote! {
+        impl<'a> #krate::types::FromSql<'a> for #ident {
+            fn from_sql(
+                _type: &#krate::types::Type,
+                buf: &'a [u8],
+            ) -> ::std::result::Result<
+                #ident,
+                ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Sync + ::std::marker::Send>,
+            > {
+                #from_sql_body
+            }
+
+            fn accepts(type_: &#krate::types::Type) -> bool {
+                #accepts_body
+            }
+        }
+    };
+
+    Ok(out)
+}
+
+fn enum_body(ident: &Ident, variants: &[Variant]) -> TokenStream {
+    let variant_names = variants.iter().map(|v| &v.name);
+    let idents = variants.iter().map(|v| &v.ident);
+
+    quote! {
+        match ::std::str::from_utf8(buf)? {
+            #(
+                #variant_names => ::std::result::Result::Ok(#ident::#idents),
+            )*
+            s => ::std::result::Result::Err(
+                ::std::format!("invalid variant `{}`", s).into()
+            ),
+        }
+    }
+}
+
+// Postgres reports the underlying type rather than the domain for query results, so accept
+// either.
+fn domain_accepts_body(krate: &Path, name: &str, field: &syn::Field) -> TokenStream {
+    let ty = &field.ty;
+    let normal_body = accepts::domain_body(krate, name, "FromSql", field);
+
+    quote! {
+        if <#ty as #krate::types::FromSql>::accepts(type_) {
+            return true;
+        }
+
+        #normal_body
+    }
+}
+
+fn domain_body(krate: &Path, ident: &Ident, field: &syn::Field) -> TokenStream {
+    let ty = &field.ty;
+
+    quote! {
+        let type_ = match *_type.kind() {
+            #krate::types::Kind::Domain(ref type_) => type_,
+            _ => _type,
+        };
+
+        <#ty as #krate::types::FromSql>::from_sql(type_, buf).map(#ident)
+    }
+}
+
+fn composite_body(krate: &Path, ident: &Ident, fields: &[Field]) -> TokenStream {
+    let temp_vars = &fields
+        .iter()
+        .map(|f| Ident::new(&format!("__{}", f.ident), f.ident.span()))
+        .collect::<Vec<_>>();
+    let field_names = fields.iter().map(|f| &f.name);
+    let field_idents = fields.iter().map(|f| &f.ident);
+
+    quote! {
+        let fields = match *_type.kind() {
+            #krate::types::Kind::Composite(ref fields) => fields,
+            _ => unreachable!(),
+        };
+
+        let mut buf = buf;
+        let num_fields = #krate::types::__read_be_i32(&mut buf)?;
+        if num_fields as usize != fields.len() {
+            return ::std::result::Result::Err(
+                ::std::format!("invalid field count: {} vs {}", num_fields, fields.len()).into()
+            );
+        }
+
+        #(
+            let mut #temp_vars = ::std::option::Option::None;
+        )*
+
+        for field in fields {
+            match field.name() {
+                #(
+                    #field_names => {
+                        #temp_vars = ::std::option::Option::Some(
+                            #krate::types::__read_composite_field(field.type_(), &mut buf)?
+                        );
+                    }
+                )*
+                _ => unreachable!(),
+            }
+        }
+
+        if !buf.is_empty() {
+            return ::std::result::Result::Err("invalid message length".into());
+        }
+
+        ::std::result::Result::Ok(#ident {
+            #(
+                #field_idents: #temp_vars.unwrap(),
+            )*
+        })
+    }
+}
@@ -0,0 +1,92 @@
+//! Derive macros for the `ToSql`, `FromSql`, and `FromRow` traits of `tokio-postgres`.
+//!
+//! These are re-exported from `tokio_postgres::types` (and `tokio_postgres`, for `FromRow`) when
+//! its `derive` Cargo feature is enabled, and from the root of `postgres` when its `derive`
+//! feature is. See the documentation of `FromRow` for its attributes.
+//!
+//! # Crate path
+//!
+//! The generated code refers to the traits through the `tokio_postgres` crate name by default.
+//! Crates that only depend on `postgres` can point it at the copy of `tokio-postgres` that
+//! `postgres` re-exports instead with a `#[postgres(crate = "...")]` attribute on the type:
+//!
+//! ```ignore
+//! #[derive(Debug, ToSql, FromSql)]
+//! #[postgres(crate = "postgres::tokio_postgres")]
+//! struct Celsius(f64);
+//! ```
+//!
+//! # Enums
+//!
+//! C-like Rust enums map to Postgres enum types. Each Rust variant corresponds to the Postgres
+//! variant of the same name.
+//!
+//! # Domains
+//!
+//! Newtype structs (tuple structs with a single field) map to Postgres domains whose underlying
+//! type is compatible with the wrapped type.
+//!
+//! # Composites
+//!
+//! Structs with named fields map to Postgres composite types. Each Rust field corresponds to the
+//! composite field of the same name, and `accepts` checks that every field exists with a
+//! compatible type.
+//!
+//! # Naming
+//!
+//! The Postgres type name defaults to the name of the Rust type, and the names of enum variants
+//! and composite fields default to their Rust names. Any of these can be overridden with a
+//! `#[postgres(name = "...")]` attribute:
+//!
+//! ```ignore
+//! #[derive(Debug, ToSql, FromSql)]
+//! #[postgres(name = "mood")]
+//! enum Mood {
+//!     #[postgres(name = "sad")]
+//!     Sad,
+//!     #[postgres(name = "ok")]
+//!     Ok,
+//!     #[postgres(name = "happy")]
+//!     Happy,
+//! }
+//! ```
+#![recursion_limit = "256"]
+#![warn(rust_2018_idioms, clippy::all)]
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod accepts;
+mod composites;
+mod enums;
+mod fromrow;
+mod fromsql;
+mod overrides;
+mod tosql;
+
+#[proc_macro_derive(ToSql, attributes(postgres))]
+pub fn derive_tosql(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    tosql::expand_derive_tosql(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(FromSql, attributes(postgres))]
+pub fn derive_fromsql(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    fromsql::expand_derive_fromsql(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(FromRow, attributes(postgres))]
+pub fn derive_fromrow(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    fromrow::expand_derive_fromrow(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -0,0 +1,113 @@
+use syn::{Attribute, Error, Lit, Meta, NestedMeta, Path};
+
+pub struct Overrides {
+    pub name: Option<String>,
+    pub index: Option<usize>,
+    pub flatten: bool,
+    pub default: bool,
+    pub crate_: Option<Path>,
+}
+
+impl Overrides {
+    pub fn extract(attrs: &[Attribute]) -> Result<Overrides, Error> {
+        let mut overrides = Overrides {
+            name: None,
+            index: None,
+            flatten: false,
+            default: false,
+            crate_: None,
+        };
+
+        for attr in attrs {
+            if !attr.path.is_ident("postgres") {
+                continue;
+            }
+
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                bad => return Err(Error::new_spanned(bad, "expected a #[postgres(...)]")),
+            };
+
+            for item in &list.nested {
+                match item {
+                    NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("name") => {
+                        match &meta.lit {
+                            Lit::Str(s) => overrides.name = Some(s.value()),
+                            bad => {
+                                return Err(Error::new_spanned(bad, "expected a string literal"))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("index") => {
+                        match &meta.lit {
+                            Lit::Int(i) => overrides.index = Some(i.base10_parse()?),
+                            bad => {
+                                return Err(Error::new_spanned(bad, "expected an integer literal"))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("crate") => {
+                        match &meta.lit {
+                            Lit::Str(s) => overrides.crate_ = Some(s.parse()?),
+                            bad => {
+                                return Err(Error::new_spanned(bad, "expected a string literal"))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("flatten") => {
+                        overrides.flatten = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => {
+                        overrides.default = true;
+                    }
+                    bad => return Err(Error::new_spanned(bad, "unknown override")),
+                }
+            }
+        }
+
+        Ok(overrides)
+    }
+
+    /// Returns an error if any overrides other than `name` were specified.
+    pub fn check_name_only(&self, attrs: &[Attribute]) -> Result<(), Error> {
+        if self.index.is_some() || self.flatten || self.default || self.crate_.is_some() {
+            return Err(error(attrs, "only the `name` override is supported here"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if any overrides other than `name` and `crate` were specified.
+    pub fn check_type(&self, attrs: &[Attribute]) -> Result<(), Error> {
+        if self.index.is_some() || self.flatten || self.default {
+            return Err(error(
+                attrs,
+                "only the `name` and `crate` overrides are supported here",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if any overrides other than `crate` were specified.
+    pub fn check_crate_only(&self, attrs: &[Attribute]) -> Result<(), Error> {
+        if self.name.is_some() || self.index.is_some() || self.flatten || self.default {
+            return Err(error(attrs, "only the `crate` override is supported here"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path to the `tokio-postgres` crate used by the generated code.
+    pub fn crate_path(&self) -> Path {
+        match self.crate_ {
+            Some(ref path) => path.clone(),
+            None => syn::parse_quote!(tokio_postgres),
+        }
+    }
+}
+
+fn error(attrs: &[Attribute], message: &str) -> Error {
+    let attr = attrs.iter().find(|a| a.path.is_ident("postgres")).unwrap();
+    Error::new_spanned(attr, message)
+}
@@ -0,0 +1,150 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataStruct, DeriveInput, Error, Fields, Path};
+
+use crate::accepts;
+use crate::composites::Field;
+use crate::enums::Variant;
+use crate::overrides::Overrides;
+
+pub fn expand_derive_tosql(input: DeriveInput) -> Result<TokenStream, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "#[derive(ToSql)] does not support generic types",
+        ));
+    }
+
+    let overrides = Overrides::extract(&input.attrs)?;
+    overrides.check_type(&input.attrs)?;
+    let krate = overrides.crate_path();
+    let name = overrides.name.unwrap_or_else(|| input.ident.to_string());
+
+    let (accepts_body, to_sql_body) = match input.data {
+        Data::Enum(ref data) => {
+            let variants = data
+                .variants
+                .iter()
+                .map(Variant::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+            (
+                accepts::enum_body(&krate, &name, &variants),
+                enum_body(&krate, &input.ident, &variants),
+            )
+        }
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(ref fields),
+            ..
+        }) if fields.unnamed.len() == 1 => {
+            let field = fields.unnamed.first().unwrap();
+            (
+                accepts::domain_body(&krate, &name, "ToSql", field),
+                domain_body(&krate),
+            )
+        }
+        Data::Struct(DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => {
+            let fields = fields
+                .named
+                .iter()
+                .map(Field::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+            (
+                accepts::composite_body(&krate, &name, "ToSql", &fields),
+                composite_body(&krate, &fields),
+            )
+        }
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "#[derive(ToSql)] may only be applied to structs, single field tuple structs, and \
+                 enums",
+            ));
+        }
+    };
+
+    let ident = &input.ident;
+    let out = quote! {
+        impl #krate::types::ToSql for #ident {
+            fn to_sql(
+                &self,
+                _type: &#krate::types::Type,
+                buf: &mut ::std::vec::Vec<u8>,
+            ) -> ::std::result::Result<
+                #krate::types::IsNull,
+                ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Sync + ::std::marker::Send>,
+            > {
+                #to_sql_body
+            }
+
+            fn accepts(type_: &#krate::types::Type) -> bool {
+                #accepts_body
+            }
+
+            #krate::to_sql_checked!();
+        }
+    };
+
+    Ok(out)
+}
+
+fn enum_body(krate: &Path, ident: &syn::Ident, variants: &[Variant]) -> TokenStream {
+    let idents = variants.iter().map(|v| &v.ident);
+    let names = variants.iter().map(|v| &v.name);
+
+    quote! {
+        let s = match *self {
+            #(
+                #ident::#idents => #names,
+            )*
+        };
+
+        buf.extend_from_slice(s.as_bytes());
+        ::std::result::Result::Ok(#krate::types::IsNull::No)
+    }
+}
+
+fn domain_body(krate: &Path) -> TokenStream {
+    quote! {
+        let type_ = match *_type.kind() {
+            #krate::types::Kind::Domain(ref type_) => type_,
+            _ => unreachable!(),
+        };
+
+        #krate::types::ToSql::to_sql(&self.0, type_, buf)
+    }
+}
+
+fn composite_body(krate: &Path, fields: &[Field]) -> TokenStream {
+    let num_fields = fields.len() as i32;
+    let field_names = fields.iter().map(|f| &f.name);
+    let field_idents = fields.iter().map(|f| &f.ident);
+
+    quote! {
+        let fields = match *_type.kind() {
+            #krate::types::Kind::Composite(ref fields) => fields,
+            _ => unreachable!(),
+        };
+
+        buf.extend_from_slice(&#num_fields.to_be_bytes());
+
+        for field in fields {
+            match field.name() {
+                #(
+                    #field_names => {
+                        #krate::types::__write_composite_field(
+                            &self.#field_idents,
+                            field.type_(),
+                            buf,
+                        )?;
+                    }
+                )*
+                _ => unreachable!(),
+            }
+        }
+
+        ::std::result::Result::Ok(#krate::types::IsNull::No)
+    }
+}
@@ -28,6 +28,7 @@ pub enum Message {
     BindComplete,
     CloseComplete,
     CommandComplete(CommandCompleteBody),
+    CopyBothResponse(CopyBothResponseBody),
     CopyData(CopyDataBody),
     CopyDone,
     CopyInResponse(CopyInResponseBody),
@@ -196,6 +197,16 @@ impl Message {
                 let storage = buf.read_all();
                 Message::RowDescription(RowDescriptionBody { storage, len })
             }
+            b'W' => {
+                let format = buf.read_u8()?;
+                let len = buf.read_u16::<BigEndian>()?;
+                let storage = buf.read_all();
+                Message::CopyBothResponse(CopyBothResponseBody {
+                    format,
+                    len,
+                    storage,
+                })
+            }
             b'Z' => {
                 let status = buf.read_u8()?;
                 Message::ReadyForQuery(ReadyForQueryBody { status })
@@ -369,6 +380,27 @@ impl CommandCompleteBody {
     }
 }
 
+pub struct CopyBothResponseBody {
+    storage: Bytes,
+    len: u16,
+    format: u8,
+}
+
+impl CopyBothResponseBody {
+    #[inline]
+    pub fn format(&self) -> u8 {
+        self.format
+    }
+
+    #[inline]
+    pub fn column_formats(&self) -> ColumnFormats<'_> {
+        ColumnFormats {
+            remaining: self.len,
+            buf: &self.storage,
+        }
+    }
+}
+
 pub struct CopyDataBody {
     storage: Bytes,
 }
@@ -804,6 +836,769 @@ impl<'a> Field<'a> {
     }
 }
 
+pub const XLOG_DATA_TAG: u8 = b'w';
+pub const PRIMARY_KEEPALIVE_TAG: u8 = b'k';
+
+/// A message sent by the server inside of a `CopyData` message while streaming replication.
+pub enum ReplicationMessage<D> {
+    XLogData(XLogDataBody<D>),
+    PrimaryKeepAlive(PrimaryKeepAliveBody),
+    #[doc(hidden)]
+    __ForExtensibility,
+}
+
+impl ReplicationMessage<Bytes> {
+    #[inline]
+    pub fn parse(buf: &Bytes) -> io::Result<ReplicationMessage<Bytes>> {
+        let mut buf = Buffer {
+            bytes: buf.clone(),
+            idx: 0,
+        };
+
+        let tag = buf.read_u8()?;
+
+        let message = match tag {
+            XLOG_DATA_TAG => {
+                let wal_start = buf.read_u64::<BigEndian>()?;
+                let wal_end = buf.read_u64::<BigEndian>()?;
+                let timestamp = buf.read_i64::<BigEndian>()?;
+                let data = buf.read_all();
+                ReplicationMessage::XLogData(XLogDataBody {
+                    wal_start,
+                    wal_end,
+                    timestamp,
+                    data,
+                })
+            }
+            PRIMARY_KEEPALIVE_TAG => {
+                let wal_end = buf.read_u64::<BigEndian>()?;
+                let timestamp = buf.read_i64::<BigEndian>()?;
+                let reply = buf.read_u8()?;
+                ReplicationMessage::PrimaryKeepAlive(PrimaryKeepAliveBody {
+                    wal_end,
+                    timestamp,
+                    reply,
+                })
+            }
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown replication message tag `{}`", tag),
+                ));
+            }
+        };
+
+        Ok(message)
+    }
+}
+
+pub struct XLogDataBody<D> {
+    wal_start: u64,
+    wal_end: u64,
+    timestamp: i64,
+    data: D,
+}
+
+impl<D> XLogDataBody<D> {
+    #[inline]
+    pub fn wal_start(&self) -> u64 {
+        self.wal_start
+    }
+
+    #[inline]
+    pub fn wal_end(&self) -> u64 {
+        self.wal_end
+    }
+
+    /// The server's system clock at the time of transmission, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    #[inline]
+    pub fn data(&self) -> &D {
+        &self.data
+    }
+
+    #[inline]
+    pub fn into_data(self) -> D {
+        self.data
+    }
+
+    pub fn map_data<F, D2, E>(self, f: F) -> Result<XLogDataBody<D2>, E>
+    where
+        F: FnOnce(D) -> Result<D2, E>,
+    {
+        let data = f(self.data)?;
+        Ok(XLogDataBody {
+            wal_start: self.wal_start,
+            wal_end: self.wal_end,
+            timestamp: self.timestamp,
+            data,
+        })
+    }
+}
+
+pub struct PrimaryKeepAliveBody {
+    wal_end: u64,
+    timestamp: i64,
+    reply: u8,
+}
+
+impl PrimaryKeepAliveBody {
+    #[inline]
+    pub fn wal_end(&self) -> u64 {
+        self.wal_end
+    }
+
+    /// The server's system clock at the time of transmission, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// 1 if the server requests a reply as soon as possible, 0 otherwise.
+    #[inline]
+    pub fn reply(&self) -> u8 {
+        self.reply
+    }
+}
+
+pub const BEGIN_TAG: u8 = b'B';
+pub const COMMIT_TAG: u8 = b'C';
+pub const ORIGIN_TAG: u8 = b'O';
+pub const RELATION_TAG: u8 = b'R';
+pub const TYPE_TAG: u8 = b'Y';
+pub const INSERT_TAG: u8 = b'I';
+pub const UPDATE_TAG: u8 = b'U';
+pub const DELETE_TAG: u8 = b'D';
+pub const TRUNCATE_TAG: u8 = b'T';
+pub const TUPLE_NEW_TAG: u8 = b'N';
+pub const TUPLE_KEY_TAG: u8 = b'K';
+pub const TUPLE_OLD_TAG: u8 = b'O';
+pub const TUPLE_DATA_NULL_TAG: u8 = b'n';
+pub const TUPLE_DATA_TOAST_TAG: u8 = b'u';
+pub const TUPLE_DATA_TEXT_TAG: u8 = b't';
+pub const TUPLE_DATA_BINARY_TAG: u8 = b'b';
+
+pub const REPLICA_IDENTITY_DEFAULT_TAG: u8 = b'd';
+pub const REPLICA_IDENTITY_NOTHING_TAG: u8 = b'n';
+pub const REPLICA_IDENTITY_FULL_TAG: u8 = b'f';
+pub const REPLICA_IDENTITY_INDEX_TAG: u8 = b'i';
+
+/// A message produced by the `pgoutput` logical decoding plugin, carried in the data of an `XLogData` message.
+pub enum LogicalReplicationMessage {
+    Begin(BeginBody),
+    Commit(CommitBody),
+    Origin(OriginBody),
+    Relation(RelationBody),
+    Type(TypeBody),
+    Insert(InsertBody),
+    Update(UpdateBody),
+    Delete(DeleteBody),
+    Truncate(TruncateBody),
+    #[doc(hidden)]
+    __ForExtensibility,
+}
+
+impl LogicalReplicationMessage {
+    pub fn parse(buf: &Bytes) -> io::Result<LogicalReplicationMessage> {
+        let mut buf = Buffer {
+            bytes: buf.clone(),
+            idx: 0,
+        };
+
+        let tag = buf.read_u8()?;
+
+        let message = match tag {
+            BEGIN_TAG => LogicalReplicationMessage::Begin(BeginBody {
+                final_lsn: buf.read_u64::<BigEndian>()?,
+                timestamp: buf.read_i64::<BigEndian>()?,
+                xid: buf.read_u32::<BigEndian>()?,
+            }),
+            COMMIT_TAG => LogicalReplicationMessage::Commit(CommitBody {
+                flags: buf.read_i8()?,
+                commit_lsn: buf.read_u64::<BigEndian>()?,
+                end_lsn: buf.read_u64::<BigEndian>()?,
+                timestamp: buf.read_i64::<BigEndian>()?,
+            }),
+            ORIGIN_TAG => LogicalReplicationMessage::Origin(OriginBody {
+                commit_lsn: buf.read_u64::<BigEndian>()?,
+                name: buf.read_cstr()?,
+            }),
+            RELATION_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let namespace = buf.read_cstr()?;
+                let name = buf.read_cstr()?;
+                let replica_identity = match buf.read_u8()? {
+                    REPLICA_IDENTITY_DEFAULT_TAG => ReplicaIdentity::Default,
+                    REPLICA_IDENTITY_NOTHING_TAG => ReplicaIdentity::Nothing,
+                    REPLICA_IDENTITY_FULL_TAG => ReplicaIdentity::Full,
+                    REPLICA_IDENTITY_INDEX_TAG => ReplicaIdentity::Index,
+                    tag => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unknown replica identity tag `{}`", tag),
+                        ));
+                    }
+                };
+                let column_len = buf.read_i16::<BigEndian>()?;
+                let capacity = count_capacity(i32::from(column_len), &buf, "column")?;
+
+                let mut columns = Vec::with_capacity(capacity);
+                for _ in 0..column_len {
+                    columns.push(Column::parse(&mut buf)?);
+                }
+
+                LogicalReplicationMessage::Relation(RelationBody {
+                    rel_id,
+                    namespace,
+                    name,
+                    replica_identity,
+                    columns,
+                })
+            }
+            TYPE_TAG => LogicalReplicationMessage::Type(TypeBody {
+                id: buf.read_u32::<BigEndian>()?,
+                namespace: buf.read_cstr()?,
+                name: buf.read_cstr()?,
+            }),
+            INSERT_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let tag = buf.read_u8()?;
+
+                let tuple = match tag {
+                    TUPLE_NEW_TAG => Tuple::parse(&mut buf)?,
+                    tag => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unexpected tuple tag `{}`", tag),
+                        ));
+                    }
+                };
+
+                LogicalReplicationMessage::Insert(InsertBody { rel_id, tuple })
+            }
+            UPDATE_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let tag = buf.read_u8()?;
+
+                let mut key_tuple = None;
+                let mut old_tuple = None;
+
+                let new_tuple = match tag {
+                    TUPLE_NEW_TAG => Tuple::parse(&mut buf)?,
+                    TUPLE_OLD_TAG | TUPLE_KEY_TAG => {
+                        if tag == TUPLE_OLD_TAG {
+                            old_tuple = Some(Tuple::parse(&mut buf)?);
+                        } else {
+                            key_tuple = Some(Tuple::parse(&mut buf)?);
+                        }
+
+                        match buf.read_u8()? {
+                            TUPLE_NEW_TAG => Tuple::parse(&mut buf)?,
+                            tag => {
+                                return Err(io::Error::new(
+                                    io::ErrorKind::InvalidInput,
+                                    format!("unexpected tuple tag `{}`", tag),
+                                ));
+                            }
+                        }
+                    }
+                    tag => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unknown tuple tag `{}`", tag),
+                        ));
+                    }
+                };
+
+                LogicalReplicationMessage::Update(UpdateBody {
+                    rel_id,
+                    key_tuple,
+                    old_tuple,
+                    new_tuple,
+                })
+            }
+            DELETE_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let tag = buf.read_u8()?;
+
+                let mut key_tuple = None;
+                let mut old_tuple = None;
+
+                match tag {
+                    TUPLE_OLD_TAG => old_tuple = Some(Tuple::parse(&mut buf)?),
+                    TUPLE_KEY_TAG => key_tuple = Some(Tuple::parse(&mut buf)?),
+                    tag => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unknown tuple tag `{}`", tag),
+                        ));
+                    }
+                }
+
+                LogicalReplicationMessage::Delete(DeleteBody {
+                    rel_id,
+                    key_tuple,
+                    old_tuple,
+                })
+            }
+            TRUNCATE_TAG => {
+                let relation_len = buf.read_i32::<BigEndian>()?;
+                let options = buf.read_i8()?;
+
+                let capacity = count_capacity(relation_len, &buf, "relation")?;
+
+                let mut rel_ids = Vec::with_capacity(capacity);
+                for _ in 0..relation_len {
+                    rel_ids.push(buf.read_u32::<BigEndian>()?);
+                }
+
+                LogicalReplicationMessage::Truncate(TruncateBody { options, rel_ids })
+            }
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown logical replication message tag `{}`", tag),
+                ));
+            }
+        };
+
+        Ok(message)
+    }
+}
+
+/// A row as it appears in the replication stream.
+pub struct Tuple(Vec<TupleData>);
+
+impl Tuple {
+    #[inline]
+    pub fn tuple_data(&self) -> &[TupleData] {
+        &self.0
+    }
+
+    fn parse(buf: &mut Buffer) -> io::Result<Tuple> {
+        let col_len = buf.read_i16::<BigEndian>()?;
+        let capacity = count_capacity(i32::from(col_len), buf, "column")?;
+        let mut tuple = Vec::with_capacity(capacity);
+        for _ in 0..col_len {
+            tuple.push(TupleData::parse(buf)?);
+        }
+
+        Ok(Tuple(tuple))
+    }
+}
+
+/// A column of a relation as described by a `Relation` message.
+pub struct Column {
+    flags: i8,
+    name: Bytes,
+    type_id: Oid,
+    type_modifier: i32,
+}
+
+impl Column {
+    /// Flags for the column. Currently either 0 for no flags, or 1 if the column is part of the key.
+    #[inline]
+    pub fn flags(&self) -> i8 {
+        self.flags
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    #[inline]
+    pub fn type_id(&self) -> Oid {
+        self.type_id
+    }
+
+    #[inline]
+    pub fn type_modifier(&self) -> i32 {
+        self.type_modifier
+    }
+
+    fn parse(buf: &mut Buffer) -> io::Result<Column> {
+        Ok(Column {
+            flags: buf.read_i8()?,
+            name: buf.read_cstr()?,
+            type_id: buf.read_u32::<BigEndian>()?,
+            type_modifier: buf.read_i32::<BigEndian>()?,
+        })
+    }
+}
+
+/// The value of a single column of a `Tuple`.
+pub enum TupleData {
+    /// The value is NULL.
+    Null,
+    /// The value is an unchanged TOASTed value, which is not sent.
+    UnchangedToast,
+    /// The value in text format.
+    Text(Bytes),
+    /// The value in binary format.
+    Binary(Bytes),
+}
+
+impl TupleData {
+    fn parse(buf: &mut Buffer) -> io::Result<TupleData> {
+        let type_tag = buf.read_u8()?;
+
+        let tuple = match type_tag {
+            TUPLE_DATA_NULL_TAG => TupleData::Null,
+            TUPLE_DATA_TOAST_TAG => TupleData::UnchangedToast,
+            TUPLE_DATA_TEXT_TAG | TUPLE_DATA_BINARY_TAG => {
+                let len = buf.read_i32::<BigEndian>()?;
+                if len < 0 || buf.slice().len() < len as usize {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "unexpected EOF",
+                    ));
+                }
+                let start = buf.idx;
+                buf.idx += len as usize;
+                let data = buf.bytes.slice(start, buf.idx);
+                if type_tag == TUPLE_DATA_TEXT_TAG {
+                    TupleData::Text(data)
+                } else {
+                    TupleData::Binary(data)
+                }
+            }
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown tuple data tag `{}`", tag),
+                ));
+            }
+        };
+
+        Ok(tuple)
+    }
+}
+
+pub struct BeginBody {
+    final_lsn: u64,
+    timestamp: i64,
+    xid: u32,
+}
+
+impl BeginBody {
+    #[inline]
+    pub fn final_lsn(&self) -> u64 {
+        self.final_lsn
+    }
+
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    #[inline]
+    pub fn xid(&self) -> u32 {
+        self.xid
+    }
+}
+
+pub struct CommitBody {
+    flags: i8,
+    commit_lsn: u64,
+    end_lsn: u64,
+    timestamp: i64,
+}
+
+impl CommitBody {
+    #[inline]
+    pub fn flags(&self) -> i8 {
+        self.flags
+    }
+
+    #[inline]
+    pub fn commit_lsn(&self) -> u64 {
+        self.commit_lsn
+    }
+
+    #[inline]
+    pub fn end_lsn(&self) -> u64 {
+        self.end_lsn
+    }
+
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+}
+
+pub struct OriginBody {
+    commit_lsn: u64,
+    name: Bytes,
+}
+
+impl OriginBody {
+    #[inline]
+    pub fn commit_lsn(&self) -> u64 {
+        self.commit_lsn
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+/// The replica identity setting of a relation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReplicaIdentity {
+    /// The primary key columns, if any.
+    Default,
+    /// No columns.
+    Nothing,
+    /// All columns.
+    Full,
+    /// The columns of a specific index.
+    Index,
+}
+
+pub struct RelationBody {
+    rel_id: u32,
+    namespace: Bytes,
+    name: Bytes,
+    replica_identity: ReplicaIdentity,
+    columns: Vec<Column>,
+}
+
+impl RelationBody {
+    #[inline]
+    pub fn rel_id(&self) -> u32 {
+        self.rel_id
+    }
+
+    #[inline]
+    pub fn namespace(&self) -> io::Result<&str> {
+        get_str(&self.namespace)
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    #[inline]
+    pub fn replica_identity(&self) -> &ReplicaIdentity {
+        &self.replica_identity
+    }
+
+    #[inline]
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+}
+
+pub struct TypeBody {
+    id: Oid,
+    namespace: Bytes,
+    name: Bytes,
+}
+
+impl TypeBody {
+    #[inline]
+    pub fn id(&self) -> Oid {
+        self.id
+    }
+
+    #[inline]
+    pub fn namespace(&self) -> io::Result<&str> {
+        get_str(&self.namespace)
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+pub struct InsertBody {
+    rel_id: u32,
+    tuple: Tuple,
+}
+
+impl InsertBody {
+    #[inline]
+    pub fn rel_id(&self) -> u32 {
+        self.rel_id
+    }
+
+    #[inline]
+    pub fn tuple(&self) -> &Tuple {
+        &self.tuple
+    }
+}
+
+pub struct UpdateBody {
+    rel_id: u32,
+    old_tuple: Option<Tuple>,
+    key_tuple: Option<Tuple>,
+    new_tuple: Tuple,
+}
+
+impl UpdateBody {
+    #[inline]
+    pub fn rel_id(&self) -> u32 {
+        self.rel_id
+    }
+
+    /// The key columns of the old row, present if the key changed and the relation's replica identity is an index.
+    #[inline]
+    pub fn key_tuple(&self) -> Option<&Tuple> {
+        self.key_tuple.as_ref()
+    }
+
+    /// The entire old row, present if the relation's replica identity is `FULL`.
+    #[inline]
+    pub fn old_tuple(&self) -> Option<&Tuple> {
+        self.old_tuple.as_ref()
+    }
+
+    #[inline]
+    pub fn new_tuple(&self) -> &Tuple {
+        &self.new_tuple
+    }
+}
+
+pub struct DeleteBody {
+    rel_id: u32,
+    old_tuple: Option<Tuple>,
+    key_tuple: Option<Tuple>,
+}
+
+impl DeleteBody {
+    #[inline]
+    pub fn rel_id(&self) -> u32 {
+        self.rel_id
+    }
+
+    /// The key columns of the deleted row, present if the relation's replica identity is the default or an index.
+    #[inline]
+    pub fn key_tuple(&self) -> Option<&Tuple> {
+        self.key_tuple.as_ref()
+    }
+
+    /// The entire deleted row, present if the relation's replica identity is `FULL`.
+    #[inline]
+    pub fn old_tuple(&self) -> Option<&Tuple> {
+        self.old_tuple.as_ref()
+    }
+}
+
+pub struct TruncateBody {
+    options: i8,
+    rel_ids: Vec<u32>,
+}
+
+impl TruncateBody {
+    #[inline]
+    pub fn rel_ids(&self) -> &[u32] {
+        &self.rel_ids
+    }
+
+    /// Option bits for the truncation: 1 for `CASCADE`, 2 for `RESTART IDENTITY`.
+    #[inline]
+    pub fn options(&self) -> i8 {
+        self.options
+    }
+}
+
+pub const NEW_ARCHIVE_TAG: u8 = b'n';
+pub const MANIFEST_TAG: u8 = b'm';
+pub const ARCHIVE_DATA_TAG: u8 = b'd';
+pub const PROGRESS_TAG: u8 = b'p';
+
+/// A message sent by the server inside of a `CopyData` message while streaming a base backup.
+pub enum BaseBackupMessage {
+    /// The start of a new archive, whose contents are sent in subsequent `Data` messages.
+    NewArchive(NewArchiveBody),
+    /// The start of the backup manifest, whose contents are sent in subsequent `Data` messages.
+    Manifest,
+    Data(Bytes),
+    Progress(ProgressBody),
+    #[doc(hidden)]
+    __ForExtensibility,
+}
+
+impl BaseBackupMessage {
+    #[inline]
+    pub fn parse(buf: &Bytes) -> io::Result<BaseBackupMessage> {
+        let mut buf = Buffer {
+            bytes: buf.clone(),
+            idx: 0,
+        };
+
+        let tag = buf.read_u8()?;
+
+        let message = match tag {
+            NEW_ARCHIVE_TAG => {
+                let name = buf.read_cstr()?;
+                let location = buf.read_cstr()?;
+                BaseBackupMessage::NewArchive(NewArchiveBody { name, location })
+            }
+            MANIFEST_TAG => BaseBackupMessage::Manifest,
+            ARCHIVE_DATA_TAG => BaseBackupMessage::Data(buf.read_all()),
+            PROGRESS_TAG => {
+                let bytes_done = buf.read_u64::<BigEndian>()?;
+                BaseBackupMessage::Progress(ProgressBody { bytes_done })
+            }
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown base backup message tag `{}`", tag),
+                ));
+            }
+        };
+
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length",
+            ));
+        }
+
+        Ok(message)
+    }
+}
+
+pub struct NewArchiveBody {
+    name: Bytes,
+    location: Bytes,
+}
+
+impl NewArchiveBody {
+    /// The file name of the archive, e.g. `base.tar`.
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    /// The location of the tablespace stored in the archive, or an empty string for the main data directory.
+    #[inline]
+    pub fn location(&self) -> io::Result<&str> {
+        get_str(&self.location)
+    }
+}
+
+pub struct ProgressBody {
+    bytes_done: u64,
+}
+
+impl ProgressBody {
+    /// The number of bytes of the current tablespace processed so far.
+    #[inline]
+    pub fn bytes_done(&self) -> u64 {
+        self.bytes_done
+    }
+}
+
 #[inline]
 fn find_null(buf: &[u8], start: usize) -> io::Result<usize> {
     match memchr(0, &buf[start..]) {
@@ -815,7 +1610,94 @@ fn find_null(buf: &[u8], start: usize) -> io::Result<usize> {
     }
 }
 
+// Element counts come straight off the wire, so a bogus count must not be able to request an
+// arbitrarily large allocation. Every element takes at least one byte, so the remaining length of
+// the buffer bounds how many there can actually be.
+fn count_capacity(count: i32, buf: &Buffer, name: &str) -> io::Result<usize> {
+    if count < 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid {} count `{}`", name, count),
+        ));
+    }
+
+    Ok(cmp::min(count as usize, buf.slice().len()))
+}
+
 #[inline]
 fn get_str(buf: &[u8]) -> io::Result<&str> {
     str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn relation_message(column_len: i16) -> Bytes {
+        let mut buf = vec![RELATION_TAG];
+        buf.extend_from_slice(&1u32.to_be_bytes());
+        buf.extend_from_slice(b"public\0foo\0");
+        buf.push(REPLICA_IDENTITY_DEFAULT_TAG);
+        buf.extend_from_slice(&column_len.to_be_bytes());
+        buf.into()
+    }
+
+    #[test]
+    fn relation_negative_column_count() {
+        let err = match LogicalReplicationMessage::parse(&relation_message(-1)) {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => e,
+        };
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn relation_truncated_columns() {
+        let err = match LogicalReplicationMessage::parse(&relation_message(i16::MAX)) {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => e,
+        };
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn truncate_negative_relation_count() {
+        let mut buf = vec![TRUNCATE_TAG];
+        buf.extend_from_slice(&(-1i32).to_be_bytes());
+        buf.push(0);
+
+        let err = match LogicalReplicationMessage::parse(&buf.into()) {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => e,
+        };
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn truncate_huge_relation_count() {
+        let mut buf = vec![TRUNCATE_TAG];
+        buf.extend_from_slice(&i32::MAX.to_be_bytes());
+        buf.push(0);
+        buf.extend_from_slice(&1u32.to_be_bytes());
+
+        let err = match LogicalReplicationMessage::parse(&buf.into()) {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => e,
+        };
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn insert_negative_column_count() {
+        let mut buf = vec![INSERT_TAG];
+        buf.extend_from_slice(&1u32.to_be_bytes());
+        buf.push(TUPLE_NEW_TAG);
+        buf.extend_from_slice(&(-2i16).to_be_bytes());
+
+        let err = match LogicalReplicationMessage::parse(&buf.into()) {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => e,
+        };
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -332,6 +332,15 @@ pub fn sasl_initial_response(mechanism: &str, data: &[u8], buf: &mut Vec<u8>) ->
     })
 }
 
+#[inline]
+pub fn gss_response(data: &[u8], buf: &mut Vec<u8>) -> io::Result<()> {
+    buf.push(b'p');
+    write_body(buf, |buf| {
+        buf.extend_from_slice(data);
+        Ok(())
+    })
+}
+
 #[inline]
 pub fn sasl_response(data: &[u8], buf: &mut Vec<u8>) -> io::Result<()> {
     buf.push(b'p');
@@ -346,6 +355,28 @@ pub fn ssl_request(buf: &mut Vec<u8>) {
     write_body(buf, |buf| buf.write_i32::<BigEndian>(80_877_103)).unwrap();
 }
 
+/// Writes a standby status update, which is sent to the server as the contents of a `CopyData` message while
+/// streaming replication.
+///
+/// The timestamp is in microseconds since midnight on 2000-01-01. If `reply` is 1, the server will respond with a
+/// keepalive message immediately.
+#[inline]
+pub fn standby_status_update(
+    write_lsn: u64,
+    flush_lsn: u64,
+    apply_lsn: u64,
+    timestamp: i64,
+    reply: u8,
+    buf: &mut Vec<u8>,
+) {
+    buf.push(b'r');
+    buf.write_u64::<BigEndian>(write_lsn).unwrap();
+    buf.write_u64::<BigEndian>(flush_lsn).unwrap();
+    buf.write_u64::<BigEndian>(apply_lsn).unwrap();
+    buf.write_i64::<BigEndian>(timestamp).unwrap();
+    buf.push(reply);
+}
+
 #[inline]
 pub fn startup_message<'a, I>(parameters: I, buf: &mut Vec<u8>) -> io::Result<()>
 where
@@ -3,6 +3,8 @@ use byteorder::{BigEndian, ByteOrder, ReadBytesExt, WriteBytesExt};
 use fallible_iterator::FallibleIterator;
 use std::boxed::Box as StdBox;
 use std::error::Error;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str;
 
 use crate::{write_nullable, FromUsize, IsNull, Oid};
@@ -13,6 +15,16 @@ const RANGE_UPPER_INCLUSIVE: u8 = 0b0000_0100;
 const RANGE_LOWER_INCLUSIVE: u8 = 0b0000_0010;
 const RANGE_EMPTY: u8 = 0b0000_0001;
 
+const PGSQL_AF_INET: u8 = 2;
+const PGSQL_AF_INET6: u8 = 3;
+
+const NUMERIC_NBASE: i16 = 10000;
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
 /// Serializes a `BOOL` value.
 #[inline]
 pub fn bool_to_sql(v: bool, buf: &mut Vec<u8>) {
@@ -398,6 +410,57 @@ pub fn time_from_sql(mut buf: &[u8]) -> Result<i64, StdBox<dyn Error + Sync + Se
     Ok(v)
 }
 
+/// Serializes an `INTERVAL` value.
+#[inline]
+pub fn interval_to_sql(months: i32, days: i32, microseconds: i64, buf: &mut Vec<u8>) {
+    buf.write_i64::<BigEndian>(microseconds).unwrap();
+    buf.write_i32::<BigEndian>(days).unwrap();
+    buf.write_i32::<BigEndian>(months).unwrap();
+}
+
+/// Deserializes an `INTERVAL` value.
+#[inline]
+pub fn interval_from_sql(mut buf: &[u8]) -> Result<Interval, StdBox<dyn Error + Sync + Send>> {
+    let microseconds = buf.read_i64::<BigEndian>()?;
+    let days = buf.read_i32::<BigEndian>()?;
+    let months = buf.read_i32::<BigEndian>()?;
+    if !buf.is_empty() {
+        return Err("invalid message length".into());
+    }
+    Ok(Interval {
+        months,
+        days,
+        microseconds,
+    })
+}
+
+/// An `INTERVAL` value.
+pub struct Interval {
+    months: i32,
+    days: i32,
+    microseconds: i64,
+}
+
+impl Interval {
+    /// Returns the number of months in the interval.
+    #[inline]
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+
+    /// Returns the number of days in the interval.
+    #[inline]
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    /// Returns the number of microseconds in the interval.
+    #[inline]
+    pub fn microseconds(&self) -> i64 {
+        self.microseconds
+    }
+}
+
 /// Serializes a `MACADDR` value.
 #[inline]
 pub fn macaddr_to_sql(v: [u8; 6], buf: &mut Vec<u8>) {
@@ -432,6 +495,89 @@ pub fn uuid_from_sql(buf: &[u8]) -> Result<[u8; 16], StdBox<dyn Error + Sync + S
     Ok(out)
 }
 
+/// Serializes an `INET` or `CIDR` value.
+#[inline]
+pub fn inet_to_sql(addr: IpAddr, netmask: u8, buf: &mut Vec<u8>) {
+    let family = match addr {
+        IpAddr::V4(_) => PGSQL_AF_INET,
+        IpAddr::V6(_) => PGSQL_AF_INET6,
+    };
+    buf.push(family);
+    buf.push(netmask);
+    buf.push(0); // is_cidr is ignored by the server
+    match addr {
+        IpAddr::V4(addr) => {
+            buf.push(4);
+            buf.extend_from_slice(&addr.octets());
+        }
+        IpAddr::V6(addr) => {
+            buf.push(16);
+            buf.extend_from_slice(&addr.octets());
+        }
+    }
+}
+
+/// Deserializes an `INET` or `CIDR` value.
+#[inline]
+pub fn inet_from_sql(mut buf: &[u8]) -> Result<Inet, StdBox<dyn Error + Sync + Send>> {
+    let family = buf.read_u8()?;
+    let netmask = buf.read_u8()?;
+    buf.read_u8()?; // is_cidr
+    let len = buf.read_u8()?;
+
+    let addr = match family {
+        PGSQL_AF_INET => {
+            if netmask > 32 {
+                return Err("invalid IPv4 netmask".into());
+            }
+            if len != 4 {
+                return Err("invalid IPv4 address length".into());
+            }
+            let mut addr = [0; 4];
+            buf.read_exact(&mut addr)?;
+            IpAddr::V4(Ipv4Addr::from(addr))
+        }
+        PGSQL_AF_INET6 => {
+            if netmask > 128 {
+                return Err("invalid IPv6 netmask".into());
+            }
+            if len != 16 {
+                return Err("invalid IPv6 address length".into());
+            }
+            let mut addr = [0; 16];
+            buf.read_exact(&mut addr)?;
+            IpAddr::V6(Ipv6Addr::from(addr))
+        }
+        _ => return Err("invalid IP family".into()),
+    };
+
+    if !buf.is_empty() {
+        return Err("invalid buffer size".into());
+    }
+
+    Ok(Inet { addr, netmask })
+}
+
+/// An `INET` or `CIDR` value.
+pub struct Inet {
+    addr: IpAddr,
+    netmask: u8,
+}
+
+impl Inet {
+    /// Returns the IP address.
+    #[inline]
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the netmask, as a number of bits.
+    #[inline]
+    pub fn netmask(&self) -> u8 {
+        self.netmask
+    }
+}
+
 /// Serializes an array value.
 #[inline]
 pub fn array_to_sql<T, I, J, F>(
@@ -954,6 +1100,191 @@ impl<'a> FallibleIterator for PathPoints<'a> {
     }
 }
 
+/// The sign of a `NUMERIC` value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NumericSign {
+    /// A positive value, or zero.
+    Positive,
+    /// A negative value.
+    Negative,
+    /// Not a number.
+    NaN,
+    /// Positive infinity.
+    ///
+    /// Infinite values are only supported by Postgres 14 and newer.
+    PositiveInfinity,
+    /// Negative infinity.
+    ///
+    /// Infinite values are only supported by Postgres 14 and newer.
+    NegativeInfinity,
+}
+
+impl NumericSign {
+    fn to_raw(self) -> u16 {
+        match self {
+            NumericSign::Positive => NUMERIC_POS,
+            NumericSign::Negative => NUMERIC_NEG,
+            NumericSign::NaN => NUMERIC_NAN,
+            NumericSign::PositiveInfinity => NUMERIC_PINF,
+            NumericSign::NegativeInfinity => NUMERIC_NINF,
+        }
+    }
+
+    fn from_raw(raw: u16) -> Option<NumericSign> {
+        match raw {
+            NUMERIC_POS => Some(NumericSign::Positive),
+            NUMERIC_NEG => Some(NumericSign::Negative),
+            NUMERIC_NAN => Some(NumericSign::NaN),
+            NUMERIC_PINF => Some(NumericSign::PositiveInfinity),
+            NUMERIC_NINF => Some(NumericSign::NegativeInfinity),
+            _ => None,
+        }
+    }
+
+    /// Determines if the sign represents a finite value.
+    #[inline]
+    pub fn is_finite(self) -> bool {
+        self == NumericSign::Positive || self == NumericSign::Negative
+    }
+}
+
+/// Serializes a `NUMERIC` value.
+///
+/// The value is `sum(digits[i] * 10000 ^ (weight - i))`, where each digit is in the range
+/// `0..10000`. `scale` is the number of decimal digits after the decimal point to display. For
+/// non-finite values, `weight` and `scale` should be 0 and `digits` should be empty.
+#[inline]
+pub fn numeric_to_sql<I>(
+    sign: NumericSign,
+    weight: i16,
+    scale: u16,
+    digits: I,
+    buf: &mut Vec<u8>,
+) -> Result<(), StdBox<dyn Error + Sync + Send>>
+where
+    I: IntoIterator<Item = i16>,
+{
+    let base = buf.len();
+    buf.extend_from_slice(&[0; 2]);
+    buf.write_i16::<BigEndian>(weight).unwrap();
+    buf.write_u16::<BigEndian>(sign.to_raw()).unwrap();
+    buf.write_u16::<BigEndian>(scale).unwrap();
+
+    let mut count = 0;
+    for digit in digits {
+        if !(0..NUMERIC_NBASE).contains(&digit) {
+            return Err("invalid numeric digit".into());
+        }
+        count += 1;
+        buf.write_i16::<BigEndian>(digit).unwrap();
+    }
+
+    if count > 0 && !sign.is_finite() {
+        return Err("non-finite numeric values cannot have digits".into());
+    }
+
+    let count = i16::from_usize(count)?;
+    BigEndian::write_i16(&mut buf[base..], count);
+
+    Ok(())
+}
+
+/// Deserializes a `NUMERIC` value.
+#[inline]
+pub fn numeric_from_sql<'a>(
+    mut buf: &'a [u8],
+) -> Result<Numeric<'a>, StdBox<dyn Error + Sync + Send>> {
+    let count = buf.read_i16::<BigEndian>()?;
+    let weight = buf.read_i16::<BigEndian>()?;
+    let sign = buf.read_u16::<BigEndian>()?;
+    let scale = buf.read_u16::<BigEndian>()?;
+
+    let sign = match NumericSign::from_raw(sign) {
+        Some(sign) => sign,
+        None => return Err("invalid numeric sign".into()),
+    };
+
+    if count < 0 || buf.len() != count as usize * 2 {
+        return Err("invalid message length".into());
+    }
+    if !sign.is_finite() && count != 0 {
+        return Err("invalid message length".into());
+    }
+
+    let mut digits = buf;
+    while !digits.is_empty() {
+        let digit = digits.read_i16::<BigEndian>()?;
+        if !(0..NUMERIC_NBASE).contains(&digit) {
+            return Err("invalid numeric digit".into());
+        }
+    }
+
+    Ok(Numeric {
+        sign,
+        weight,
+        scale,
+        digits: buf,
+    })
+}
+
+/// A `NUMERIC` value.
+pub struct Numeric<'a> {
+    sign: NumericSign,
+    weight: i16,
+    scale: u16,
+    digits: &'a [u8],
+}
+
+impl<'a> Numeric<'a> {
+    /// Returns the sign of the value.
+    #[inline]
+    pub fn sign(&self) -> NumericSign {
+        self.sign
+    }
+
+    /// Returns the weight of the first base-10000 digit.
+    #[inline]
+    pub fn weight(&self) -> i16 {
+        self.weight
+    }
+
+    /// Returns the number of decimal digits after the decimal point.
+    #[inline]
+    pub fn scale(&self) -> u16 {
+        self.scale
+    }
+
+    /// Returns an iterator over the base-10000 digits of the value, most significant first.
+    #[inline]
+    pub fn digits(&self) -> NumericDigits<'a> {
+        NumericDigits(self.digits)
+    }
+}
+
+/// An iterator over the base-10000 digits of a `NUMERIC` value.
+pub struct NumericDigits<'a>(&'a [u8]);
+
+impl<'a> Iterator for NumericDigits<'a> {
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        if self.0.is_empty() {
+            return None;
+        }
+        // the digits were validated in numeric_from_sql
+        self.0.read_i16::<BigEndian>().ok()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len() / 2;
+        (len, Some(len))
+    }
+}
+
+impl<'a> ExactSizeIterator for NumericDigits<'a> {}
+
 #[cfg(test)]
 mod test {
     use fallible_iterator::FallibleIterator;
@@ -1112,4 +1443,61 @@ mod test {
         assert_eq!(array.dimensions().collect::<Vec<_>>().unwrap(), dimensions);
         assert_eq!(array.values().collect::<Vec<_>>().unwrap(), values);
     }
+
+    #[test]
+    fn numeric() {
+        let mut buf = vec![];
+        numeric_to_sql(NumericSign::Negative, 1, 3, vec![12, 3456, 7000], &mut buf).unwrap();
+        let numeric = numeric_from_sql(&buf).unwrap();
+        assert_eq!(numeric.sign(), NumericSign::Negative);
+        assert_eq!(numeric.weight(), 1);
+        assert_eq!(numeric.scale(), 3);
+        assert_eq!(numeric.digits().collect::<Vec<_>>(), [12, 3456, 7000]);
+    }
+
+    #[test]
+    fn numeric_special() {
+        for &sign in &[
+            NumericSign::NaN,
+            NumericSign::PositiveInfinity,
+            NumericSign::NegativeInfinity,
+        ] {
+            let mut buf = vec![];
+            numeric_to_sql(sign, 0, 0, None, &mut buf).unwrap();
+            let numeric = numeric_from_sql(&buf).unwrap();
+            assert_eq!(numeric.sign(), sign);
+            assert_eq!(numeric.digits().count(), 0);
+        }
+    }
+
+    #[test]
+    fn numeric_invalid_digit() {
+        let mut buf = vec![];
+        assert!(numeric_to_sql(NumericSign::Positive, 0, 0, Some(10000), &mut buf).is_err());
+    }
+
+    #[test]
+    fn inet() {
+        let mut buf = vec![];
+        inet_to_sql("192.168.0.0".parse().unwrap(), 16, &mut buf);
+        let inet = inet_from_sql(&buf).unwrap();
+        assert_eq!(inet.addr(), "192.168.0.0".parse::<IpAddr>().unwrap());
+        assert_eq!(inet.netmask(), 16);
+
+        let mut buf = vec![];
+        inet_to_sql("2001:db8::1".parse().unwrap(), 128, &mut buf);
+        let inet = inet_from_sql(&buf).unwrap();
+        assert_eq!(inet.addr(), "2001:db8::1".parse::<IpAddr>().unwrap());
+        assert_eq!(inet.netmask(), 128);
+    }
+
+    #[test]
+    fn interval() {
+        let mut buf = vec![];
+        interval_to_sql(14, -3, 3_600_000_001, &mut buf);
+        let interval = interval_from_sql(&buf).unwrap();
+        assert_eq!(interval.months(), 14);
+        assert_eq!(interval.days(), -3);
+        assert_eq!(interval.microseconds(), 3_600_000_001);
+    }
 }
@@ -1,17 +1,35 @@
 use futures::{Async, Future, Poll, Stream};
 use std::io::{self, Read};
+use std::sync::Arc;
 use tokio_postgres::types::{ToSql, Type};
-use tokio_postgres::Error;
+use tokio_postgres::{Error, Overflow};
 #[cfg(feature = "runtime")]
 use tokio_postgres::{MakeTlsMode, Socket, TlsMode};
 
+use crate::notifications;
 #[cfg(feature = "runtime")]
 use crate::Config;
-use crate::{CopyOutReader, Query, Statement, ToStatement, Transaction};
-
-pub struct Client(tokio_postgres::Client);
+use crate::{
+    CopyOutReader, Notifications, Pipeline, Query, Statement, ToStatement, Transaction,
+    TransactionBuilder,
+};
+
+pub struct Client {
+    client: tokio_postgres::Client,
+    notifications: Arc<notifications::Shared>,
+}
 
 impl Client {
+    pub(crate) fn new(
+        client: tokio_postgres::Client,
+        notifications: Arc<notifications::Shared>,
+    ) -> Client {
+        Client {
+            client,
+            notifications,
+        }
+    }
+
     #[cfg(feature = "runtime")]
     pub fn connect<T>(params: &str, tls_mode: T) -> Result<Client, Error>
     where
@@ -29,11 +47,48 @@ impl Client {
     }
 
     pub fn prepare(&mut self, query: &str) -> Result<Statement, Error> {
-        self.0.prepare(query).wait().map(Statement)
+        self.client.prepare(query).wait().map(Statement)
     }
 
     pub fn prepare_typed(&mut self, query: &str, types: &[Type]) -> Result<Statement, Error> {
-        self.0.prepare_typed(query, types).wait().map(Statement)
+        self.client
+            .prepare_typed(query, types)
+            .wait()
+            .map(Statement)
+    }
+
+    pub fn prepare_cached(&mut self, query: &str) -> Result<Statement, Error> {
+        self.client.prepare_cached(query).wait().map(Statement)
+    }
+
+    pub fn prepare_typed_cached(
+        &mut self,
+        query: &str,
+        types: &[Type],
+    ) -> Result<Statement, Error> {
+        self.client
+            .prepare_typed_cached(query, types)
+            .wait()
+            .map(Statement)
+    }
+
+    pub fn set_statement_cache_capacity(&mut self, capacity: usize) {
+        self.client.set_statement_cache_capacity(capacity);
+    }
+
+    pub fn clear_statement_cache(&mut self) {
+        self.client.clear_statement_cache();
+    }
+
+    #[cfg(feature = "runtime")]
+    pub fn set_query_timeout_tls<T>(&mut self, tls_mode: T)
+    where
+        T: MakeTlsMode<Socket> + Clone + 'static + Send,
+        T::TlsMode: Send,
+        T::Stream: Send,
+        <T::TlsMode as TlsMode<Socket>>::Future: Send,
+    {
+        self.client.set_query_timeout_tls(tls_mode);
     }
 
     pub fn execute<T>(&mut self, query: &T, params: &[&dyn ToSql]) -> Result<u64, Error>
@@ -41,7 +96,7 @@ impl Client {
         T: ?Sized + ToStatement,
     {
         let statement = query.__statement(self)?;
-        self.0.execute(&statement.0, params).wait()
+        self.client.execute(&statement.0, params).wait()
     }
 
     pub fn query<T>(&mut self, query: &T, params: &[&dyn ToSql]) -> Result<Query<'_>, Error>
@@ -49,7 +104,7 @@ impl Client {
         T: ?Sized + ToStatement,
     {
         let statement = query.__statement(self)?;
-        Ok(Query::new(self.0.query(&statement.0, params)))
+        Ok(Query::new(self.client.query(&statement.0, params)))
     }
 
     pub fn copy_in<T, R>(
@@ -63,7 +118,7 @@ impl Client {
         R: Read,
     {
         let statement = query.__statement(self)?;
-        self.0
+        self.client
             .copy_in(&statement.0, params, CopyInStream(reader))
             .wait()
     }
@@ -77,12 +132,16 @@ impl Client {
         T: ?Sized + ToStatement,
     {
         let statement = query.__statement(self)?;
-        let stream = self.0.copy_out(&statement.0, params);
+        let stream = self.client.copy_out(&statement.0, params);
         CopyOutReader::new(stream)
     }
 
     pub fn batch_execute(&mut self, query: &str) -> Result<(), Error> {
-        self.0.batch_execute(query).wait()
+        self.client.batch_execute(query).wait()
+    }
+
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline::new(self)
     }
 
     pub fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
@@ -90,26 +149,37 @@ impl Client {
         Ok(Transaction::new(self))
     }
 
+    /// Returns a builder for a transaction with custom settings.
+    pub fn build_transaction(&mut self) -> TransactionBuilder<'_> {
+        TransactionBuilder::new(self)
+    }
+
+    pub fn notifications(&mut self) -> Notifications<'_> {
+        Notifications::new(&self.notifications)
+    }
+
     pub fn is_closed(&self) -> bool {
-        self.0.is_closed()
+        self.client.is_closed()
     }
 
     pub fn get_ref(&self) -> &tokio_postgres::Client {
-        &self.0
+        &self.client
     }
 
     pub fn get_mut(&mut self) -> &mut tokio_postgres::Client {
-        &mut self.0
+        &mut self.client
     }
 
     pub fn into_inner(self) -> tokio_postgres::Client {
-        self.0
+        self.client
     }
 }
 
 impl From<tokio_postgres::Client> for Client {
     fn from(c: tokio_postgres::Client) -> Client {
-        Client(c)
+        // the sender is dropped, so there are never any notifications
+        let (_, notifications) = notifications::channel(1, Overflow::DropNewest);
+        Client::new(c, notifications)
     }
 }
 
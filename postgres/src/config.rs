@@ -1,29 +1,102 @@
 use futures::sync::oneshot;
-use futures::Future;
-use log::error;
+use futures::{future, Async, Future};
+use log::{error, info};
+use std::fmt;
+use std::net::IpAddr;
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio_postgres::{Error, MakeTlsMode, Socket, TargetSessionAttrs, TlsMode};
+use tokio_postgres::error::DbError;
+use tokio_postgres::{
+    AsyncMessage, AuthMethod, ChannelBindingMode, Error, LoadBalanceHosts, MakeTlsMode, Overflow,
+    ReplicationMode, Socket, SslMode, TargetSessionAttrs, TlsMode,
+};
 
+use crate::notifications;
 use crate::{Client, RUNTIME};
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Config(tokio_postgres::Config);
+#[derive(Clone)]
+pub struct Config {
+    pub(crate) config: tokio_postgres::Config,
+    notice_callback: Arc<dyn Fn(DbError) + Send + Sync>,
+    notification_capacity: usize,
+    notification_overflow: Overflow,
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Config")
+            .field("config", &self.config)
+            .field("notification_capacity", &self.notification_capacity)
+            .field("notification_overflow", &self.notification_overflow)
+            .finish()
+    }
+}
+
+impl PartialEq for Config {
+    fn eq(&self, other: &Config) -> bool {
+        self.config == other.config
+            && self.notification_capacity == other.notification_capacity
+            && self.notification_overflow == other.notification_overflow
+    }
+}
 
 impl Default for Config {
     fn default() -> Config {
-        Config(tokio_postgres::Config::default())
+        Config::new()
+    }
+}
+
+impl From<tokio_postgres::Config> for Config {
+    fn from(config: tokio_postgres::Config) -> Config {
+        Config {
+            config,
+            notice_callback: Arc::new(|notice| {
+                info!("{}: {}", notice.severity(), notice.message());
+            }),
+            notification_capacity: 1024,
+            notification_overflow: Overflow::DropOldest,
+        }
     }
 }
 
 impl Config {
     pub fn new() -> Config {
-        Config(tokio_postgres::Config::new())
+        Config::from(tokio_postgres::Config::new())
+    }
+
+    pub fn channel_binding(&mut self, channel_binding: ChannelBindingMode) -> &mut Config {
+        self.config.channel_binding(channel_binding);
+        self
+    }
+
+    pub fn require_auth(&mut self, require_auth: &[AuthMethod]) -> &mut Config {
+        self.config.require_auth(require_auth);
+        self
+    }
+
+    pub fn krb_srvname(&mut self, krb_srvname: &str) -> &mut Config {
+        self.config.krb_srvname(krb_srvname);
+        self
+    }
+
+    pub fn service(&mut self, service: &str) -> Result<&mut Config, Error> {
+        self.config.service(service)?;
+        Ok(self)
+    }
+
+    pub fn from_env() -> Result<Config, Error> {
+        tokio_postgres::Config::from_env().map(Config::from)
+    }
+
+    pub fn merge_env(&mut self) -> Result<&mut Config, Error> {
+        self.config.merge_env()?;
+        Ok(self)
     }
 
     pub fn user(&mut self, user: &str) -> &mut Config {
-        self.0.user(user);
+        self.config.user(user);
         self
     }
 
@@ -31,27 +104,27 @@ impl Config {
     where
         T: AsRef<[u8]>,
     {
-        self.0.password(password);
+        self.config.password(password);
         self
     }
 
     pub fn dbname(&mut self, dbname: &str) -> &mut Config {
-        self.0.dbname(dbname);
+        self.config.dbname(dbname);
         self
     }
 
     pub fn options(&mut self, options: &str) -> &mut Config {
-        self.0.options(options);
+        self.config.options(options);
         self
     }
 
     pub fn application_name(&mut self, application_name: &str) -> &mut Config {
-        self.0.application_name(application_name);
+        self.config.application_name(application_name);
         self
     }
 
     pub fn host(&mut self, host: &str) -> &mut Config {
-        self.0.host(host);
+        self.config.host(host);
         self
     }
 
@@ -60,27 +133,52 @@ impl Config {
     where
         T: AsRef<Path>,
     {
-        self.0.host_path(host);
+        self.config.host_path(host);
+        self
+    }
+
+    pub fn hostaddr(&mut self, hostaddr: IpAddr) -> &mut Config {
+        self.config.hostaddr(hostaddr);
         self
     }
 
     pub fn port(&mut self, port: u16) -> &mut Config {
-        self.0.port(port);
+        self.config.port(port);
         self
     }
 
     pub fn connect_timeout(&mut self, connect_timeout: Duration) -> &mut Config {
-        self.0.connect_timeout(connect_timeout);
+        self.config.connect_timeout(connect_timeout);
+        self
+    }
+
+    pub fn query_timeout(&mut self, query_timeout: Duration) -> &mut Config {
+        self.config.query_timeout(query_timeout);
         self
     }
 
     pub fn keepalives(&mut self, keepalives: bool) -> &mut Config {
-        self.0.keepalives(keepalives);
+        self.config.keepalives(keepalives);
         self
     }
 
     pub fn keepalives_idle(&mut self, keepalives_idle: Duration) -> &mut Config {
-        self.0.keepalives_idle(keepalives_idle);
+        self.config.keepalives_idle(keepalives_idle);
+        self
+    }
+
+    pub fn keepalives_interval(&mut self, keepalives_interval: Duration) -> &mut Config {
+        self.config.keepalives_interval(keepalives_interval);
+        self
+    }
+
+    pub fn keepalives_retries(&mut self, keepalives_retries: u32) -> &mut Config {
+        self.config.keepalives_retries(keepalives_retries);
+        self
+    }
+
+    pub fn tcp_user_timeout(&mut self, tcp_user_timeout: Duration) -> &mut Config {
+        self.config.tcp_user_timeout(tcp_user_timeout);
         self
     }
 
@@ -88,7 +186,98 @@ impl Config {
         &mut self,
         target_session_attrs: TargetSessionAttrs,
     ) -> &mut Config {
-        self.0.target_session_attrs(target_session_attrs);
+        self.config.target_session_attrs(target_session_attrs);
+        self
+    }
+
+    pub fn load_balance_hosts(&mut self, load_balance_hosts: LoadBalanceHosts) -> &mut Config {
+        self.config.load_balance_hosts(load_balance_hosts);
+        self
+    }
+
+    pub fn passfile<T>(&mut self, passfile: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.passfile(passfile);
+        self
+    }
+
+    pub fn replication_mode(&mut self, replication_mode: ReplicationMode) -> &mut Config {
+        self.config.replication_mode(replication_mode);
+        self
+    }
+
+    pub fn ssl_mode(&mut self, ssl_mode: SslMode) -> &mut Config {
+        self.config.ssl_mode(ssl_mode);
+        self
+    }
+
+    pub fn ssl_root_cert<T>(&mut self, ssl_root_cert: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.ssl_root_cert(ssl_root_cert);
+        self
+    }
+
+    pub fn ssl_cert<T>(&mut self, ssl_cert: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.ssl_cert(ssl_cert);
+        self
+    }
+
+    pub fn ssl_key<T>(&mut self, ssl_key: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.ssl_key(ssl_key);
+        self
+    }
+
+    pub fn ssl_crl<T>(&mut self, ssl_crl: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.ssl_crl(ssl_crl);
+        self
+    }
+
+    pub fn ssl_password<T>(&mut self, ssl_password: T) -> &mut Config
+    where
+        T: AsRef<[u8]>,
+    {
+        self.config.ssl_password(ssl_password);
+        self
+    }
+
+    /// Sets the function called with each notice the server sends on connections established by `connect`.
+    ///
+    /// Notices are logged at the `info` level by default.
+    pub fn notice_callback<F>(&mut self, f: F) -> &mut Config
+    where
+        F: Fn(DbError) + Send + Sync + 'static,
+    {
+        self.notice_callback = Arc::new(f);
+        self
+    }
+
+    /// Sets the size of the buffer holding notifications until they're retrieved with `Client::notifications`, on
+    /// connections established by `connect`.
+    ///
+    /// `overflow` determines what happens once the buffer is full. With `Overflow::Wait`, the connection stops reading
+    /// from the server, so queries on the client stall until notifications are retrieved. Defaults to 1024
+    /// notifications, discarding the oldest one when the buffer is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn notification_buffer(&mut self, capacity: usize, overflow: Overflow) -> &mut Config {
+        assert!(capacity > 0, "notification buffer capacity must be nonzero");
+        self.notification_capacity = capacity;
+        self.notification_overflow = overflow;
         self
     }
 
@@ -99,12 +288,36 @@ impl Config {
         T::Stream: Send,
         <T::TlsMode as TlsMode<Socket>>::Future: Send,
     {
-        let connect = self.0.connect(tls_mode);
-        let (client, connection) = oneshot::spawn(connect, &RUNTIME.executor()).wait()?;
-        let connection = connection.map_err(|e| error!("postgres connection error: {}", e));
+        let connect = self.config.connect(tls_mode);
+        let (client, mut connection) = oneshot::spawn(connect, &RUNTIME.executor()).wait()?;
+
+        // the connection is driven in the background, so asynchronous messages are handed off to the client
+        let (mut sender, notifications) =
+            notifications::channel(self.notification_capacity, self.notification_overflow);
+        let notice_callback = self.notice_callback.clone();
+        let connection = future::poll_fn(move || loop {
+            if let Async::NotReady = sender.poll_ready() {
+                return Ok(Async::NotReady);
+            }
+
+            match connection.poll_message() {
+                Ok(Async::Ready(Some(AsyncMessage::Notice(notice)))) => notice_callback(notice),
+                Ok(Async::Ready(Some(AsyncMessage::Notification(notification)))) => {
+                    sender.send(notification);
+                }
+                Ok(Async::Ready(Some(_))) => {}
+                Ok(Async::Ready(None)) => return Ok(Async::Ready(())),
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    error!("postgres connection error: {}", e);
+                    sender.send_error(e);
+                    return Err(());
+                }
+            }
+        });
         RUNTIME.executor().spawn(connection);
 
-        Ok(Client::from(client))
+        Ok(Client::new(client, notifications))
     }
 }
 
@@ -112,6 +325,6 @@ impl FromStr for Config {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Config, Error> {
-        s.parse().map(Config)
+        s.parse::<tokio_postgres::Config>().map(Config::from)
     }
 }
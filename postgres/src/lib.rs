@@ -7,12 +7,17 @@ mod client;
 #[cfg(feature = "runtime")]
 mod config;
 mod copy_out_reader;
+pub mod notifications;
+mod pipeline;
+#[cfg(feature = "runtime")]
+pub mod pool;
 mod portal;
 mod query;
 mod query_portal;
 mod statement;
 mod to_statement;
 mod transaction;
+mod transaction_builder;
 
 #[cfg(feature = "runtime")]
 #[cfg(test)]
@@ -22,12 +27,25 @@ pub use crate::client::*;
 #[cfg(feature = "runtime")]
 pub use crate::config::*;
 pub use crate::copy_out_reader::*;
+pub use crate::notifications::Notifications;
+pub use crate::pipeline::*;
 pub use crate::portal::*;
 pub use crate::query::*;
 pub use crate::query_portal::*;
 pub use crate::statement::*;
 pub use crate::to_statement::*;
 pub use crate::transaction::*;
+pub use crate::transaction_builder::*;
+/// The `tokio-postgres` crate this crate is built on.
+///
+/// Derived implementations can refer to it with `#[postgres(crate = "postgres::tokio_postgres")]`,
+/// so that crates using the derives don't need their own dependency on `tokio-postgres`.
+#[cfg(feature = "derive")]
+pub use tokio_postgres;
+#[cfg(feature = "derive")]
+pub use tokio_postgres::types::{FromSql, ToSql};
+#[cfg(feature = "derive")]
+pub use tokio_postgres::FromRow;
 
 #[cfg(feature = "runtime")]
 lazy_static! {
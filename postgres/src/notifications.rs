@@ -0,0 +1,205 @@
+//! Asynchronous notifications.
+
+use fallible_iterator::FallibleIterator;
+use futures::task::AtomicTask;
+use futures::Async;
+use std::sync::mpsc::{self, RecvTimeoutError, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio_postgres::{Error, Notification, Overflow};
+
+// The receiving half of the buffer, which is shared with the connection so it can discard old notifications.
+pub(crate) struct Shared {
+    receiver: Mutex<mpsc::Receiver<Result<Notification, Error>>>,
+    // the connection may be waiting for room in the buffer, so it's notified when a notification is retrieved
+    task: AtomicTask,
+}
+
+impl Shared {
+    fn receive<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mpsc::Receiver<Result<Notification, Error>>) -> T,
+    {
+        let r = f(&lock(&self.receiver));
+        self.task.notify();
+        r
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(e) => e.into_inner(),
+    }
+}
+
+pub(crate) fn channel(capacity: usize, overflow: Overflow) -> (Sender, Arc<Shared>) {
+    let (sender, receiver) = mpsc::sync_channel(capacity);
+    let shared = Arc::new(Shared {
+        receiver: Mutex::new(receiver),
+        task: AtomicTask::new(),
+    });
+    let sender = Sender {
+        sender,
+        shared: shared.clone(),
+        overflow,
+        pending: None,
+    };
+    (sender, shared)
+}
+
+// The sending half of the buffer, used by the connection.
+pub(crate) struct Sender {
+    sender: mpsc::SyncSender<Result<Notification, Error>>,
+    shared: Arc<Shared>,
+    overflow: Overflow,
+    pending: Option<Notification>,
+}
+
+impl Sender {
+    // returns `NotReady` while a notification is waiting for room in the buffer
+    pub(crate) fn poll_ready(&mut self) -> Async<()> {
+        if let Some(notification) = self.pending.take() {
+            self.send(notification);
+        }
+
+        if self.pending.is_some() {
+            Async::NotReady
+        } else {
+            Async::Ready(())
+        }
+    }
+
+    pub(crate) fn send(&mut self, notification: Notification) {
+        let notification = match self.sender.try_send(Ok(notification)) {
+            Err(TrySendError::Full(Ok(notification))) => notification,
+            _ => return,
+        };
+
+        match self.overflow {
+            Overflow::DropOldest => {
+                self.discard_oldest();
+                let _ = self.sender.try_send(Ok(notification));
+            }
+            Overflow::DropNewest => {}
+            Overflow::Wait | Overflow::__NonExhaustive => {
+                // register before retrying so a notification retrieved in the meantime isn't missed
+                self.shared.task.register();
+                if let Err(TrySendError::Full(Ok(notification))) =
+                    self.sender.try_send(Ok(notification))
+                {
+                    self.pending = Some(notification);
+                }
+            }
+        }
+    }
+
+    // errors end the connection, so they're delivered regardless of the overflow behavior
+    pub(crate) fn send_error(&mut self, error: Error) {
+        if let Err(TrySendError::Full(error)) = self.sender.try_send(Err(error)) {
+            self.discard_oldest();
+            let _ = self.sender.try_send(error);
+        }
+    }
+
+    fn discard_oldest(&self) {
+        // the buffer is full, so a receiver holding the lock won't block
+        let _ = lock(&self.shared.receiver).try_recv();
+    }
+}
+
+/// Notifications sent to a client with the `NOTIFY` command.
+///
+/// The client receives notifications for the channels it is subscribed to with the `LISTEN` command. They are buffered
+/// until retrieved through one of the iterators, up to the limit set with `Config::notification_buffer`. Only clients
+/// connected with `Config::connect` receive notifications.
+pub struct Notifications<'a> {
+    shared: &'a Shared,
+}
+
+impl<'a> Notifications<'a> {
+    pub(crate) fn new(shared: &'a Shared) -> Notifications<'a> {
+        Notifications { shared }
+    }
+
+    /// Returns a blocking iterator over notifications.
+    ///
+    /// The iterator waits for each notification to arrive, and ends once the connection is closed.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            shared: self.shared,
+        }
+    }
+
+    /// Returns an iterator over notifications which waits up to `timeout` for each one to arrive.
+    ///
+    /// The iterator ends if no notification arrives in time, or once the connection is closed.
+    pub fn timeout_iter(&self, timeout: Duration) -> TimeoutIter<'_> {
+        TimeoutIter {
+            shared: self.shared,
+            timeout,
+        }
+    }
+
+    /// Returns a nonblocking iterator over notifications.
+    ///
+    /// The iterator ends once the notifications which have already arrived have been returned.
+    pub fn try_iter(&self) -> TryIter<'_> {
+        TryIter {
+            shared: self.shared,
+        }
+    }
+}
+
+/// A blocking iterator over pending notifications.
+pub struct Iter<'a> {
+    shared: &'a Shared,
+}
+
+impl<'a> FallibleIterator for Iter<'a> {
+    type Item = Notification;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Notification>, Error> {
+        match self.shared.receive(|r| r.recv()) {
+            Ok(notification) => notification.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// An iterator over pending notifications which waits a limited time for each one.
+pub struct TimeoutIter<'a> {
+    shared: &'a Shared,
+    timeout: Duration,
+}
+
+impl<'a> FallibleIterator for TimeoutIter<'a> {
+    type Item = Notification;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Notification>, Error> {
+        let timeout = self.timeout;
+        match self.shared.receive(|r| r.recv_timeout(timeout)) {
+            Ok(notification) => notification.map(Some),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => Ok(None),
+        }
+    }
+}
+
+/// A nonblocking iterator over pending notifications.
+pub struct TryIter<'a> {
+    shared: &'a Shared,
+}
+
+impl<'a> FallibleIterator for TryIter<'a> {
+    type Item = Notification;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Notification>, Error> {
+        match self.shared.receive(|r| r.try_recv()) {
+            Ok(notification) => notification.map(Some),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use futures::{Future, Stream};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Error, PipelineResult};
+
+use crate::{Client, ToStatement};
+
+pub struct Pipeline<'a> {
+    client: &'a mut Client,
+    pipeline: tokio_postgres::Pipeline,
+}
+
+impl<'a> Pipeline<'a> {
+    pub(crate) fn new(client: &'a mut Client) -> Pipeline<'a> {
+        let pipeline = client.get_mut().pipeline();
+        Pipeline { client, pipeline }
+    }
+
+    pub fn add<T>(&mut self, query: &T, params: &[&dyn ToSql]) -> Result<&mut Pipeline<'a>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        // pipelines are typically run repeatedly, so avoid preparing their statements every time
+        let statement = query.__cached_statement(self.client)?;
+        self.pipeline.add(&statement.0, params);
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pipeline.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipeline.is_empty()
+    }
+
+    pub fn run(self) -> Result<Vec<PipelineResult>, Error> {
+        self.pipeline.run().collect().wait()
+    }
+}
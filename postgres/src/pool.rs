@@ -0,0 +1,175 @@
+//! A pool of database connections.
+//!
+//! This is a blocking wrapper over `tokio_postgres::pool`. Connections are driven by the same background runtime
+//! used by `Client::connect`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use postgres::pool::Builder;
+//! use tokio_postgres::NoTls;
+//!
+//! let config = "host=localhost user=postgres".parse().unwrap();
+//! let pool = Builder::new().max_size(15).build(config, NoTls);
+//!
+//! let mut client = pool.get().unwrap();
+//! client.batch_execute("SELECT 1").unwrap();
+//! ```
+
+use futures::sync::oneshot;
+use futures::Future;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+use tokio_postgres::pool::State;
+use tokio_postgres::{Error, MakeTlsMode, Socket, TlsMode};
+
+use crate::{Client, Config, RUNTIME};
+
+/// A builder for [`Pool`]s.
+///
+/// [`Pool`]: struct.Pool.html
+#[derive(Debug, Clone, Default)]
+pub struct Builder(tokio_postgres::pool::Builder);
+
+impl Builder {
+    /// Creates a new builder with the default configuration.
+    pub fn new() -> Builder {
+        Builder(tokio_postgres::pool::Builder::new())
+    }
+
+    /// Sets the maximum number of connections managed by the pool.
+    ///
+    /// Defaults to 10.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0.
+    pub fn max_size(&mut self, max_size: u32) -> &mut Builder {
+        self.0.max_size(max_size);
+        self
+    }
+
+    /// Sets the maximum amount of time a checkout will wait for a connection before failing.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn connection_timeout(&mut self, connection_timeout: Duration) -> &mut Builder {
+        self.0.connection_timeout(connection_timeout);
+        self
+    }
+
+    /// Sets the amount of time after which an idle connection is closed.
+    ///
+    /// If set to `None`, idle connections are never closed. Defaults to 10 minutes.
+    pub fn idle_timeout(&mut self, idle_timeout: Option<Duration>) -> &mut Builder {
+        self.0.idle_timeout(idle_timeout);
+        self
+    }
+
+    /// Sets the maximum lifetime of a connection, after which it is closed once it is next returned to the pool.
+    ///
+    /// If set to `None`, connections are kept open indefinitely. Defaults to 30 minutes.
+    pub fn max_lifetime(&mut self, max_lifetime: Option<Duration>) -> &mut Builder {
+        self.0.max_lifetime(max_lifetime);
+        self
+    }
+
+    /// Sets a query which is run on idle connections before they are handed out to check that they are still
+    /// usable.
+    ///
+    /// Defaults to no test query.
+    pub fn test_query(&mut self, test_query: &str) -> &mut Builder {
+        self.0.test_query(test_query);
+        self
+    }
+
+    /// Creates a pool which connects to the database described by `config`.
+    ///
+    /// No connections are opened until they are first requested.
+    pub fn build<T>(&self, config: Config, make_tls_mode: T) -> Pool<T>
+    where
+        T: MakeTlsMode<Socket>,
+    {
+        Pool(self.0.build(config.config, make_tls_mode))
+    }
+}
+
+/// A pool of database connections.
+///
+/// Pools are cheap to clone, and clones share the same underlying set of connections.
+pub struct Pool<T>(tokio_postgres::pool::Pool<T>);
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Pool<T> {
+        Pool(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl<T> Pool<T>
+where
+    T: MakeTlsMode<Socket> + Clone + 'static + Send,
+    T::TlsMode: Send,
+    T::Stream: Send,
+    <T::TlsMode as TlsMode<Socket>>::Future: Send,
+{
+    /// Creates a pool with the default configuration.
+    pub fn new(config: Config, make_tls_mode: T) -> Pool<T> {
+        Builder::new().build(config, make_tls_mode)
+    }
+
+    /// Retrieves a connection from the pool, blocking until one is available or the connection timeout elapses.
+    pub fn get(&self) -> Result<PooledClient<T>, Error> {
+        let mut pooled = oneshot::spawn(self.0.get(), &RUNTIME.executor()).wait()?;
+        let client = Client::from(pooled.__take());
+        Ok(PooledClient {
+            pooled,
+            client: Some(client),
+        })
+    }
+}
+
+impl<T> Pool<T> {
+    /// Returns information about the current state of the pool.
+    pub fn state(&self) -> State {
+        self.0.state()
+    }
+}
+
+/// A client checked out of a [`Pool`].
+///
+/// The client dereferences to a [`Client`], and is returned to the pool when dropped.
+///
+/// [`Pool`]: struct.Pool.html
+/// [`Client`]: ../struct.Client.html
+pub struct PooledClient<T> {
+    pooled: tokio_postgres::pool::PooledClient<T>,
+    client: Option<Client>,
+}
+
+impl<T> Deref for PooledClient<T> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for PooledClient<T> {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for PooledClient<T> {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            self.pooled.__restore(client.into_inner());
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use fallible_iterator::FallibleIterator;
 use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_postgres::error::SqlState;
 use tokio_postgres::types::Type;
-use tokio_postgres::NoTls;
+use tokio_postgres::{IsolationLevel, NoTls, Overflow};
 
 use super::*;
 
@@ -16,6 +19,27 @@ fn prepare() {
     assert_eq!(stmt.columns()[1].type_(), &Type::TEXT);
 }
 
+#[test]
+fn prepare_cached() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let stmt = client.prepare_cached("SELECT $1::TEXT").unwrap();
+    let rows = client
+        .query(&stmt, &[&"hello"])
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows[0].get::<_, &str>(0), "hello");
+
+    client.prepare_cached("SELECT $1::TEXT").unwrap();
+    let rows = client
+        .query("SELECT count(*) FROM pg_prepared_statements", &[])
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows[0].get::<_, i64>(0), 2);
+}
+
 #[test]
 fn query_prepared() {
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
@@ -68,6 +92,34 @@ fn transaction_commit() {
     assert_eq!(rows[0].get::<_, i32>(0), 1);
 }
 
+#[test]
+fn build_transaction() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut transaction = client
+        .build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .read_only(true)
+        .start()
+        .unwrap();
+
+    let rows = transaction
+        .query(
+            "SELECT current_setting('transaction_isolation'), current_setting('transaction_read_only')",
+            &[],
+        )
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows[0].get::<_, &str>(0), "repeatable read");
+    assert_eq!(rows[0].get::<_, &str>(1), "on");
+
+    let err = transaction
+        .execute("CREATE TABLE foo (id INT)", &[])
+        .unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::READ_ONLY_SQL_TRANSACTION));
+}
+
 #[test]
 fn transaction_rollback() {
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
@@ -265,3 +317,268 @@ fn portal() {
     assert_eq!(rows.len(), 1);
     assert_eq!(rows[0].get::<_, i32>(0), 3);
 }
+
+#[test]
+fn pipeline() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY)")
+        .unwrap();
+
+    let mut pipeline = client.pipeline();
+    pipeline
+        .add("INSERT INTO foo (id) VALUES ($1)", &[&1i32])
+        .unwrap()
+        .add("INSERT INTO foo (id) VALUES ($1)", &[&2i32])
+        .unwrap()
+        .add("SELECT id FROM foo ORDER BY id", &[])
+        .unwrap();
+    let results = pipeline.run().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].rows_affected(), 1);
+    assert_eq!(results[1].rows_affected(), 1);
+    assert_eq!(results[2].rows_affected(), 2);
+    let rows = results[2].rows();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+    assert_eq!(rows[1].get::<_, i32>(0), 2);
+
+    let mut pipeline = client.pipeline();
+    pipeline
+        .add("INSERT INTO foo (id) VALUES ($1)", &[&3i32])
+        .unwrap()
+        .add("INSERT INTO foo (id) VALUES ($1)", &[&1i32])
+        .unwrap();
+    pipeline.run().err().unwrap();
+
+    let rows = client
+        .query("SELECT id FROM foo", &[])
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+
+    // the pipelines' statements are cached, so the INSERT was only prepared once
+    let rows = client
+        .query(
+            "SELECT count(*) FROM pg_prepared_statements WHERE statement = $1",
+            &[&"INSERT INTO foo (id) VALUES ($1)"],
+        )
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows[0].get::<_, i64>(0), 1);
+}
+
+#[test]
+fn query_timeout() {
+    let mut client = Client::configure()
+        .host("localhost")
+        .port(5433)
+        .user("postgres")
+        .query_timeout(Duration::from_millis(100))
+        .connect(NoTls)
+        .unwrap();
+
+    let err = client.execute("SELECT pg_sleep(100)", &[]).err().unwrap();
+    assert!(err.is_timeout());
+
+    let rows = client
+        .query("SELECT 1", &[])
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+}
+
+#[test]
+fn notifications() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    let mut notifier = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client.batch_execute("LISTEN notifications_test").unwrap();
+    notifier
+        .batch_execute(
+            "
+            NOTIFY notifications_test, 'hello';
+            NOTIFY notifications_test, 'world';
+            ",
+        )
+        .unwrap();
+
+    let notifications = client
+        .notifications()
+        .iter()
+        .take(2)
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(notifications.len(), 2);
+    assert_eq!(notifications[0].channel(), "notifications_test");
+    assert_eq!(notifications[0].payload(), "hello");
+    assert_eq!(notifications[1].payload(), "world");
+    assert!(client.notifications().try_iter().next().unwrap().is_none());
+
+    notifier
+        .batch_execute("NOTIFY notifications_test, 'again'")
+        .unwrap();
+    let notification = client
+        .notifications()
+        .timeout_iter(Duration::from_secs(10))
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(notification.payload(), "again");
+    assert!(client
+        .notifications()
+        .timeout_iter(Duration::from_millis(10))
+        .next()
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn notification_buffer() {
+    let mut notifier = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    for &(overflow, ref expected) in &[
+        (Overflow::DropOldest, ["b", "c"]),
+        (Overflow::DropNewest, ["a", "b"]),
+    ] {
+        let mut client = Client::configure()
+            .host("localhost")
+            .port(5433)
+            .user("postgres")
+            .notification_buffer(2, overflow)
+            .connect(NoTls)
+            .unwrap();
+
+        client.batch_execute("LISTEN notification_buffer").unwrap();
+        notifier
+            .batch_execute(
+                "
+                NOTIFY notification_buffer, 'a';
+                NOTIFY notification_buffer, 'b';
+                NOTIFY notification_buffer, 'c';
+                ",
+            )
+            .unwrap();
+        // pending notifications are delivered before the query completes
+        client.batch_execute("SELECT 1").unwrap();
+
+        let payloads = client
+            .notifications()
+            .try_iter()
+            .map(|n| n.payload().to_string())
+            .collect::<Vec<_>>()
+            .unwrap();
+        assert_eq!(payloads, expected);
+    }
+
+    let mut client = Client::configure()
+        .host("localhost")
+        .port(5433)
+        .user("postgres")
+        .notification_buffer(1, Overflow::Wait)
+        .connect(NoTls)
+        .unwrap();
+
+    client.batch_execute("LISTEN notification_buffer").unwrap();
+    notifier
+        .batch_execute(
+            "
+            NOTIFY notification_buffer, 'a';
+            NOTIFY notification_buffer, 'b';
+            NOTIFY notification_buffer, 'c';
+            ",
+        )
+        .unwrap();
+
+    let payloads = client
+        .notifications()
+        .timeout_iter(Duration::from_secs(10))
+        .take(3)
+        .map(|n| n.payload().to_string())
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(payloads, ["a", "b", "c"]);
+    client.batch_execute("SELECT 1").unwrap();
+}
+
+#[test]
+fn notice_callback() {
+    let notices = Arc::new(Mutex::new(vec![]));
+    let mut client = Client::configure()
+        .host("localhost")
+        .port(5433)
+        .user("postgres")
+        .notice_callback({
+            let notices = notices.clone();
+            move |notice| notices.lock().unwrap().push(notice.message().to_string())
+        })
+        .connect(NoTls)
+        .unwrap();
+
+    client
+        .batch_execute("DO $$BEGIN RAISE NOTICE 'hello'; END$$")
+        .unwrap();
+    assert_eq!(*notices.lock().unwrap(), ["hello"]);
+}
+
+#[test]
+fn pool() {
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    let pool = pool::Builder::new().max_size(1).build(config, NoTls);
+
+    let mut client = pool.get().unwrap();
+    client
+        .batch_execute("SET application_name = 'pooled'")
+        .unwrap();
+    drop(client);
+
+    let mut client = pool.get().unwrap();
+    let rows = client
+        .query("SELECT current_setting('application_name')", &[])
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows[0].get::<_, &str>(0), "pooled");
+    assert_eq!(pool.state().connections(), 1);
+}
+
+#[test]
+#[cfg(feature = "derive")]
+fn derive() {
+    #[derive(Debug, PartialEq, ToSql, FromSql)]
+    #[postgres(crate = "crate::tokio_postgres")]
+    struct Celsius(f64);
+
+    #[derive(Debug, PartialEq, FromRow)]
+    #[postgres(crate = "crate::tokio_postgres")]
+    struct Reading {
+        id: i32,
+        temperature: Celsius,
+    }
+
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    client
+        .batch_execute("CREATE DOMAIN pg_temp.\"Celsius\" AS DOUBLE PRECISION")
+        .unwrap();
+
+    let rows = client
+        .query(
+            "SELECT 1 AS id, $1::\"Celsius\" AS temperature",
+            &[&Celsius(21.5)],
+        )
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    let reading = Reading::from_row(&rows[0]).unwrap();
+    assert_eq!(
+        reading,
+        Reading {
+            id: 1,
+            temperature: Celsius(21.5),
+        }
+    );
+}
@@ -9,6 +9,9 @@ mod sealed {
 pub trait ToStatement: sealed::Sealed {
     #[doc(hidden)]
     fn __statement(&self, client: &mut Client) -> Result<Statement, Error>;
+
+    #[doc(hidden)]
+    fn __cached_statement(&self, client: &mut Client) -> Result<Statement, Error>;
 }
 
 impl sealed::Sealed for str {}
@@ -17,6 +20,10 @@ impl ToStatement for str {
     fn __statement(&self, client: &mut Client) -> Result<Statement, Error> {
         client.prepare(self)
     }
+
+    fn __cached_statement(&self, client: &mut Client) -> Result<Statement, Error> {
+        client.prepare_cached(self)
+    }
 }
 
 impl sealed::Sealed for Statement {}
@@ -25,4 +32,8 @@ impl ToStatement for Statement {
     fn __statement(&self, _: &mut Client) -> Result<Statement, Error> {
         Ok(self.clone())
     }
+
+    fn __cached_statement(&self, _: &mut Client) -> Result<Statement, Error> {
+        Ok(self.clone())
+    }
 }
@@ -3,7 +3,7 @@ use std::io::Read;
 use tokio_postgres::types::{ToSql, Type};
 use tokio_postgres::Error;
 
-use crate::{Client, CopyOutReader, Portal, Query, QueryPortal, Statement, ToStatement};
+use crate::{Client, CopyOutReader, Pipeline, Portal, Query, QueryPortal, Statement, ToStatement};
 
 pub struct Transaction<'a> {
     client: &'a mut Client,
@@ -124,6 +124,10 @@ impl<'a> Transaction<'a> {
         self.client.batch_execute(query)
     }
 
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        self.client.pipeline()
+    }
+
     pub fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
         let depth = self.depth + 1;
         self.client
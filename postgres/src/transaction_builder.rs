@@ -0,0 +1,52 @@
+use tokio_postgres::{Error, IsolationLevel};
+
+use crate::{Client, Transaction};
+
+/// A builder for database transactions.
+pub struct TransactionBuilder<'a> {
+    client: &'a mut Client,
+    isolation_level: Option<IsolationLevel>,
+    read_only: Option<bool>,
+    deferrable: Option<bool>,
+}
+
+impl<'a> TransactionBuilder<'a> {
+    pub(crate) fn new(client: &'a mut Client) -> TransactionBuilder<'a> {
+        TransactionBuilder {
+            client,
+            isolation_level: None,
+            read_only: None,
+            deferrable: None,
+        }
+    }
+
+    /// Sets the isolation level of the transaction.
+    pub fn isolation_level(mut self, isolation_level: IsolationLevel) -> TransactionBuilder<'a> {
+        self.isolation_level = Some(isolation_level);
+        self
+    }
+
+    /// Sets whether the transaction is read-only.
+    pub fn read_only(mut self, read_only: bool) -> TransactionBuilder<'a> {
+        self.read_only = Some(read_only);
+        self
+    }
+
+    /// Sets whether the transaction is deferrable.
+    ///
+    /// This only has an effect on serializable, read-only transactions.
+    pub fn deferrable(mut self, deferrable: bool) -> TransactionBuilder<'a> {
+        self.deferrable = Some(deferrable);
+        self
+    }
+
+    /// Begins the transaction.
+    ///
+    /// Like `Client::transaction`, the transaction rolls back when dropped unless it is committed.
+    pub fn start(self) -> Result<Transaction<'a>, Error> {
+        let query =
+            tokio_postgres::__begin_query(self.isolation_level, self.read_only, self.deferrable);
+        self.client.batch_execute(&query)?;
+        Ok(Transaction::new(self.client))
+    }
+}
@@ -1,13 +1,132 @@
 #![warn(rust_2018_idioms, clippy::all)]
 
 use futures::{try_ready, Async, Future, Poll};
+#[cfg(feature = "runtime")]
+use native_tls::{Certificate, Identity};
+#[cfg(feature = "runtime")]
+use std::error::Error;
+#[cfg(feature = "runtime")]
+use std::fs;
 use tokio_io::{AsyncRead, AsyncWrite};
 use tokio_postgres::{ChannelBinding, TlsConnect};
+#[cfg(feature = "runtime")]
+use tokio_postgres::{Config, MakeTlsConnect, MakeTlsMode, MaybeTlsStream, SslMode, SslModeTls};
 use tokio_tls::{Connect, TlsStream};
 
 #[cfg(test)]
 mod test;
 
+/// A `MakeTlsConnect` and `MakeTlsMode` implementation using `native-tls`.
+///
+/// The wrapped connector is used as-is unless the connection configuration sets `sslrootcert`, `sslcert`, `sslkey`,
+/// or an `sslmode` other than `verify-full`. Since a `native_tls::TlsConnector` can't be reconfigured, a new one is
+/// built from the configuration in that case. The `sslcrl` and `sslpassword` options are not supported.
+///
+/// Requires the `runtime` Cargo feature (enabled by default).
+#[cfg(feature = "runtime")]
+#[derive(Clone)]
+pub struct MakeTlsConnector(native_tls::TlsConnector);
+
+#[cfg(feature = "runtime")]
+impl MakeTlsConnector {
+    /// Creates a new connector.
+    pub fn new(connector: native_tls::TlsConnector) -> MakeTlsConnector {
+        MakeTlsConnector(connector)
+    }
+
+    fn connector(
+        &self,
+        config: &Config,
+    ) -> Result<native_tls::TlsConnector, Box<dyn Error + Sync + Send>> {
+        if config.get_ssl_crl().is_some() {
+            return Err("sslcrl is not supported by native-tls".into());
+        }
+        if config.get_ssl_password().is_some() {
+            return Err("sslpassword is not supported by native-tls".into());
+        }
+
+        let ssl_mode = config.get_ssl_mode();
+        let root_cert = config.get_ssl_root_cert();
+        let cert = config.get_ssl_cert();
+        let key = config.get_ssl_key();
+        match ssl_mode {
+            Some(SslMode::VerifyFull) | None
+                if root_cert.is_none() && cert.is_none() && key.is_none() =>
+            {
+                return Ok(self.0.clone())
+            }
+            _ => {}
+        }
+
+        let mut builder = native_tls::TlsConnector::builder();
+        if let Some(path) = root_cert {
+            builder.add_root_certificate(Certificate::from_pem(&fs::read(path)?)?);
+            builder.disable_built_in_roots(true);
+        }
+        match (cert, key) {
+            (Some(cert), Some(key)) => {
+                builder.identity(Identity::from_pkcs8(&fs::read(cert)?, &fs::read(key)?)?);
+            }
+            (None, None) => {}
+            _ => return Err("sslcert and sslkey must be set together".into()),
+        }
+        match ssl_mode {
+            Some(SslMode::VerifyFull) | None => {}
+            Some(SslMode::VerifyCa) => {
+                builder.danger_accept_invalid_hostnames(true);
+            }
+            // like libpq, the other modes only verify the server's certificate if a root certificate is provided
+            Some(_) => {
+                builder.danger_accept_invalid_hostnames(true);
+                if root_cert.is_none() {
+                    builder.danger_accept_invalid_certs(true);
+                }
+            }
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl<S> MakeTlsConnect<S> for MakeTlsConnector
+where
+    S: AsyncRead + AsyncWrite,
+{
+    type Stream = TlsStream<S>;
+    type TlsConnect = TlsConnector;
+    type Error = Box<dyn Error + Sync + Send>;
+
+    fn make_tls_connect(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<TlsConnector, Box<dyn Error + Sync + Send>> {
+        let connector = self.connector(config)?;
+        Ok(TlsConnector::with_connector(connector, domain))
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl<S> MakeTlsMode<S> for MakeTlsConnector
+where
+    S: AsyncRead + AsyncWrite,
+{
+    type Stream = MaybeTlsStream<TlsStream<S>, S>;
+    type TlsMode = SslModeTls<TlsConnector>;
+    type Error = Box<dyn Error + Sync + Send>;
+
+    fn make_tls_mode(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<SslModeTls<TlsConnector>, Box<dyn Error + Sync + Send>> {
+        SslModeTls::from_config(config, || {
+            MakeTlsConnect::<S>::make_tls_connect(self, domain, config)
+        })
+    }
+}
+
 pub struct TlsConnector {
     connector: tokio_tls::TlsConnector,
     domain: String,
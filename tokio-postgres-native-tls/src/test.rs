@@ -4,6 +4,8 @@ use tokio::net::TcpStream;
 use tokio::runtime::current_thread::Runtime;
 use tokio_postgres::{self, PreferTls, RequireTls, TlsMode};
 
+#[cfg(feature = "runtime")]
+use crate::MakeTlsConnector;
 use crate::TlsConnector;
 
 fn smoke_test<T>(s: &str, tls: T)
@@ -38,9 +40,7 @@ where
 #[test]
 fn require() {
     let connector = native_tls::TlsConnector::builder()
-        .add_root_certificate(
-            Certificate::from_pem(include_bytes!("../../test/server.crt")).unwrap(),
-        )
+        .add_root_certificate(Certificate::from_pem(include_bytes!("../../test/ca.crt")).unwrap())
         .build()
         .unwrap();
     smoke_test(
@@ -52,9 +52,7 @@ fn require() {
 #[test]
 fn prefer() {
     let connector = native_tls::TlsConnector::builder()
-        .add_root_certificate(
-            Certificate::from_pem(include_bytes!("../../test/server.crt")).unwrap(),
-        )
+        .add_root_certificate(Certificate::from_pem(include_bytes!("../../test/ca.crt")).unwrap())
         .build()
         .unwrap();
     smoke_test(
@@ -66,9 +64,7 @@ fn prefer() {
 #[test]
 fn scram_user() {
     let connector = native_tls::TlsConnector::builder()
-        .add_root_certificate(
-            Certificate::from_pem(include_bytes!("../../test/server.crt")).unwrap(),
-        )
+        .add_root_certificate(Certificate::from_pem(include_bytes!("../../test/ca.crt")).unwrap())
         .build()
         .unwrap();
     smoke_test(
@@ -76,3 +72,23 @@ fn scram_user() {
         RequireTls(TlsConnector::with_connector(connector, "localhost")),
     );
 }
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_ssl_mode() {
+    let mut runtime = Runtime::new().unwrap();
+
+    // sslmode=require doesn't verify the server's certificate without a root certificate
+    let connector = MakeTlsConnector::new(native_tls::TlsConnector::new().unwrap());
+
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=ssl_user dbname=postgres sslmode=require",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+}
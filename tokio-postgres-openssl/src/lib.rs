@@ -6,16 +6,28 @@ use openssl::error::ErrorStack;
 use openssl::hash::MessageDigest;
 use openssl::nid::Nid;
 #[cfg(feature = "runtime")]
-use openssl::ssl::SslConnector;
+use openssl::pkey::PKey;
 use openssl::ssl::{ConnectConfiguration, HandshakeError, SslRef};
+#[cfg(feature = "runtime")]
+use openssl::ssl::{SslConnector, SslFiletype, SslVerifyMode};
+#[cfg(feature = "runtime")]
+use openssl::x509::store::{X509Lookup, X509StoreBuilder};
+#[cfg(feature = "runtime")]
+use openssl::x509::verify::X509VerifyFlags;
+#[cfg(feature = "runtime")]
+use openssl::x509::X509;
+#[cfg(feature = "runtime")]
+use std::error::Error;
 use std::fmt::Debug;
 #[cfg(feature = "runtime")]
+use std::fs;
+#[cfg(feature = "runtime")]
 use std::sync::Arc;
 use tokio_io::{AsyncRead, AsyncWrite};
 use tokio_openssl::{ConnectAsync, ConnectConfigurationExt, SslStream};
-#[cfg(feature = "runtime")]
-use tokio_postgres::MakeTlsConnect;
 use tokio_postgres::{ChannelBinding, TlsConnect};
+#[cfg(feature = "runtime")]
+use tokio_postgres::{Config, MakeTlsConnect, MakeTlsMode, MaybeTlsStream, SslMode, SslModeTls};
 
 #[cfg(test)]
 mod test;
@@ -51,15 +63,93 @@ where
 {
     type Stream = SslStream<S>;
     type TlsConnect = TlsConnector;
-    type Error = ErrorStack;
+    type Error = Box<dyn Error + Sync + Send>;
 
-    fn make_tls_connect(&mut self, domain: &str) -> Result<TlsConnector, ErrorStack> {
+    fn make_tls_connect(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<TlsConnector, Box<dyn Error + Sync + Send>> {
         let mut ssl = self.connector.configure()?;
         (self.config)(&mut ssl)?;
+        configure_ssl(&mut ssl, config)?;
         Ok(TlsConnector::new(ssl, domain))
     }
 }
 
+#[cfg(feature = "runtime")]
+impl<S> MakeTlsMode<S> for MakeTlsConnector
+where
+    S: AsyncRead + AsyncWrite + Debug + 'static + Sync + Send,
+{
+    type Stream = MaybeTlsStream<SslStream<S>, S>;
+    type TlsMode = SslModeTls<TlsConnector>;
+    type Error = Box<dyn Error + Sync + Send>;
+
+    fn make_tls_mode(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<SslModeTls<TlsConnector>, Box<dyn Error + Sync + Send>> {
+        SslModeTls::from_config(config, || {
+            MakeTlsConnect::<S>::make_tls_connect(self, domain, config)
+        })
+    }
+}
+
+// Applies the TLS settings of the connection configuration. Certificate verification is only relaxed if `sslmode` is
+// set explicitly, so connectors configured in code keep their behavior by default.
+#[cfg(feature = "runtime")]
+fn configure_ssl(
+    ssl: &mut ConnectConfiguration,
+    config: &Config,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    if config.get_ssl_root_cert().is_some() || config.get_ssl_crl().is_some() {
+        let mut store = X509StoreBuilder::new()?;
+        match config.get_ssl_root_cert() {
+            Some(path) => store
+                .add_lookup(X509Lookup::file())?
+                .load_cert_file(path, SslFiletype::PEM)?,
+            None => store.set_default_paths()?,
+        }
+        if let Some(path) = config.get_ssl_crl() {
+            store
+                .add_lookup(X509Lookup::file())?
+                .load_crl_file(path, SslFiletype::PEM)?;
+            store.set_flags(X509VerifyFlags::CRL_CHECK)?;
+        }
+        ssl.set_verify_cert_store(store.build())?;
+    }
+
+    if let Some(path) = config.get_ssl_cert() {
+        let cert = X509::from_pem(&fs::read(path)?)?;
+        ssl.set_certificate(&cert)?;
+    }
+
+    if let Some(path) = config.get_ssl_key() {
+        let pem = fs::read(path)?;
+        let key = match config.get_ssl_password() {
+            Some(password) => PKey::private_key_from_pem_passphrase(&pem, password)?,
+            None => PKey::private_key_from_pem(&pem)?,
+        };
+        ssl.set_private_key(&key)?;
+    }
+
+    match config.get_ssl_mode() {
+        Some(SslMode::VerifyFull) | None => {}
+        Some(SslMode::VerifyCa) => ssl.set_verify_hostname(false),
+        // like libpq, the other modes only verify the server's certificate if a root certificate is provided
+        Some(_) => {
+            ssl.set_verify_hostname(false);
+            if config.get_ssl_root_cert().is_none() {
+                ssl.set_verify(SslVerifyMode::NONE);
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub struct TlsConnector {
     ssl: ConnectConfiguration,
     domain: String,
@@ -1,5 +1,7 @@
 use futures::{Future, Stream};
 use openssl::ssl::{SslConnector, SslMethod};
+#[cfg(feature = "runtime")]
+use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio::runtime::current_thread::Runtime;
 use tokio_postgres::{self, PreferTls, RequireTls, TlsMode};
@@ -38,7 +40,7 @@ where
 #[test]
 fn require() {
     let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
-    builder.set_ca_file("../test/server.crt").unwrap();
+    builder.set_ca_file("../test/ca.crt").unwrap();
     let ctx = builder.build();
     smoke_test(
         "user=ssl_user dbname=postgres",
@@ -49,7 +51,7 @@ fn require() {
 #[test]
 fn prefer() {
     let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
-    builder.set_ca_file("../test/server.crt").unwrap();
+    builder.set_ca_file("../test/ca.crt").unwrap();
     let ctx = builder.build();
     smoke_test(
         "user=ssl_user dbname=postgres",
@@ -60,7 +62,7 @@ fn prefer() {
 #[test]
 fn scram_user() {
     let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
-    builder.set_ca_file("../test/server.crt").unwrap();
+    builder.set_ca_file("../test/ca.crt").unwrap();
     let ctx = builder.build();
     smoke_test(
         "user=scram_user password=password dbname=postgres",
@@ -74,7 +76,7 @@ fn runtime() {
     let mut runtime = Runtime::new().unwrap();
 
     let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
-    builder.set_ca_file("../test/server.crt").unwrap();
+    builder.set_ca_file("../test/ca.crt").unwrap();
     let connector = MakeTlsConnector::new(builder.build());
 
     let connect = tokio_postgres::connect(
@@ -88,3 +90,73 @@ fn runtime() {
     let execute = client.batch_execute("SELECT 1");
     runtime.block_on(execute).unwrap();
 }
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_ssl_mode() {
+    let mut runtime = Runtime::new().unwrap();
+
+    // sslmode=require doesn't verify the server's certificate without a root certificate
+    let builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    let connector = MakeTlsConnector::new(builder.build());
+
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=ssl_user dbname=postgres sslmode=require",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_channel_binding() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    let connector = MakeTlsConnector::new(builder.build());
+
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=scram_user password=password dbname=postgres \
+         sslmode=require channel_binding=require",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_query_timeout() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_ca_file("../test/ca.crt").unwrap();
+    let connector = MakeTlsConnector::new(builder.build());
+
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=ssl_user dbname=postgres sslmode=verify-full",
+        connector.clone(),
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    client.set_query_timeout_tls(connector);
+
+    let prepare = client.prepare("SELECT pg_sleep(100)");
+    let statement = runtime.block_on(prepare).unwrap();
+    let execute = client
+        .execute(&statement, &[])
+        .timeout(Duration::from_millis(100));
+    let err = runtime.block_on(execute).err().unwrap();
+    assert!(err.is_timeout());
+}
@@ -0,0 +1,504 @@
+#![warn(rust_2018_idioms, clippy::all)]
+
+use futures::{Async, Future, Poll};
+use ring::digest;
+#[cfg(feature = "runtime")]
+use rustls::internal::pemfile;
+#[cfg(feature = "runtime")]
+use rustls::{Certificate, RootCertStore, ServerCertVerified, ServerCertVerifier, TLSError};
+use rustls::{ClientConfig, ClientSession, Session};
+#[cfg(feature = "runtime")]
+use std::error::Error;
+#[cfg(feature = "runtime")]
+use std::fs::File;
+use std::io::{self, Read, Write};
+#[cfg(feature = "runtime")]
+use std::io::{BufReader, Seek, SeekFrom};
+use std::net::IpAddr;
+use std::sync::Arc;
+#[cfg(feature = "runtime")]
+use std::time::SystemTime;
+use tokio_io::{try_nb, AsyncRead, AsyncWrite};
+use tokio_postgres::{ChannelBinding, TlsConnect};
+#[cfg(feature = "runtime")]
+use tokio_postgres::{Config, MakeTlsConnect, MakeTlsMode, MaybeTlsStream, SslMode, SslModeTls};
+use webpki::DNSNameRef;
+
+#[cfg(test)]
+mod test;
+
+/// A `MakeTlsConnect` and `MakeTlsMode` implementation using `rustls`.
+///
+/// The wrapped configuration is used as-is unless the connection configuration sets `sslrootcert`, `sslcert`,
+/// `sslkey`, or an `sslmode` other than `verify-full`, in which case a copy is adjusted to match. The `sslcrl` and
+/// `sslpassword` options are not supported. Since the server's hostname can't be verified against an IP address,
+/// connecting to one with `sslmode=verify-full` fails.
+///
+/// Requires the `runtime` Cargo feature (enabled by default).
+#[cfg(feature = "runtime")]
+#[derive(Clone)]
+pub struct MakeTlsConnector(Arc<ClientConfig>);
+
+#[cfg(feature = "runtime")]
+impl MakeTlsConnector {
+    /// Creates a new connector.
+    pub fn new(config: ClientConfig) -> MakeTlsConnector {
+        MakeTlsConnector(Arc::new(config))
+    }
+
+    fn client_config(
+        &self,
+        config: &Config,
+    ) -> Result<Arc<ClientConfig>, Box<dyn Error + Sync + Send>> {
+        if config.get_ssl_crl().is_some() {
+            return Err("sslcrl is not supported by rustls".into());
+        }
+        if config.get_ssl_password().is_some() {
+            return Err("sslpassword is not supported by rustls".into());
+        }
+
+        let ssl_mode = config.get_ssl_mode();
+        let root_cert = config.get_ssl_root_cert();
+        let cert = config.get_ssl_cert();
+        let key = config.get_ssl_key();
+        match ssl_mode {
+            Some(SslMode::VerifyFull) | None
+                if root_cert.is_none() && cert.is_none() && key.is_none() =>
+            {
+                return Ok(self.0.clone())
+            }
+            _ => {}
+        }
+
+        let mut client_config = (*self.0).clone();
+        if let Some(path) = root_cert {
+            let mut root_store = RootCertStore::empty();
+            root_store
+                .add_pem_file(&mut BufReader::new(File::open(path)?))
+                .map_err(|()| "invalid sslrootcert")?;
+            client_config.root_store = root_store;
+        }
+        match (cert, key) {
+            (Some(cert), Some(key)) => {
+                let certs = pemfile::certs(&mut BufReader::new(File::open(cert)?))
+                    .map_err(|()| "invalid sslcert")?;
+                let mut key = BufReader::new(File::open(key)?);
+                let mut keys =
+                    pemfile::pkcs8_private_keys(&mut key).map_err(|()| "invalid sslkey")?;
+                if keys.is_empty() {
+                    key.seek(SeekFrom::Start(0))?;
+                    keys = pemfile::rsa_private_keys(&mut key).map_err(|()| "invalid sslkey")?;
+                }
+                let key = keys.pop().ok_or("no private key found in sslkey")?;
+                client_config.set_single_client_cert(certs, key)?;
+            }
+            (None, None) => {}
+            _ => return Err("sslcert and sslkey must be set together".into()),
+        }
+        match ssl_mode {
+            Some(SslMode::VerifyFull) | None => {}
+            Some(SslMode::VerifyCa) => {
+                client_config
+                    .dangerous()
+                    .set_certificate_verifier(Arc::new(NoHostnameVerifier));
+            }
+            // like libpq, the other modes only verify the server's certificate if a root certificate is provided
+            Some(_) => {
+                if root_cert.is_none() {
+                    client_config
+                        .dangerous()
+                        .set_certificate_verifier(Arc::new(NoVerifier));
+                } else {
+                    client_config
+                        .dangerous()
+                        .set_certificate_verifier(Arc::new(NoHostnameVerifier));
+                }
+            }
+        }
+
+        Ok(Arc::new(client_config))
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl<S> MakeTlsConnect<S> for MakeTlsConnector
+where
+    S: AsyncRead + AsyncWrite,
+{
+    type Stream = TlsStream<S>;
+    type TlsConnect = TlsConnector;
+    type Error = Box<dyn Error + Sync + Send>;
+
+    fn make_tls_connect(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<TlsConnector, Box<dyn Error + Sync + Send>> {
+        let client_config = self.client_config(config)?;
+        let mut connector = TlsConnector::with_config(client_config, domain);
+        // the other modes never check the server's hostname
+        connector.verify_hostname = match config.get_ssl_mode() {
+            Some(SslMode::VerifyFull) | None => true,
+            Some(_) => false,
+        };
+        Ok(connector)
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl<S> MakeTlsMode<S> for MakeTlsConnector
+where
+    S: AsyncRead + AsyncWrite,
+{
+    type Stream = MaybeTlsStream<TlsStream<S>, S>;
+    type TlsMode = SslModeTls<TlsConnector>;
+    type Error = Box<dyn Error + Sync + Send>;
+
+    fn make_tls_mode(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<SslModeTls<TlsConnector>, Box<dyn Error + Sync + Send>> {
+        SslModeTls::from_config(config, || {
+            MakeTlsConnect::<S>::make_tls_connect(self, domain, config)
+        })
+    }
+}
+
+// accepts any certificate, for the modes which don't verify the server without a root certificate.
+#[cfg(feature = "runtime")]
+struct NoVerifier;
+
+#[cfg(feature = "runtime")]
+impl ServerCertVerifier for NoVerifier {
+    fn verify_server_cert(
+        &self,
+        _: &RootCertStore,
+        _: &[Certificate],
+        _: DNSNameRef<'_>,
+        _: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+// verifies the certificate chain, but not that it was issued for the server's hostname.
+#[cfg(feature = "runtime")]
+struct NoHostnameVerifier;
+
+#[cfg(feature = "runtime")]
+static SIGNATURE_ALGORITHMS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::ED25519,
+    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+#[cfg(feature = "runtime")]
+impl ServerCertVerifier for NoHostnameVerifier {
+    fn verify_server_cert(
+        &self,
+        roots: &RootCertStore,
+        presented_certs: &[Certificate],
+        _: DNSNameRef<'_>,
+        _: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        let (cert, chain) = presented_certs
+            .split_first()
+            .ok_or(TLSError::NoCertificatesPresented)?;
+        let cert = webpki::EndEntityCert::from(&cert.0).map_err(TLSError::WebPKIError)?;
+        let chain = chain.iter().map(|c| &*c.0).collect::<Vec<_>>();
+        let anchors = roots
+            .roots
+            .iter()
+            .map(|a| a.to_trust_anchor())
+            .collect::<Vec<_>>();
+        let now = webpki::Time::try_from(SystemTime::now())
+            .map_err(|_| TLSError::FailedToGetCurrentTime)?;
+
+        cert.verify_is_valid_tls_server_cert(
+            SIGNATURE_ALGORITHMS,
+            &webpki::TLSServerTrustAnchors(&anchors),
+            &chain,
+            now,
+        )
+        .map_err(TLSError::WebPKIError)?;
+
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+pub struct TlsConnector {
+    config: Arc<ClientConfig>,
+    domain: String,
+    verify_hostname: bool,
+}
+
+impl TlsConnector {
+    pub fn with_config(config: Arc<ClientConfig>, domain: &str) -> TlsConnector {
+        TlsConnector {
+            config,
+            domain: domain.to_string(),
+            verify_hostname: true,
+        }
+    }
+
+    fn dns_name(&self) -> io::Result<DNSNameRef<'_>> {
+        match DNSNameRef::try_from_ascii_str(&self.domain) {
+            Ok(domain) => Ok(domain),
+            // the name is only otherwise used for SNI, which doesn't apply to IP addresses
+            Err(_) if !self.verify_hostname => {
+                Ok(DNSNameRef::try_from_ascii_str("invalid").unwrap())
+            }
+            Err(_) if self.domain.parse::<IpAddr>().is_ok() => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "rustls can't verify the hostname of an IP address host ({}), use sslmode=verify-ca",
+                    self.domain
+                ),
+            )),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid DNS name: {}", self.domain),
+            )),
+        }
+    }
+}
+
+impl<S> TlsConnect<S> for TlsConnector
+where
+    S: AsyncRead + AsyncWrite,
+{
+    type Stream = TlsStream<S>;
+    type Error = io::Error;
+    type Future = TlsConnectFuture<S>;
+
+    fn connect(self, stream: S) -> TlsConnectFuture<S> {
+        let stream = self.dns_name().map(|domain| TlsStream {
+            session: ClientSession::new(&self.config, domain),
+            stream,
+            eof: false,
+            shutdown: false,
+        });
+
+        TlsConnectFuture(Some(stream))
+    }
+}
+
+pub struct TlsConnectFuture<S>(Option<io::Result<TlsStream<S>>>);
+
+impl<S> Future for TlsConnectFuture<S>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    type Item = (TlsStream<S>, ChannelBinding);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(TlsStream<S>, ChannelBinding), io::Error> {
+        let stream = match self.0.as_mut().expect("future polled after completion") {
+            Ok(stream) => stream,
+            Err(_) => match self.0.take() {
+                Some(Err(e)) => return Err(e),
+                _ => unreachable!(),
+            },
+        };
+
+        while stream.session.is_handshaking() {
+            try_nb!(stream.session.complete_io(&mut stream.stream));
+        }
+        while stream.session.wants_write() {
+            try_nb!(stream.session.write_tls(&mut stream.stream));
+        }
+
+        let stream = match self.0.take() {
+            Some(Ok(stream)) => stream,
+            _ => unreachable!(),
+        };
+        let channel_binding = match stream
+            .session
+            .get_peer_certificates()
+            .and_then(|certs| certs.into_iter().next())
+            .and_then(|cert| tls_server_end_point(&cert.0))
+        {
+            Some(buf) => ChannelBinding::tls_server_end_point(buf),
+            None => ChannelBinding::none(),
+        };
+
+        Ok(Async::Ready((stream, channel_binding)))
+    }
+}
+
+/// A stream protected by TLS.
+pub struct TlsStream<S> {
+    session: ClientSession,
+    stream: S,
+    eof: bool,
+    shutdown: bool,
+}
+
+impl<S> TlsStream<S> {
+    /// Returns a shared reference to the rustls session.
+    pub fn get_ref(&self) -> &ClientSession {
+        &self.session
+    }
+
+    /// Returns a shared reference to the underlying stream.
+    pub fn get_stream(&self) -> &S {
+        &self.stream
+    }
+}
+
+impl<S> TlsStream<S>
+where
+    S: Read + Write,
+{
+    fn write_tls(&mut self) -> io::Result<()> {
+        while self.session.wants_write() {
+            self.session.write_tls(&mut self.stream)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S> Read for TlsStream<S>
+where
+    S: Read + Write,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.session.read(buf) {
+                Ok(0) if buf.is_empty() || self.eof => return Ok(0),
+                Ok(0) => {}
+                Ok(n) => return Ok(n),
+                // the server sent a close_notify alert
+                Err(ref e) if e.kind() == io::ErrorKind::ConnectionAborted => return Ok(0),
+                Err(e) => return Err(e),
+            }
+
+            if self.session.read_tls(&mut self.stream)? == 0 {
+                self.eof = true;
+            }
+            if let Err(e) = self.session.process_new_packets() {
+                // try to let the server know why we're giving up
+                let _ = self.write_tls();
+                return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+            }
+            match self.write_tls() {
+                Ok(()) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<S> Write for TlsStream<S>
+where
+    S: Read + Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        loop {
+            let n = self.session.write(buf)?;
+            if n > 0 || buf.is_empty() {
+                match self.write_tls() {
+                    Ok(()) => {}
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(e),
+                }
+                return Ok(n);
+            }
+
+            // the session's buffer is full, so some of it needs to make it to the socket first
+            self.session.write_tls(&mut self.stream)?;
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.session.flush()?;
+        self.write_tls()?;
+        self.stream.flush()
+    }
+}
+
+impl<S> AsyncRead for TlsStream<S> where S: AsyncRead + AsyncWrite {}
+
+impl<S> AsyncWrite for TlsStream<S>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        if !self.shutdown {
+            self.session.send_close_notify();
+            self.shutdown = true;
+        }
+        try_nb!(self.flush());
+        self.stream.shutdown()
+    }
+}
+
+// RFC 5929 hashes the certificate with the hash function of its signature algorithm, upgrading MD5 and SHA-1 to
+// SHA-256.
+fn tls_server_end_point(cert: &[u8]) -> Option<Vec<u8>> {
+    const SHA384_WITH_RSA: &[u8] = b"\x2a\x86\x48\x86\xf7\x0d\x01\x01\x0c";
+    const SHA512_WITH_RSA: &[u8] = b"\x2a\x86\x48\x86\xf7\x0d\x01\x01\x0d";
+    const ECDSA_WITH_SHA384: &[u8] = b"\x2a\x86\x48\xce\x3d\x04\x03\x03";
+    const ECDSA_WITH_SHA512: &[u8] = b"\x2a\x86\x48\xce\x3d\x04\x03\x04";
+
+    let algorithm = match signature_algorithm(cert)? {
+        SHA384_WITH_RSA | ECDSA_WITH_SHA384 => &digest::SHA384,
+        SHA512_WITH_RSA | ECDSA_WITH_SHA512 => &digest::SHA512,
+        _ => &digest::SHA256,
+    };
+
+    Some(digest::digest(algorithm, cert).as_ref().to_vec())
+}
+
+// Certificate ::= SEQUENCE { tbsCertificate, signatureAlgorithm SEQUENCE { algorithm OID, ... }, signature }
+fn signature_algorithm(cert: &[u8]) -> Option<&[u8]> {
+    let (tag, cert, _) = der_element(cert)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (_, _, rest) = der_element(cert)?;
+    let (tag, algorithm, _) = der_element(rest)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (tag, oid, _) = der_element(algorithm)?;
+    if tag != 0x06 {
+        return None;
+    }
+
+    Some(oid)
+}
+
+// splits a DER element into its tag, contents, and the remaining data.
+fn der_element(buf: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, buf) = buf.split_first()?;
+    let (&len, mut buf) = buf.split_first()?;
+
+    let len = if len & 0x80 == 0 {
+        len as usize
+    } else {
+        let n = (len & 0x7f) as usize;
+        if n == 0 || n > 4 || buf.len() < n {
+            return None;
+        }
+        let len = buf[..n].iter().fold(0, |len, &b| (len << 8) | b as usize);
+        buf = &buf[n..];
+        len
+    };
+
+    if buf.len() < len {
+        return None;
+    }
+    Some((tag, &buf[..len], &buf[len..]))
+}
@@ -0,0 +1,261 @@
+use futures::{Future, Stream};
+use rustls::ClientConfig;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::runtime::current_thread::Runtime;
+use tokio_postgres::{self, PreferTls, RequireTls, TlsMode};
+
+#[cfg(feature = "runtime")]
+use crate::MakeTlsConnector;
+use crate::TlsConnector;
+
+fn client_config() -> Arc<ClientConfig> {
+    let mut config = ClientConfig::new();
+    config
+        .root_store
+        .add_pem_file(&mut &include_bytes!("../../test/ca.crt")[..])
+        .unwrap();
+    Arc::new(config)
+}
+
+fn smoke_test<T>(s: &str, tls: T)
+where
+    T: TlsMode<TcpStream>,
+    T::Stream: 'static,
+{
+    let mut runtime = Runtime::new().unwrap();
+
+    let builder = s.parse::<tokio_postgres::Config>().unwrap();
+
+    let handshake = TcpStream::connect(&"127.0.0.1:5433".parse().unwrap())
+        .map_err(|e| panic!("{}", e))
+        .and_then(|s| builder.connect_raw(s, tls));
+    let (mut client, connection) = runtime.block_on(handshake).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let prepare = client.prepare("SELECT 1::INT4");
+    let statement = runtime.block_on(prepare).unwrap();
+    let select = client.query(&statement, &[]).collect().map(|rows| {
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get::<_, i32>(0), 1);
+    });
+    runtime.block_on(select).unwrap();
+
+    drop(statement);
+    drop(client);
+    runtime.run().unwrap();
+}
+
+#[test]
+fn require() {
+    smoke_test(
+        "user=ssl_user dbname=postgres",
+        RequireTls(TlsConnector::with_config(client_config(), "localhost")),
+    );
+}
+
+#[test]
+fn prefer() {
+    smoke_test(
+        "user=ssl_user dbname=postgres",
+        PreferTls(TlsConnector::with_config(client_config(), "localhost")),
+    );
+}
+
+#[test]
+fn scram_user() {
+    smoke_test(
+        "user=scram_user password=password dbname=postgres channel_binding=require",
+        RequireTls(TlsConnector::with_config(client_config(), "localhost")),
+    );
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_ssl_mode() {
+    let mut runtime = Runtime::new().unwrap();
+
+    // sslmode=require doesn't verify the server's certificate without a root certificate
+    let connector = MakeTlsConnector::new(ClientConfig::new());
+
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=ssl_user dbname=postgres sslmode=require",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn prefer_tls_ssl_mode() {
+    let mut runtime = Runtime::new().unwrap();
+
+    // the server's certificate would fail verification, so this only succeeds if TLS isn't attempted
+    let connector = PreferTls(MakeTlsConnector::new(ClientConfig::new()));
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres dbname=postgres sslmode=disable",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let query = client
+        .prepare("SELECT ssl FROM pg_stat_ssl WHERE pid = pg_backend_pid()")
+        .and_then(|s| client.query(&s, &[]).collect());
+    let rows = runtime.block_on(query).unwrap();
+    assert!(!rows[0].get::<_, bool>(0));
+
+    let connector = PreferTls(MakeTlsConnector::new(ClientConfig::new()));
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=ssl_user dbname=postgres sslmode=verify-full",
+        connector,
+    );
+    runtime.block_on(connect).err().unwrap();
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn require_tls_ssl_mode() {
+    let mut runtime = Runtime::new().unwrap();
+
+    for ssl_mode in &["disable", "allow"] {
+        let connector = RequireTls(MakeTlsConnector::new((*client_config()).clone()));
+        let connect = tokio_postgres::connect(
+            &format!(
+                "host=localhost port=5433 user=ssl_user dbname=postgres sslmode={}",
+                ssl_mode
+            ),
+            connector,
+        );
+        runtime.block_on(connect).err().unwrap();
+    }
+
+    let connector = RequireTls(MakeTlsConnector::new((*client_config()).clone()));
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=ssl_user dbname=postgres sslmode=prefer",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_disable() {
+    let mut runtime = Runtime::new().unwrap();
+
+    // the TLS settings are ignored entirely since TLS won't be used
+    let connector = MakeTlsConnector::new(ClientConfig::new());
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres dbname=postgres sslmode=disable sslrootcert=/nonexistent",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_verify_full() {
+    let mut runtime = Runtime::new().unwrap();
+
+    // the server's certificate isn't signed by any of the (empty) default roots
+    let connector = MakeTlsConnector::new(ClientConfig::new());
+
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=ssl_user dbname=postgres sslmode=verify-full",
+        connector,
+    );
+    runtime.block_on(connect).err().unwrap();
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_ssl_root_cert() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let connector = MakeTlsConnector::new(ClientConfig::new());
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=ssl_user dbname=postgres sslmode=verify-full sslrootcert=../test/ca.crt",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_ip_host() {
+    let mut runtime = Runtime::new().unwrap();
+
+    for ssl_mode in &["require", "verify-ca"] {
+        let connector = MakeTlsConnector::new(ClientConfig::new());
+        let connect = tokio_postgres::connect(
+            &format!(
+                "host=127.0.0.1 port=5433 user=ssl_user dbname=postgres sslmode={} sslrootcert=../test/ca.crt",
+                ssl_mode
+            ),
+            connector,
+        );
+        let (mut client, connection) = runtime.block_on(connect).unwrap();
+        let connection = connection.map_err(|e| panic!("{}", e));
+        runtime.spawn(connection);
+
+        let execute = client.batch_execute("SELECT 1");
+        runtime.block_on(execute).unwrap();
+    }
+
+    let connector = MakeTlsConnector::new(ClientConfig::new());
+    let connect = tokio_postgres::connect(
+        "host=127.0.0.1 port=5433 user=ssl_user dbname=postgres sslmode=verify-full sslrootcert=../test/ca.crt",
+        connector,
+    );
+    let e = runtime.block_on(connect).err().unwrap();
+    assert!(e.to_string().contains("IP address"), "{}", e);
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn runtime_client_cert() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let connector = MakeTlsConnector::new(ClientConfig::new());
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=cert_user dbname=postgres sslmode=verify-full \
+         sslrootcert=../test/ca.crt sslcert=../test/client.crt sslkey=../test/client.key",
+        connector,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+
+    // the server requires a certificate for this user
+    let connector = MakeTlsConnector::new(ClientConfig::new());
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=cert_user dbname=postgres sslmode=verify-full sslrootcert=../test/ca.crt",
+        connector,
+    );
+    runtime.block_on(connect).err().unwrap();
+}
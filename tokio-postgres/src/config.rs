@@ -1,13 +1,15 @@
 use std::borrow::Cow;
+use std::env;
 use std::error;
 #[cfg(all(feature = "runtime", unix))]
 use std::ffi::OsStr;
 use std::fmt;
 use std::iter;
 use std::mem;
+#[cfg(feature = "runtime")]
+use std::net::IpAddr;
 #[cfg(all(feature = "runtime", unix))]
 use std::os::unix::ffi::OsStrExt;
-#[cfg(all(feature = "runtime", unix))]
 use std::path::{Path, PathBuf};
 use std::str::{self, FromStr};
 use std::sync::Arc;
@@ -18,6 +20,7 @@ use tokio_io::{AsyncRead, AsyncWrite};
 #[cfg(feature = "runtime")]
 use crate::proto::ConnectFuture;
 use crate::proto::ConnectRawFuture;
+use crate::service;
 #[cfg(feature = "runtime")]
 use crate::{Connect, MakeTlsMode, Socket};
 use crate::{ConnectRaw, Error, TlsMode};
@@ -30,6 +33,98 @@ pub enum TargetSessionAttrs {
     Any,
     /// The database must be writable.
     ReadWrite,
+    /// The database must not be writable.
+    ReadOnly,
+    /// The server must not be in hot standby mode.
+    Primary,
+    /// The server must be in hot standby mode.
+    Standby,
+    /// A server in hot standby mode is preferred, but any server is accepted if none are available.
+    PreferStandby,
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+/// Load balancing configuration.
+#[cfg(feature = "runtime")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LoadBalanceHosts {
+    /// Hosts and their addresses are tried in the order given.
+    Disable,
+    /// Hosts and their addresses are tried in a random order.
+    Random,
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+/// TLS configuration.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SslMode {
+    /// Do not use TLS.
+    Disable,
+    /// Attempt to connect without TLS, and retry with TLS if the server rejects the connection.
+    Allow,
+    /// Attempt to connect with TLS but allow sessions without.
+    Prefer,
+    /// Require the use of TLS, without verifying the server's certificate unless a root certificate is configured.
+    Require,
+    /// Require the use of TLS and verify that the server's certificate is signed by a trusted certificate authority.
+    VerifyCa,
+    /// Require the use of TLS, verify the server's certificate, and check that its hostname matches the host
+    /// connected to.
+    VerifyFull,
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+/// Channel binding configuration.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ChannelBindingMode {
+    /// Do not use channel binding.
+    Disable,
+    /// Attempt to use channel binding but allow sessions without.
+    Prefer,
+    /// Require the use of channel binding.
+    Require,
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+/// An authentication method which can be requested by the server.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AuthMethod {
+    /// Cleartext password authentication.
+    Password,
+    /// MD5 password authentication.
+    Md5,
+    /// GSSAPI authentication.
+    Gss,
+    /// SSPI authentication.
+    Sspi,
+    /// SCRAM-SHA-256 authentication.
+    ScramSha256,
+    /// No authentication.
+    None,
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+const AUTH_METHODS: [(&str, AuthMethod); 6] = [
+    ("password", AuthMethod::Password),
+    ("md5", AuthMethod::Md5),
+    ("gss", AuthMethod::Gss),
+    ("sspi", AuthMethod::Sspi),
+    ("scram-sha-256", AuthMethod::ScramSha256),
+    ("none", AuthMethod::None),
+];
+
+/// Replication mode configuration.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReplicationMode {
+    /// Physical replication.
+    Physical,
+    /// Logical replication.
+    Logical,
     #[doc(hidden)]
     __NonExhaustive,
 }
@@ -49,18 +144,43 @@ pub(crate) struct Inner {
     pub(crate) dbname: Option<String>,
     pub(crate) options: Option<String>,
     pub(crate) application_name: Option<String>,
+    pub(crate) replication_mode: Option<ReplicationMode>,
+    pub(crate) ssl_mode: Option<SslMode>,
+    pub(crate) ssl_root_cert: Option<PathBuf>,
+    pub(crate) ssl_cert: Option<PathBuf>,
+    pub(crate) ssl_key: Option<PathBuf>,
+    pub(crate) ssl_crl: Option<PathBuf>,
+    pub(crate) ssl_password: Option<Vec<u8>>,
+    pub(crate) service: Option<String>,
+    pub(crate) krb_srvname: Option<String>,
+    pub(crate) channel_binding: Option<ChannelBindingMode>,
+    pub(crate) require_auth: Option<Vec<AuthMethod>>,
     #[cfg(feature = "runtime")]
     pub(crate) host: Vec<Host>,
     #[cfg(feature = "runtime")]
+    pub(crate) hostaddr: Vec<IpAddr>,
+    #[cfg(feature = "runtime")]
     pub(crate) port: Vec<u16>,
     #[cfg(feature = "runtime")]
     pub(crate) connect_timeout: Option<Duration>,
     #[cfg(feature = "runtime")]
-    pub(crate) keepalives: bool,
+    pub(crate) query_timeout: Option<Duration>,
+    #[cfg(feature = "runtime")]
+    pub(crate) keepalives: Option<bool>,
+    #[cfg(feature = "runtime")]
+    pub(crate) keepalives_idle: Option<Duration>,
+    #[cfg(feature = "runtime")]
+    pub(crate) keepalives_interval: Option<Duration>,
+    #[cfg(feature = "runtime")]
+    pub(crate) keepalives_retries: Option<u32>,
+    #[cfg(feature = "runtime")]
+    pub(crate) tcp_user_timeout: Option<Duration>,
+    #[cfg(feature = "runtime")]
+    pub(crate) target_session_attrs: Option<TargetSessionAttrs>,
     #[cfg(feature = "runtime")]
-    pub(crate) keepalives_idle: Duration,
+    pub(crate) passfile: Option<PathBuf>,
     #[cfg(feature = "runtime")]
-    pub(crate) target_session_attrs: TargetSessionAttrs,
+    pub(crate) load_balance_hosts: Option<LoadBalanceHosts>,
 }
 
 /// Connection configuration.
@@ -76,25 +196,78 @@ pub(crate) struct Inner {
 ///
 /// * `user` - The username to authenticate with. Required.
 /// * `password` - The password to authenticate with.
+/// * `passfile` - The path to a password file used to look up the password if one isn't specified. Defaults to the
+///     `PGPASSFILE` environment variable if it is set, or `~/.pgpass` on Unix and `%APPDATA%\postgresql\pgpass.conf`
+///     on Windows otherwise.
 /// * `dbname` - The name of the database to connect to. Defaults to the username.
 /// * `options` - Command line options used to configure the server.
 /// * `application_name` - Sets the `application_name` parameter on the server.
+/// * `replication` - Starts the connection in a replication mode. If set to `database`, the connection can be used for
+///     logical replication from the database specified by `dbname`. If set to `true`, `on`, `yes`, or `1`, the
+///     connection can be used for physical replication. Defaults to a normal connection.
+/// * `sslmode` - Controls the use of TLS. If set to `disable`, TLS will not be used. If set to `allow`, TLS will only
+///     be used if the server rejects a connection without it. If set to `prefer`, TLS will be used if available, but
+///     not used otherwise. If set to `require`, TLS will be required. If set to `verify-ca` or `verify-full`, TLS will
+///     be required and the server's certificate will be verified, along with its hostname in the latter case.
+///     Connecting with `NoTls` fails if TLS is required, and connecting with `RequireTls` fails if it is disabled or
+///     only allowed. Defaults to `prefer`.
+/// * `sslrootcert` - The path to a file of trusted certificate authority certificates used to verify the server's
+///     certificate.
+/// * `sslcert` - The path to the client's certificate file.
+/// * `sslkey` - The path to the client's private key file.
+/// * `sslcrl` - The path to a file of certificate revocation lists used when verifying the server's certificate.
+/// * `sslpassword` - The password used to decrypt the client's private key.
+/// * `channel_binding` - Controls the use of channel binding in the authentication process. If set to `disable`,
+///     channel binding will not be used. If set to `prefer`, channel binding will be used if available, but not used
+///     otherwise. If set to `require`, the connection will fail if channel binding is not used. Defaults to `prefer`.
+/// * `require_auth` - A comma-separated list of the authentication methods the server may request: `password`,
+///     `md5`, `gss`, `sspi`, `scram-sha-256`, or `none` to allow the server to skip authentication. If the methods
+///     are prefixed with `!`, the server may request any method but those. Defaults to allowing all methods.
+/// * `krbsrvname` - The Kerberos service name used for GSSAPI authentication. Defaults to `postgres`.
+/// * `gsslib` - The GSSAPI library to use. Only `gssapi` is supported.
+/// * `service` - The name of a service in the connection service file to take settings from. Settings specified
+///     directly take precedence over those of the service. The service is looked up in the file specified by the
+///     `PGSERVICEFILE` environment variable, or `~/.pg_service.conf` if it is not set, and then in `pg_service.conf`
+///     in the directory specified by the `PGSYSCONFDIR` environment variable.
 /// * `host` - The host to connect to. On Unix platforms, if the host starts with a `/` character it is treated as the
 ///     path to the directory containing Unix domain sockets. Otherwise, it is treated as a hostname. Multiple hosts
 ///     can be specified, separated by commas. Each host will be tried in turn when connecting. Required if connecting
-///     with the `connect` method.
+///     with the `connect` method and `hostaddr` is not set.
+/// * `hostaddr` - The IP address of the host to connect to, which avoids a DNS lookup. The `host` is still used for
+///     TLS certificate verification and password file lookups. Multiple addresses can be specified, separated by
+///     commas, and there must be the same number of addresses as hosts if `host` is set.
+/// * `load_balance_hosts` - Controls the order in which hosts and their addresses are tried. If set to `random`, the
+///     hosts and the addresses each of them resolves to are shuffled before connecting, spreading connections across
+///     servers. Defaults to `disable`.
 /// * `port` - The port to connect to. Multiple ports can be specified, separated by commas. The number of ports must be
 ///     either 1, in which case it will be used for all hosts, or the same as the number of hosts. Defaults to 5432 if
 ///     omitted or the empty string.
 /// * `connect_timeout` - The time limit in seconds applied to each socket-level connection attempt. Note that hostnames
 ///     can resolve to multiple IP addresses, and this limit is applied to each address. Defaults to no timeout.
+///     Connection attempts to the addresses of a host overlap: if an attempt hasn't completed within 250 milliseconds,
+///     the next address is tried alongside it, alternating between IPv6 and IPv4 addresses.
+/// * `query_timeout` - The time limit in seconds applied to each call to `execute` and `query`, measured from when the
+///     server starts processing the query. If it is exceeded, a cancellation request is sent to the server and the call
+///     fails with a timeout error once the server has abandoned the query. The cancellation request is sent without TLS
+///     unless `Client::set_query_timeout_tls` is used. Defaults to no timeout.
 /// * `keepalives` - Controls the use of TCP keepalive. A value of 0 disables keepalive and nonzero integers enable it.
 ///     This option is ignored when connecting with Unix sockets. Defaults to on.
 /// * `keepalives_idle` - The number of seconds of inactivity after which a keepalive message is sent to the server.
 ///     This option is ignored when connecting with Unix sockets. Defaults to 2 hours.
+/// * `keepalives_interval` - The number of seconds after which an unacknowledged keepalive message is retransmitted.
+///     This option is ignored when connecting with Unix sockets, and on platforms without the socket option, such as
+///     Windows and OpenBSD. Defaults to the system default.
+/// * `keepalives_retries` - The number of unacknowledged keepalive messages after which the connection is considered
+///     dead. This option is ignored when connecting with Unix sockets, and on platforms without the socket option, such
+///     as Windows and OpenBSD. Defaults to the system default.
+/// * `tcp_user_timeout` - The number of milliseconds that transmitted data may remain unacknowledged before the
+///     connection is forcibly closed. This option is ignored when connecting with Unix sockets, and on platforms other
+///     than Linux. Defaults to the system default.
 /// * `target_session_attrs` - Specifies requirements of the session. If set to `read-write`, the client will check that
 ///     the `transaction_read_write` session parameter is set to `on`. This can be used to connect to the primary server
-///     in a database cluster as opposed to the secondary read-only mirrors. Defaults to `all`.
+///     in a database cluster as opposed to the secondary read-only mirrors. `read-only` requires the opposite.
+///     `primary` and `standby` check if the server is in hot standby mode, and `prefer-standby` tries all hosts for a
+///     server in hot standby mode before falling back to any server. Defaults to `any`.
 ///
 /// ## Examples
 ///
@@ -152,18 +325,43 @@ impl Config {
             dbname: None,
             options: None,
             application_name: None,
+            replication_mode: None,
+            ssl_mode: None,
+            ssl_root_cert: None,
+            ssl_cert: None,
+            ssl_key: None,
+            ssl_crl: None,
+            ssl_password: None,
+            service: None,
+            krb_srvname: None,
+            channel_binding: None,
+            require_auth: None,
             #[cfg(feature = "runtime")]
             host: vec![],
             #[cfg(feature = "runtime")]
+            hostaddr: vec![],
+            #[cfg(feature = "runtime")]
             port: vec![],
             #[cfg(feature = "runtime")]
             connect_timeout: None,
             #[cfg(feature = "runtime")]
-            keepalives: true,
+            query_timeout: None,
+            #[cfg(feature = "runtime")]
+            keepalives: None,
+            #[cfg(feature = "runtime")]
+            keepalives_idle: None,
+            #[cfg(feature = "runtime")]
+            keepalives_interval: None,
+            #[cfg(feature = "runtime")]
+            keepalives_retries: None,
             #[cfg(feature = "runtime")]
-            keepalives_idle: Duration::from_secs(2 * 60 * 60),
+            tcp_user_timeout: None,
             #[cfg(feature = "runtime")]
-            target_session_attrs: TargetSessionAttrs::Any,
+            target_session_attrs: None,
+            #[cfg(feature = "runtime")]
+            passfile: None,
+            #[cfg(feature = "runtime")]
+            load_balance_hosts: None,
         }))
     }
 
@@ -204,6 +402,99 @@ impl Config {
         self
     }
 
+    /// Sets the replication mode of the connection.
+    ///
+    /// A replication connection speaks the streaming replication protocol, and can only be used to issue replication
+    /// commands and, in logical mode, simple SQL queries.
+    pub fn replication_mode(&mut self, replication_mode: ReplicationMode) -> &mut Config {
+        Arc::make_mut(&mut self.0).replication_mode = Some(replication_mode);
+        self
+    }
+
+    /// Sets the TLS mode of the connection.
+    ///
+    /// This is honored by TLS backends' `MakeTlsMode` implementations when passed directly to `connect`. Defaults to
+    /// `Prefer`.
+    pub fn ssl_mode(&mut self, ssl_mode: SslMode) -> &mut Config {
+        Arc::make_mut(&mut self.0).ssl_mode = Some(ssl_mode);
+        self
+    }
+
+    /// Gets the TLS mode of the connection, if one has been set.
+    pub fn get_ssl_mode(&self) -> Option<SslMode> {
+        self.0.ssl_mode
+    }
+
+    /// Sets the path to a file of trusted certificate authority certificates used to verify the server's certificate.
+    pub fn ssl_root_cert<T>(&mut self, ssl_root_cert: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        Arc::make_mut(&mut self.0).ssl_root_cert = Some(ssl_root_cert.as_ref().to_path_buf());
+        self
+    }
+
+    /// Gets the path to the trusted certificate authority certificates, if one has been set.
+    pub fn get_ssl_root_cert(&self) -> Option<&Path> {
+        self.0.ssl_root_cert.as_ref().map(|p| &**p)
+    }
+
+    /// Sets the path to the client's certificate file.
+    pub fn ssl_cert<T>(&mut self, ssl_cert: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        Arc::make_mut(&mut self.0).ssl_cert = Some(ssl_cert.as_ref().to_path_buf());
+        self
+    }
+
+    /// Gets the path to the client's certificate file, if one has been set.
+    pub fn get_ssl_cert(&self) -> Option<&Path> {
+        self.0.ssl_cert.as_ref().map(|p| &**p)
+    }
+
+    /// Sets the path to the client's private key file.
+    pub fn ssl_key<T>(&mut self, ssl_key: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        Arc::make_mut(&mut self.0).ssl_key = Some(ssl_key.as_ref().to_path_buf());
+        self
+    }
+
+    /// Gets the path to the client's private key file, if one has been set.
+    pub fn get_ssl_key(&self) -> Option<&Path> {
+        self.0.ssl_key.as_ref().map(|p| &**p)
+    }
+
+    /// Sets the path to a file of certificate revocation lists used when verifying the server's certificate.
+    pub fn ssl_crl<T>(&mut self, ssl_crl: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        Arc::make_mut(&mut self.0).ssl_crl = Some(ssl_crl.as_ref().to_path_buf());
+        self
+    }
+
+    /// Gets the path to the certificate revocation lists, if one has been set.
+    pub fn get_ssl_crl(&self) -> Option<&Path> {
+        self.0.ssl_crl.as_ref().map(|p| &**p)
+    }
+
+    /// Sets the password used to decrypt the client's private key.
+    pub fn ssl_password<T>(&mut self, ssl_password: T) -> &mut Config
+    where
+        T: AsRef<[u8]>,
+    {
+        Arc::make_mut(&mut self.0).ssl_password = Some(ssl_password.as_ref().to_vec());
+        self
+    }
+
+    /// Gets the password used to decrypt the client's private key, if one has been set.
+    pub fn get_ssl_password(&self) -> Option<&[u8]> {
+        self.0.ssl_password.as_ref().map(|p| &**p)
+    }
+
     /// Adds a host to the configuration.
     ///
     /// Multiple hosts can be specified by calling this method multiple times, and each will be tried in order. On Unix
@@ -241,6 +532,19 @@ impl Config {
         self
     }
 
+    /// Adds a host IP address to the configuration.
+    ///
+    /// The address is connected to directly in place of the hostname, which is still used for TLS certificate
+    /// verification and password file lookups. Multiple addresses can be specified by calling this method multiple
+    /// times. There must be the same number of addresses as hosts, unless no hosts are specified at all.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn hostaddr(&mut self, hostaddr: IpAddr) -> &mut Config {
+        Arc::make_mut(&mut self.0).hostaddr.push(hostaddr);
+        self
+    }
+
     /// Adds a port to the configuration.
     ///
     /// Multiple ports can be specified by calling this method multiple times. There must either be no ports, in which
@@ -266,6 +570,21 @@ impl Config {
         self
     }
 
+    /// Sets the default time limit applied to each call to `execute` and `query`.
+    ///
+    /// If a query runs for longer than this, a cancellation request is sent to the server and the query fails with a
+    /// timeout error. Time spent waiting for earlier queries on the same connection to finish doesn't count towards the
+    /// limit. The limit can be overridden for an individual query. Defaults to no limit.
+    ///
+    /// The cancellation request is sent without TLS unless `Client::set_query_timeout_tls` is used.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn query_timeout(&mut self, query_timeout: Duration) -> &mut Config {
+        Arc::make_mut(&mut self.0).query_timeout = Some(query_timeout);
+        self
+    }
+
     /// Controls the use of TCP keepalive.
     ///
     /// This is ignored for Unix domain socket connections. Defaults to `true`.
@@ -273,7 +592,7 @@ impl Config {
     /// Requires the `runtime` Cargo feature (enabled by default).
     #[cfg(feature = "runtime")]
     pub fn keepalives(&mut self, keepalives: bool) -> &mut Config {
-        Arc::make_mut(&mut self.0).keepalives = keepalives;
+        Arc::make_mut(&mut self.0).keepalives = Some(keepalives);
         self
     }
 
@@ -284,14 +603,50 @@ impl Config {
     /// Requires the `runtime` Cargo feature (enabled by default).
     #[cfg(feature = "runtime")]
     pub fn keepalives_idle(&mut self, keepalives_idle: Duration) -> &mut Config {
-        Arc::make_mut(&mut self.0).keepalives_idle = keepalives_idle;
+        Arc::make_mut(&mut self.0).keepalives_idle = Some(keepalives_idle);
+        self
+    }
+
+    /// Sets the time after which an unacknowledged keepalive packet is retransmitted.
+    ///
+    /// This is ignored for Unix domain sockets, if the `keepalives` option is disabled, or on platforms without the
+    /// socket option, such as Windows and OpenBSD. Defaults to the system default.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn keepalives_interval(&mut self, keepalives_interval: Duration) -> &mut Config {
+        Arc::make_mut(&mut self.0).keepalives_interval = Some(keepalives_interval);
+        self
+    }
+
+    /// Sets the number of unacknowledged keepalive packets after which the connection is considered dead.
+    ///
+    /// This is ignored for Unix domain sockets, if the `keepalives` option is disabled, or on platforms without the
+    /// socket option, such as Windows and OpenBSD. Defaults to the system default.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn keepalives_retries(&mut self, keepalives_retries: u32) -> &mut Config {
+        Arc::make_mut(&mut self.0).keepalives_retries = Some(keepalives_retries);
+        self
+    }
+
+    /// Sets the time that transmitted data may remain unacknowledged before the connection is forcibly closed.
+    ///
+    /// Unlike keepalives, this also detects a dead connection while data is being sent. This is ignored for Unix
+    /// domain sockets and on platforms other than Linux. Defaults to the system default.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn tcp_user_timeout(&mut self, tcp_user_timeout: Duration) -> &mut Config {
+        Arc::make_mut(&mut self.0).tcp_user_timeout = Some(tcp_user_timeout);
         self
     }
 
     /// Sets the requirements of the session.
     ///
     /// This can be used to connect to the primary server in a clustered database rather than one of the read-only
-    /// secondary servers. Defaults to `Any`.
+    /// secondary servers, or the reverse. Defaults to `Any`.
     ///
     /// Requires the `runtime` Cargo feature (enabled by default).
     #[cfg(feature = "runtime")]
@@ -299,10 +654,246 @@ impl Config {
         &mut self,
         target_session_attrs: TargetSessionAttrs,
     ) -> &mut Config {
-        Arc::make_mut(&mut self.0).target_session_attrs = target_session_attrs;
+        Arc::make_mut(&mut self.0).target_session_attrs = Some(target_session_attrs);
         self
     }
 
+    /// Controls the order in which hosts and their addresses are tried.
+    ///
+    /// Defaults to `Disable`.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn load_balance_hosts(&mut self, load_balance_hosts: LoadBalanceHosts) -> &mut Config {
+        Arc::make_mut(&mut self.0).load_balance_hosts = Some(load_balance_hosts);
+        self
+    }
+
+    /// Sets the path to the password file.
+    ///
+    /// The file consists of lines of the form `hostname:port:database:username:password`, and the password of the
+    /// first line matching the connection is used if no password has been set. Any of the first four fields can be
+    /// `*` to match anything, and `:` and `\` characters should be backslash-escaped. Connections over Unix sockets
+    /// match a hostname of `localhost`. Like libpq, the file is ignored on Unix systems if it is accessible by the
+    /// group or world.
+    ///
+    /// Defaults to the `PGPASSFILE` environment variable if it is set, or `~/.pgpass` on Unix and
+    /// `%APPDATA%\postgresql\pgpass.conf` on Windows otherwise.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn passfile<T>(&mut self, passfile: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        Arc::make_mut(&mut self.0).passfile = Some(passfile.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the channel binding behavior.
+    ///
+    /// Channel binding ties SCRAM authentication to the TLS session, and requires TLS and a server which supports
+    /// `SCRAM-SHA-256-PLUS`. Defaults to `Prefer`.
+    pub fn channel_binding(&mut self, channel_binding: ChannelBindingMode) -> &mut Config {
+        Arc::make_mut(&mut self.0).channel_binding = Some(channel_binding);
+        self
+    }
+
+    /// Gets the channel binding behavior.
+    pub fn get_channel_binding(&self) -> ChannelBindingMode {
+        self.0.channel_binding.unwrap_or(ChannelBindingMode::Prefer)
+    }
+
+    /// Sets the authentication methods the server is allowed to request.
+    ///
+    /// The connection will fail if the server requests any other method. `AuthMethod::None` allows the server to
+    /// skip authentication entirely. Defaults to allowing all methods.
+    pub fn require_auth(&mut self, require_auth: &[AuthMethod]) -> &mut Config {
+        Arc::make_mut(&mut self.0).require_auth = Some(require_auth.to_vec());
+        self
+    }
+
+    /// Gets the authentication methods the server is allowed to request, if they have been restricted.
+    pub fn get_require_auth(&self) -> Option<&[AuthMethod]> {
+        self.0.require_auth.as_ref().map(|m| &**m)
+    }
+
+    /// Sets the Kerberos service name used for GSSAPI authentication.
+    ///
+    /// GSSAPI authentication requires the `gssapi` Cargo feature. Defaults to `postgres`.
+    pub fn krb_srvname(&mut self, krb_srvname: &str) -> &mut Config {
+        Arc::make_mut(&mut self.0).krb_srvname = Some(krb_srvname.to_string());
+        self
+    }
+
+    /// Gets the Kerberos service name used for GSSAPI authentication, if one has been set.
+    pub fn get_krb_srvname(&self) -> Option<&str> {
+        self.0.krb_srvname.as_ref().map(|s| &**s)
+    }
+
+    /// Applies the settings of a service in the connection service file.
+    ///
+    /// Only settings which have not already been set are taken from the service. The service is looked up in the file
+    /// specified by the `PGSERVICEFILE` environment variable, or `~/.pg_service.conf` if it is not set, and then in
+    /// `pg_service.conf` in the directory specified by the `PGSYSCONFDIR` environment variable, like libpq.
+    pub fn service(&mut self, service: &str) -> Result<&mut Config, Error> {
+        for (key, value) in service::find_service(service)? {
+            if !self.is_set(&key) {
+                self.param(&key, &value)?;
+            }
+        }
+        Arc::make_mut(&mut self.0).service = Some(service.to_string());
+        Ok(self)
+    }
+
+    /// Gets the name of the service the configuration's settings were taken from, if one has been set.
+    pub fn get_service(&self) -> Option<&str> {
+        self.0.service.as_ref().map(|s| &**s)
+    }
+
+    // determines if a setting has been explicitly set
+    fn is_set(&self, key: &str) -> bool {
+        match key {
+            "user" => self.0.user.is_some(),
+            "password" => self.0.password.is_some(),
+            "dbname" => self.0.dbname.is_some(),
+            "options" => self.0.options.is_some(),
+            "application_name" => self.0.application_name.is_some(),
+            "replication" => self.0.replication_mode.is_some(),
+            "sslmode" => self.0.ssl_mode.is_some(),
+            "sslrootcert" => self.0.ssl_root_cert.is_some(),
+            "sslcert" => self.0.ssl_cert.is_some(),
+            "sslkey" => self.0.ssl_key.is_some(),
+            "sslcrl" => self.0.ssl_crl.is_some(),
+            "sslpassword" => self.0.ssl_password.is_some(),
+            "krbsrvname" => self.0.krb_srvname.is_some(),
+            "channel_binding" => self.0.channel_binding.is_some(),
+            "require_auth" => self.0.require_auth.is_some(),
+            #[cfg(feature = "runtime")]
+            "host" => !self.0.host.is_empty(),
+            #[cfg(feature = "runtime")]
+            "hostaddr" => !self.0.hostaddr.is_empty(),
+            #[cfg(feature = "runtime")]
+            "port" => !self.0.port.is_empty(),
+            #[cfg(feature = "runtime")]
+            "connect_timeout" => self.0.connect_timeout.is_some(),
+            #[cfg(feature = "runtime")]
+            "query_timeout" => self.0.query_timeout.is_some(),
+            #[cfg(feature = "runtime")]
+            "keepalives" => self.0.keepalives.is_some(),
+            #[cfg(feature = "runtime")]
+            "keepalives_idle" => self.0.keepalives_idle.is_some(),
+            #[cfg(feature = "runtime")]
+            "keepalives_interval" => self.0.keepalives_interval.is_some(),
+            #[cfg(feature = "runtime")]
+            "keepalives_retries" => self.0.keepalives_retries.is_some(),
+            #[cfg(feature = "runtime")]
+            "tcp_user_timeout" => self.0.tcp_user_timeout.is_some(),
+            #[cfg(feature = "runtime")]
+            "target_session_attrs" => self.0.target_session_attrs.is_some(),
+            #[cfg(feature = "runtime")]
+            "passfile" => self.0.passfile.is_some(),
+            #[cfg(feature = "runtime")]
+            "load_balance_hosts" => self.0.load_balance_hosts.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Creates a new configuration from the libpq environment variables.
+    ///
+    /// See `merge_env` for the variables which are read.
+    pub fn from_env() -> Result<Config, Error> {
+        let mut config = Config::new();
+        config.merge_env()?;
+        Ok(config)
+    }
+
+    /// Fills in settings which have not already been set from the libpq environment variables.
+    ///
+    /// This allows a connection string to be combined with the environment, with values in the string taking
+    /// precedence:
+    ///
+    /// ```
+    /// # fn f() -> Result<(), tokio_postgres::Error> {
+    /// let mut config = "dbname=app".parse::<tokio_postgres::Config>()?;
+    /// config.merge_env()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The following variables are supported, each corresponding to the key of the same name in connection strings.
+    /// Empty variables are ignored.
+    ///
+    /// * `PGHOST` - `host`
+    /// * `PGHOSTADDR` - `hostaddr`
+    /// * `PGPORT` - `port`
+    /// * `PGUSER` - `user`
+    /// * `PGDATABASE` - `dbname`
+    /// * `PGPASSWORD` - `password`
+    /// * `PGAPPNAME` - `application_name`
+    /// * `PGCONNECT_TIMEOUT` - `connect_timeout`
+    /// * `PGOPTIONS` - `options`
+    /// * `PGSSLMODE` - `sslmode`
+    /// * `PGTARGETSESSIONATTRS` - `target_session_attrs`
+    /// * `PGLOADBALANCEHOSTS` - `load_balance_hosts`
+    ///
+    /// `PGHOST`, `PGHOSTADDR`, `PGPORT`, `PGCONNECT_TIMEOUT`, `PGTARGETSESSIONATTRS`, and `PGLOADBALANCEHOSTS` are only
+    /// read if the `runtime` Cargo feature is enabled.
+    pub fn merge_env(&mut self) -> Result<&mut Config, Error> {
+        if self.0.user.is_none() {
+            self.env_param("PGUSER", "user")?;
+        }
+        if self.0.dbname.is_none() {
+            self.env_param("PGDATABASE", "dbname")?;
+        }
+        if self.0.password.is_none() {
+            self.env_param("PGPASSWORD", "password")?;
+        }
+        if self.0.application_name.is_none() {
+            self.env_param("PGAPPNAME", "application_name")?;
+        }
+        if self.0.options.is_none() {
+            self.env_param("PGOPTIONS", "options")?;
+        }
+        if self.0.ssl_mode.is_none() {
+            self.env_param("PGSSLMODE", "sslmode")?;
+        }
+        #[cfg(feature = "runtime")]
+        {
+            if self.0.host.is_empty() {
+                self.env_param("PGHOST", "host")?;
+            }
+            if self.0.hostaddr.is_empty() {
+                self.env_param("PGHOSTADDR", "hostaddr")?;
+            }
+            if self.0.port.is_empty() {
+                self.env_param("PGPORT", "port")?;
+            }
+            if self.0.connect_timeout.is_none() {
+                self.env_param("PGCONNECT_TIMEOUT", "connect_timeout")?;
+            }
+            if self.0.target_session_attrs.is_none() {
+                self.env_param("PGTARGETSESSIONATTRS", "target_session_attrs")?;
+            }
+            if self.0.load_balance_hosts.is_none() {
+                self.env_param("PGLOADBALANCEHOSTS", "load_balance_hosts")?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    fn env_param(&mut self, var: &str, key: &'static str) -> Result<(), Error> {
+        match env::var(var) {
+            Ok(ref value) if value.is_empty() => Ok(()),
+            Ok(value) => self.param(key, &value),
+            Err(env::VarError::NotPresent) => Ok(()),
+            Err(env::VarError::NotUnicode(_)) => {
+                Err(Error::config_parse(Box::new(InvalidValue(key))))
+            }
+        }
+    }
+
     fn param(&mut self, key: &str, value: &str) -> Result<(), Error> {
         match key {
             "user" => {
@@ -320,6 +911,43 @@ impl Config {
             "application_name" => {
                 self.application_name(&value);
             }
+            "replication" => match value {
+                "database" => {
+                    self.replication_mode(ReplicationMode::Logical);
+                }
+                "true" | "on" | "yes" | "1" => {
+                    self.replication_mode(ReplicationMode::Physical);
+                }
+                "false" | "off" | "no" | "0" => {}
+                _ => return Err(Error::config_parse(Box::new(InvalidValue("replication")))),
+            },
+            "sslmode" => {
+                let ssl_mode = match value {
+                    "disable" => SslMode::Disable,
+                    "allow" => SslMode::Allow,
+                    "prefer" => SslMode::Prefer,
+                    "require" => SslMode::Require,
+                    "verify-ca" => SslMode::VerifyCa,
+                    "verify-full" => SslMode::VerifyFull,
+                    _ => return Err(Error::config_parse(Box::new(InvalidValue("sslmode")))),
+                };
+                self.ssl_mode(ssl_mode);
+            }
+            "sslrootcert" => {
+                self.ssl_root_cert(value);
+            }
+            "sslcert" => {
+                self.ssl_cert(value);
+            }
+            "sslkey" => {
+                self.ssl_key(value);
+            }
+            "sslcrl" => {
+                self.ssl_crl(value);
+            }
+            "sslpassword" => {
+                self.ssl_password(value);
+            }
             #[cfg(feature = "runtime")]
             "host" => {
                 for host in value.split(',') {
@@ -327,6 +955,15 @@ impl Config {
                 }
             }
             #[cfg(feature = "runtime")]
+            "hostaddr" => {
+                for hostaddr in value.split(',') {
+                    let hostaddr = hostaddr
+                        .parse()
+                        .map_err(|_| Error::config_parse(Box::new(InvalidValue("hostaddr"))))?;
+                    self.hostaddr(hostaddr);
+                }
+            }
+            #[cfg(feature = "runtime")]
             "port" => {
                 for port in value.split(',') {
                     let port = if port.is_empty() {
@@ -348,6 +985,15 @@ impl Config {
                 }
             }
             #[cfg(feature = "runtime")]
+            "query_timeout" => {
+                let timeout = value
+                    .parse::<i64>()
+                    .map_err(|_| Error::config_parse(Box::new(InvalidValue("query_timeout"))))?;
+                if timeout > 0 {
+                    self.query_timeout(Duration::from_secs(timeout as u64));
+                }
+            }
+            #[cfg(feature = "runtime")]
             "keepalives" => {
                 let keepalives = value
                     .parse::<u64>()
@@ -364,10 +1010,41 @@ impl Config {
                 }
             }
             #[cfg(feature = "runtime")]
+            "keepalives_interval" => {
+                let keepalives_interval = value.parse::<i64>().map_err(|_| {
+                    Error::config_parse(Box::new(InvalidValue("keepalives_interval")))
+                })?;
+                if keepalives_interval > 0 {
+                    self.keepalives_interval(Duration::from_secs(keepalives_interval as u64));
+                }
+            }
+            #[cfg(feature = "runtime")]
+            "keepalives_retries" => {
+                let keepalives_retries = value.parse::<i64>().map_err(|_| {
+                    Error::config_parse(Box::new(InvalidValue("keepalives_retries")))
+                })?;
+                if keepalives_retries > 0 {
+                    self.keepalives_retries(keepalives_retries as u32);
+                }
+            }
+            #[cfg(feature = "runtime")]
+            "tcp_user_timeout" => {
+                let tcp_user_timeout = value
+                    .parse::<i64>()
+                    .map_err(|_| Error::config_parse(Box::new(InvalidValue("tcp_user_timeout"))))?;
+                if tcp_user_timeout > 0 {
+                    self.tcp_user_timeout(Duration::from_millis(tcp_user_timeout as u64));
+                }
+            }
+            #[cfg(feature = "runtime")]
             "target_session_attrs" => {
                 let target_session_attrs = match &*value {
                     "any" => TargetSessionAttrs::Any,
                     "read-write" => TargetSessionAttrs::ReadWrite,
+                    "read-only" => TargetSessionAttrs::ReadOnly,
+                    "primary" => TargetSessionAttrs::Primary,
+                    "standby" => TargetSessionAttrs::Standby,
+                    "prefer-standby" => TargetSessionAttrs::PreferStandby,
                     _ => {
                         return Err(Error::config_parse(Box::new(InvalidValue(
                             "target_session_attrs",
@@ -376,6 +1053,53 @@ impl Config {
                 };
                 self.target_session_attrs(target_session_attrs);
             }
+            #[cfg(feature = "runtime")]
+            "passfile" => {
+                self.passfile(value);
+            }
+            #[cfg(feature = "runtime")]
+            "load_balance_hosts" => {
+                let load_balance_hosts = match value {
+                    "disable" => LoadBalanceHosts::Disable,
+                    "random" => LoadBalanceHosts::Random,
+                    _ => {
+                        return Err(Error::config_parse(Box::new(InvalidValue(
+                            "load_balance_hosts",
+                        ))));
+                    }
+                };
+                self.load_balance_hosts(load_balance_hosts);
+            }
+            "krbsrvname" => {
+                self.krb_srvname(value);
+            }
+            "channel_binding" => {
+                let channel_binding = match value {
+                    "disable" => ChannelBindingMode::Disable,
+                    "prefer" => ChannelBindingMode::Prefer,
+                    "require" => ChannelBindingMode::Require,
+                    _ => {
+                        return Err(Error::config_parse(Box::new(InvalidValue(
+                            "channel_binding",
+                        ))))
+                    }
+                };
+                self.channel_binding(channel_binding);
+            }
+            "require_auth" => {
+                let require_auth = parse_require_auth(value)
+                    .ok_or_else(|| Error::config_parse(Box::new(InvalidValue("require_auth"))))?;
+                self.require_auth(&require_auth);
+            }
+            "gsslib" => {
+                if value != "gssapi" {
+                    return Err(Error::config_parse(Box::new(InvalidValue("gsslib"))));
+                }
+            }
+            // the service is applied once the rest of the string has been parsed
+            "service" => {
+                Arc::make_mut(&mut self.0).service = Some(value.to_string());
+            }
             key => {
                 return Err(Error::config_parse(Box::new(UnknownOption(
                     key.to_string(),
@@ -409,14 +1133,55 @@ impl Config {
     }
 }
 
+// either all methods are listed, or all are negated with `!` and the server may use any other method
+fn parse_require_auth(value: &str) -> Option<Vec<AuthMethod>> {
+    let negated = value.starts_with('!');
+
+    let mut methods = vec![];
+    for method in value.split(',') {
+        let method = if negated {
+            if !method.starts_with('!') {
+                return None;
+            }
+            &method[1..]
+        } else {
+            method
+        };
+        let method = AUTH_METHODS
+            .iter()
+            .find(|(name, _)| *name == method)
+            .map(|(_, method)| *method)?;
+        if methods.contains(&method) {
+            return None;
+        }
+        methods.push(method);
+    }
+
+    if negated {
+        methods = AUTH_METHODS
+            .iter()
+            .map(|(_, method)| *method)
+            .filter(|method| !methods.contains(method))
+            .collect();
+    }
+
+    Some(methods)
+}
+
 impl FromStr for Config {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Config, Error> {
-        match UrlParser::parse(s)? {
-            Some(config) => Ok(config),
-            None => Parser::parse(s),
+        let mut config = match UrlParser::parse(s)? {
+            Some(config) => config,
+            None => Parser::parse(s)?,
+        };
+
+        if let Some(service) = config.0.service.clone() {
+            config.service(&service)?;
         }
+
+        Ok(config)
     }
 }
 
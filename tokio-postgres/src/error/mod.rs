@@ -336,6 +336,8 @@ enum Kind {
     Tls,
     ToSql,
     FromSql,
+    FromSqlColumn(String),
+    Column(String),
     CopyInStream,
     Closed,
     Db,
@@ -346,6 +348,10 @@ enum Kind {
     Config,
     #[cfg(feature = "runtime")]
     Connect,
+    #[cfg(feature = "runtime")]
+    Timeout,
+    #[cfg(feature = "runtime")]
+    QueryTimeout,
 }
 
 struct ErrorInner {
@@ -367,24 +373,31 @@ impl fmt::Debug for Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match self.0.kind {
-            Kind::Io => "error communicating with the server",
-            Kind::UnexpectedMessage => "unexpected message from server",
-            Kind::Tls => "error performing TLS handshake",
-            Kind::ToSql => "error serializing a value",
-            Kind::FromSql => "error deserializing a value",
-            Kind::CopyInStream => "error from a copy_in stream",
-            Kind::Closed => "connection closed",
-            Kind::Db => "db error",
-            Kind::Parse => "error parsing response from server",
-            Kind::Encode => "error encoding message to server",
-            Kind::Authentication => "authentication error",
-            Kind::ConfigParse => "invalid connection string",
-            Kind::Config => "invalid configuration",
+        match self.0.kind {
+            Kind::Io => fmt.write_str("error communicating with the server")?,
+            Kind::UnexpectedMessage => fmt.write_str("unexpected message from server")?,
+            Kind::Tls => fmt.write_str("error performing TLS handshake")?,
+            Kind::ToSql => fmt.write_str("error serializing a value")?,
+            Kind::FromSql => fmt.write_str("error deserializing a value")?,
+            Kind::FromSqlColumn(ref column) => {
+                write!(fmt, "error deserializing column `{}`", column)?
+            }
+            Kind::Column(ref column) => write!(fmt, "invalid column `{}`", column)?,
+            Kind::CopyInStream => fmt.write_str("error from a copy_in stream")?,
+            Kind::Closed => fmt.write_str("connection closed")?,
+            Kind::Db => fmt.write_str("db error")?,
+            Kind::Parse => fmt.write_str("error parsing response from server")?,
+            Kind::Encode => fmt.write_str("error encoding message to server")?,
+            Kind::Authentication => fmt.write_str("authentication error")?,
+            Kind::ConfigParse => fmt.write_str("invalid connection string")?,
+            Kind::Config => fmt.write_str("invalid configuration")?,
             #[cfg(feature = "runtime")]
-            Kind::Connect => "error connecting to server",
-        };
-        fmt.write_str(s)?;
+            Kind::Connect => fmt.write_str("error connecting to server")?,
+            #[cfg(feature = "runtime")]
+            Kind::Timeout => fmt.write_str("timed out")?,
+            #[cfg(feature = "runtime")]
+            Kind::QueryTimeout => fmt.write_str("query timed out")?,
+        }
         if let Some(ref cause) = self.0.cause {
             write!(fmt, ": {}", cause)?;
         }
@@ -414,6 +427,17 @@ impl Error {
             .map(|e| e.code())
     }
 
+    /// Determines if the error was caused by a timeout.
+    ///
+    /// This covers both query timeouts and timeouts waiting to check a connection out of a pool.
+    pub fn is_timeout(&self) -> bool {
+        match self.0.kind {
+            #[cfg(feature = "runtime")]
+            Kind::Timeout | Kind::QueryTimeout => true,
+            _ => false,
+        }
+    }
+
     fn new(kind: Kind, cause: Option<Box<dyn error::Error + Sync + Send>>) -> Error {
         Error(Box::new(ErrorInner { kind, cause }))
     }
@@ -451,6 +475,14 @@ impl Error {
         Error::new(Kind::FromSql, Some(e))
     }
 
+    pub(crate) fn from_sql_column(e: Box<dyn error::Error + Sync + Send>, column: String) -> Error {
+        Error::new(Kind::FromSqlColumn(column), Some(e))
+    }
+
+    pub(crate) fn column(column: String) -> Error {
+        Error::new(Kind::Column(column), None)
+    }
+
     pub(crate) fn copy_in_stream<E>(e: E) -> Error
     where
         E: Into<Box<dyn error::Error + Sync + Send>>,
@@ -482,4 +514,14 @@ impl Error {
     pub(crate) fn connect(e: io::Error) -> Error {
         Error::new(Kind::Connect, Some(Box::new(e)))
     }
+
+    #[cfg(feature = "runtime")]
+    pub(crate) fn timeout() -> Error {
+        Error::new(Kind::Timeout, None)
+    }
+
+    #[cfg(feature = "runtime")]
+    pub(crate) fn query_timeout(e: Error) -> Error {
+        Error::new(Kind::QueryTimeout, Some(Box::new(e)))
+    }
 }
@@ -0,0 +1,93 @@
+//! GSSAPI authentication support.
+//!
+//! Requires the `gssapi` Cargo feature, which pulls in the `libgssapi` bindings to the system GSSAPI library. Without
+//! it, authentication requests from the server fail with an error.
+
+pub(crate) use self::imp::GssContext;
+
+#[cfg(feature = "gssapi")]
+mod imp {
+    use libgssapi::context::{ClientCtx, CtxFlags, SecurityContext};
+    use libgssapi::name::Name;
+    use libgssapi::oid::GSS_NT_HOSTBASED_SERVICE;
+    use std::error;
+    use std::fmt;
+
+    use crate::Error;
+
+    /// The client side of a GSSAPI security context.
+    pub struct GssContext(ClientCtx);
+
+    impl GssContext {
+        /// Creates a context authenticating to the `service@host` principal.
+        pub fn new(service: &str, host: &str) -> Result<GssContext, Error> {
+            let target = format!("{}@{}", service, host);
+            let name = Name::new(target.as_bytes(), Some(&GSS_NT_HOSTBASED_SERVICE))
+                .map_err(|e| gss_error("could not import name", e))?;
+
+            Ok(GssContext(ClientCtx::new(
+                None,
+                name,
+                CtxFlags::GSS_C_MUTUAL_FLAG,
+                None,
+            )))
+        }
+
+        /// Processes a token from the server, if one was received, returning the token to send in response.
+        pub fn step(&mut self, input: Option<&[u8]>) -> Result<Vec<u8>, Error> {
+            match self.0.step(input, None) {
+                Ok(Some(token)) => Ok(token.to_vec()),
+                Ok(None) => Ok(vec![]),
+                Err(e) => Err(gss_error("could not initiate context", e)),
+            }
+        }
+
+        /// Determines if the context has been fully established.
+        pub fn is_complete(&self) -> bool {
+            self.0.is_complete()
+        }
+    }
+
+    #[derive(Debug)]
+    struct GssError {
+        context: &'static str,
+        error: libgssapi::error::Error,
+    }
+
+    impl fmt::Display for GssError {
+        fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(fmt, "GSSAPI error: {}: {}", self.context, self.error)
+        }
+    }
+
+    impl error::Error for GssError {}
+
+    fn gss_error(context: &'static str, error: libgssapi::error::Error) -> Error {
+        Error::authentication(Box::new(GssError { context, error }))
+    }
+}
+
+#[cfg(not(feature = "gssapi"))]
+mod imp {
+    use void::Void;
+
+    use crate::Error;
+
+    pub struct GssContext(Void);
+
+    impl GssContext {
+        pub fn new(_: &str, _: &str) -> Result<GssContext, Error> {
+            Err(Error::authentication(
+                "GSSAPI authentication requires the `gssapi` Cargo feature".into(),
+            ))
+        }
+
+        pub fn step(&mut self, _: Option<&[u8]>) -> Result<Vec<u8>, Error> {
+            match self.0 {}
+        }
+
+        pub fn is_complete(&self) -> bool {
+            match self.0 {}
+        }
+    }
+}
@@ -82,26 +82,49 @@
 //! The client works with arbitrary `AsyncRead + AsyncWrite` streams. Convenience APIs are provided to handle the
 //! connection process, but these are gated by the `runtime` Cargo feature, which is enabled by default. If disabled,
 //! all dependence on the tokio runtime is removed.
+//!
+//! # GSSAPI
+//!
+//! GSSAPI (Kerberos) authentication is gated by the `gssapi` Cargo feature, which is disabled by default. It uses the
+//! `libgssapi` crate, so building with it enabled requires the GSSAPI development files (`libkrb5-dev` on Debian,
+//! `krb5-devel` on Fedora) and libclang to generate the bindings, and the resulting binary links against the system's
+//! `libgssapi_krb5` (or Heimdal's `libgssapi`). Credentials are taken from the default Kerberos credential cache, so
+//! `kinit` must be run before connecting.
 #![warn(rust_2018_idioms, clippy::all)]
 
 use bytes::{Bytes, IntoBuf};
-use futures::{try_ready, Async, Future, Poll, Stream};
+use futures::{try_ready, Async, Future, Poll, Sink, StartSend, Stream};
 use std::error::Error as StdError;
+#[cfg(feature = "runtime")]
+use std::time::Duration;
 use tokio_io::{AsyncRead, AsyncWrite};
 
 pub use crate::config::*;
 pub use crate::error::*;
+use crate::replication::{
+    BaseBackup, BaseBackupOptions, IdentifySystem, LogicalReplicationStream, Lsn,
+    ReplicationStream, TimelineHistory,
+};
 pub use crate::row::*;
 #[cfg(feature = "runtime")]
 pub use crate::socket::Socket;
 pub use crate::stmt::Column;
 pub use crate::tls::*;
 use crate::types::{ToSql, Type};
+#[cfg(feature = "derive")]
+pub use postgres_derive::FromRow;
 
 mod config;
 pub mod error;
+mod gss;
+#[cfg(feature = "runtime")]
+mod passfile;
+#[cfg(feature = "runtime")]
+pub mod pool;
 mod proto;
+pub mod replication;
 mod row;
+mod service;
 #[cfg(feature = "runtime")]
 mod socket;
 mod stmt;
@@ -146,6 +169,36 @@ impl Client {
         Prepare(self.0.prepare(self.0.next_statement(), query, param_types))
     }
 
+    /// Like `prepare`, but uses the client's statement cache.
+    ///
+    /// If a statement for the same query has already been prepared through this method, it is returned without a
+    /// round trip to the server. Otherwise the statement is prepared and added to the cache, evicting the least
+    /// recently used entry if the cache is full. A cached statement is evicted automatically if the server reports that
+    /// its plan has been invalidated, for example by a schema change which alters its result type.
+    pub fn prepare_cached(&mut self, query: &str) -> PrepareCached {
+        self.prepare_typed_cached(query, &[])
+    }
+
+    /// Like `prepare_typed`, but uses the client's statement cache.
+    ///
+    /// Statements are cached by both their query and parameter types.
+    pub fn prepare_typed_cached(&mut self, query: &str, param_types: &[Type]) -> PrepareCached {
+        PrepareCached(self.0.prepare_cached(query, param_types))
+    }
+
+    /// Sets the maximum number of statements held in the client's statement cache.
+    ///
+    /// Least recently used statements are evicted if the cache currently holds more statements than the new capacity.
+    /// A capacity of 0 disables caching. Defaults to 100.
+    pub fn set_statement_cache_capacity(&mut self, capacity: usize) {
+        self.0.set_statement_cache_capacity(capacity);
+    }
+
+    /// Removes all statements from the client's statement cache.
+    pub fn clear_statement_cache(&mut self) {
+        self.0.clear_statement_cache();
+    }
+
     /// Executes a statement, returning the number of rows modified.
     ///
     /// If the statement does not modify any rows (e.g. `SELECT`), 0 is returned.
@@ -163,7 +216,20 @@ impl Client {
     ///
     /// Panics if the number of parameters provided does not match the number expected.
     pub fn query(&mut self, statement: &Statement, params: &[&dyn ToSql]) -> Query {
-        Query(self.0.query(&statement.0, params))
+        Query(self.0.query_with_timeout(&statement.0, params))
+    }
+
+    /// Creates a pipeline which executes a sequence of statements in a single round trip.
+    ///
+    /// Statements added to the pipeline are sent together, followed by a single synchronization point. Outside of an
+    /// explicit transaction they run in one implicit transaction, so the pipeline is all-or-nothing: if a statement
+    /// fails, the remaining statements are skipped and the effects of the earlier ones are rolled back.
+    pub fn pipeline(&mut self) -> Pipeline {
+        Pipeline {
+            client: self.0.clone(),
+            messages: Ok(vec![]),
+            statements: vec![],
+        }
     }
 
     /// Binds a statement to a set of parameters, creating a `Portal` which can be incrementally queried.
@@ -226,8 +292,84 @@ impl Client {
         BatchExecute(self.0.batch_execute(query))
     }
 
+    /// Like `batch_execute`, but returns the rows produced by the statements.
+    ///
+    /// Values are returned in their text format. This is primarily useful for commands which cannot be prepared, like
+    /// those accepted by replication connections (e.g. `IDENTIFY_SYSTEM`). The warning on `batch_execute` applies here
+    /// as well.
+    pub fn simple_query(&mut self, query: &str) -> SimpleQuery {
+        SimpleQuery(self.0.batch_execute(query))
+    }
+
+    /// Executes a command which enters copy-both mode, such as `START_REPLICATION`.
+    ///
+    /// The command is sent with the simple query protocol. The returned object is a `Stream` of the data sent by the
+    /// server as well as a `Sink` for data sent to the server. Closing the sink ends the copy, after which the stream
+    /// completes once the server has finished the command.
+    pub fn copy_both(&mut self, query: &str) -> CopyBoth {
+        CopyBoth(self.0.copy_both(query))
+    }
+
+    /// Identifies the server, returning its system ID, current timeline, and current WAL flush location.
+    ///
+    /// The connection must have been opened in a replication mode.
+    pub fn identify_system(&mut self) -> IdentifySystem {
+        IdentifySystem::new(self.simple_query("IDENTIFY_SYSTEM"))
+    }
+
+    /// Retrieves the history file of a timeline.
+    ///
+    /// The connection must have been opened in a replication mode.
+    pub fn timeline_history(&mut self, timeline: u32) -> TimelineHistory {
+        TimelineHistory::new(self.simple_query(&format!("TIMELINE_HISTORY {}", timeline)))
+    }
+
+    /// Starts streaming WAL from the server.
+    ///
+    /// The connection must have been opened with the `Physical` replication mode. If a `slot_name` is provided, the
+    /// server retains WAL for that slot until the client confirms it has been flushed. Streaming starts at
+    /// `start_lsn` on the given timeline, or on the server's current timeline if `timeline` is `None`. The stream
+    /// completes if the server switches to a new timeline.
+    pub fn start_physical_replication(
+        &mut self,
+        slot_name: Option<&str>,
+        start_lsn: Lsn,
+        timeline: Option<u32>,
+    ) -> ReplicationStream {
+        let query = replication::physical_replication_query(slot_name, start_lsn, timeline);
+        ReplicationStream::new(self.copy_both(&query))
+    }
+
+    /// Starts streaming changes from a logical replication slot.
+    ///
+    /// The connection must have been opened with the `Logical` replication mode. Streaming starts at `start_lsn`, or
+    /// at the slot's confirmed position if that is later. The `options` are passed to the slot's output plugin - for
+    /// `pgoutput`, `proto_version` and `publication_names` are required.
+    pub fn start_logical_replication(
+        &mut self,
+        slot_name: &str,
+        start_lsn: Lsn,
+        options: &[(&str, &str)],
+    ) -> LogicalReplicationStream {
+        let query = replication::logical_replication_query(slot_name, start_lsn, options);
+        LogicalReplicationStream::new(self.copy_both(&query))
+    }
+
+    /// Streams a backup of the entire database cluster.
+    ///
+    /// The connection must have been opened with the `Physical` replication mode. The backup is sent as one tar
+    /// archive per tablespace.
+    pub fn base_backup(&mut self, options: &BaseBackupOptions) -> BaseBackup {
+        let legacy = match self.0.server_version() {
+            Some(version) => version < 15,
+            None => false,
+        };
+        let query = replication::base_backup_query(options, legacy);
+        BaseBackup::new(self.0.base_backup(&query, legacy))
+    }
+
     pub fn transaction(&mut self) -> TransactionBuilder {
-        TransactionBuilder(self.0.clone())
+        TransactionBuilder::new(self.0.clone())
     }
 
     /// Attempts to cancel an in-progress query.
@@ -244,6 +386,25 @@ impl Client {
         CancelQuery(self.0.cancel_query(make_tls_mode))
     }
 
+    /// Sets the TLS configuration used for the cancellation requests sent when queries time out.
+    ///
+    /// By default, like libpq, those cancellation requests are sent without TLS since they only carry the connection's
+    /// cancellation key. That exposes the key to the network, and fails with servers or proxies which only accept TLS
+    /// connections, in which case timed out queries keep running and the connection remains busy until they finish.
+    /// The cancellation requests use the provided TLS configuration instead, honoring the connection's `sslmode`.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn set_query_timeout_tls<T>(&mut self, make_tls_mode: T)
+    where
+        T: MakeTlsMode<Socket> + Clone + 'static + Send,
+        T::TlsMode: Send,
+        T::Stream: Send,
+        <T::TlsMode as TlsMode<Socket>>::Future: Send,
+    {
+        self.0.set_timeout_tls(Box::new(make_tls_mode));
+    }
+
     /// Like `cancel_query`, but uses a stream which is already connected to the server rather than opening a new
     /// connection itself.
     pub fn cancel_query_raw<S, T>(&mut self, stream: S, tls_mode: T) -> CancelQueryRaw<S, T>
@@ -298,6 +459,24 @@ where
     pub fn poll_message(&mut self) -> Poll<Option<AsyncMessage>, Error> {
         self.0.poll_message()
     }
+
+    /// Splits the connection into a future which drives it and a stream of the asynchronous messages it receives.
+    ///
+    /// Up to `capacity` messages are buffered until the stream consumes them, and `overflow` determines what happens
+    /// once the buffer is full. If the stream is dropped, further messages are discarded. The stream ends once the
+    /// driver completes or is dropped; any error is reported by the driver itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn split(
+        self,
+        capacity: usize,
+        overflow: Overflow,
+    ) -> (ConnectionDriver<S>, AsyncMessages) {
+        let (driver, messages) = proto::ConnectionDriver::new(self.0, capacity, overflow);
+        (ConnectionDriver(driver), AsyncMessages(messages))
+    }
 }
 
 impl<S> Future for Connection<S>
@@ -312,6 +491,65 @@ where
     }
 }
 
+/// The future half of a `Connection` which has been split with `Connection::split`.
+///
+/// Like `Connection`, it performs the actual IO with the server and should generally be spawned off onto an executor.
+#[must_use = "futures do nothing unless polled"]
+pub struct ConnectionDriver<S>(proto::ConnectionDriver<S>);
+
+impl<S> ConnectionDriver<S>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    /// Returns the value of a runtime parameter for this connection.
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.0.parameter(name)
+    }
+}
+
+impl<S> Future for ConnectionDriver<S>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    type Item = ();
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<(), Error> {
+        self.0.poll()
+    }
+}
+
+/// The stream half of a `Connection` which has been split with `Connection::split`.
+///
+/// The stream never fails; errors are returned by the `ConnectionDriver` instead.
+#[must_use = "streams do nothing unless polled"]
+pub struct AsyncMessages(proto::AsyncMessages);
+
+impl Stream for AsyncMessages {
+    type Item = AsyncMessage;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<AsyncMessage>, Error> {
+        self.0.poll()
+    }
+}
+
+/// The behavior of a split `Connection` once its buffer of asynchronous messages is full.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Overflow {
+    /// Stop reading from the server until the stream consumes a message.
+    ///
+    /// Responses to queries are delayed along with the messages, so the stream must be polled concurrently with the
+    /// client.
+    Wait,
+    /// Discard the oldest buffered message to make room for the new one.
+    DropOldest,
+    /// Discard the new message.
+    DropNewest,
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
 /// An asynchronous message from the server.
 #[allow(clippy::large_enum_variant)]
 pub enum AsyncMessage {
@@ -421,6 +659,20 @@ impl Future for Prepare {
     }
 }
 
+#[must_use = "futures do nothing unless polled"]
+pub struct PrepareCached(proto::PrepareCachedFuture);
+
+impl Future for PrepareCached {
+    type Item = Statement;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Statement, Error> {
+        let statement = try_ready!(self.0.poll());
+
+        Ok(Async::Ready(Statement(statement)))
+    }
+}
+
 /// A prepared statement.
 ///
 /// Prepared statements can only be used with the connection that created them.
@@ -440,7 +692,22 @@ impl Statement {
 }
 
 #[must_use = "futures do nothing unless polled"]
-pub struct Execute(proto::ExecuteFuture);
+pub struct Execute(proto::Timeout<proto::ExecuteFuture>);
+
+impl Execute {
+    /// Sets the time limit for the execution of the statement, overriding the connection's `query_timeout`.
+    ///
+    /// The limit starts once the server begins executing the statement, rather than while it waits for earlier
+    /// requests on the same connection. If it is exceeded, a cancellation request is sent to the server, and the future
+    /// resolves to an error for which `Error::is_timeout` returns `true` once the server has abandoned the statement.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn timeout(mut self, timeout: Duration) -> Execute {
+        self.0.set_timeout(timeout);
+        self
+    }
+}
 
 impl Future for Execute {
     type Item = u64;
@@ -452,7 +719,23 @@ impl Future for Execute {
 }
 
 #[must_use = "streams do nothing unless polled"]
-pub struct Query(proto::QueryStream<proto::Statement>);
+pub struct Query(proto::Timeout<proto::QueryStream<proto::Statement>>);
+
+impl Query {
+    /// Sets the time limit for the execution of the query, overriding the connection's `query_timeout`.
+    ///
+    /// The limit starts once the server begins executing the query, rather than while it waits for earlier requests on
+    /// the same connection, and covers the time spent streaming rows. If it is exceeded, a cancellation request is sent
+    /// to the server, and the stream ends with an error for which `Error::is_timeout` returns `true` once the server
+    /// has abandoned the query.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub fn timeout(mut self, timeout: Duration) -> Query {
+        self.0.set_timeout(timeout);
+        self
+    }
+}
 
 impl Stream for Query {
     type Item = Row;
@@ -532,17 +815,219 @@ impl Stream for CopyOut {
     }
 }
 
-pub struct TransactionBuilder(proto::Client);
+#[must_use = "streams do nothing unless polled"]
+pub struct CopyBoth(proto::CopyBothStream);
+
+impl Stream for CopyBoth {
+    type Item = Bytes;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Bytes>, Error> {
+        self.0.poll()
+    }
+}
+
+impl Sink for CopyBoth {
+    type SinkItem = Bytes;
+    type SinkError = Error;
+
+    fn start_send(&mut self, item: Bytes) -> StartSend<Bytes, Error> {
+        self.0.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Error> {
+        self.0.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Error> {
+        self.0.close()
+    }
+}
+
+/// A sequence of statements executed in a single round trip.
+pub struct Pipeline {
+    client: proto::Client,
+    messages: Result<Vec<u8>, Error>,
+    statements: Vec<proto::Statement>,
+}
+
+impl Pipeline {
+    /// Adds a statement to the pipeline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of parameters provided does not match the number expected.
+    pub fn add(&mut self, statement: &Statement, params: &[&dyn ToSql]) -> &mut Pipeline {
+        let message = self.client.bind_execute_message(&statement.0, params);
+        if let Ok(messages) = &mut self.messages {
+            match message {
+                Ok(message) => messages.extend_from_slice(&message),
+                Err(e) => self.messages = Err(e),
+            }
+        }
+        self.statements.push(statement.0.clone());
+        self
+    }
+
+    /// Returns the number of statements in the pipeline.
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// Determines if the pipeline contains no statements.
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// Sends the pipeline to the server, returning a stream of the results of each statement in order.
+    ///
+    /// If a statement fails, the stream returns its error after the results of the statements before it, and then
+    /// ends. Outside of an explicit transaction, the effects of those earlier statements have been rolled back. If
+    /// any parameters fail to serialize, nothing is sent to the server.
+    pub fn run(self) -> RunPipeline {
+        RunPipeline(self.client.pipeline(self.messages, self.statements))
+    }
+}
+
+#[must_use = "streams do nothing unless polled"]
+pub struct RunPipeline(proto::PipelineStream);
+
+impl Stream for RunPipeline {
+    type Item = PipelineResult;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<PipelineResult>, Error> {
+        self.0.poll()
+    }
+}
+
+/// The result of a statement in a pipeline.
+pub struct PipelineResult {
+    rows: Vec<Row>,
+    rows_affected: u64,
+}
+
+impl PipelineResult {
+    /// Returns the rows returned by the statement.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Consumes the result, returning the rows returned by the statement.
+    pub fn into_rows(self) -> Vec<Row> {
+        self.rows
+    }
+
+    /// Returns the number of rows reported in the statement's command tag.
+    ///
+    /// This is the number of rows modified by an `INSERT`, `UPDATE`, or `DELETE`, or returned by a `SELECT`. Statements
+    /// whose command tag has no row count, such as `CREATE TABLE`, report 0.
+    pub fn rows_affected(&self) -> u64 {
+        self.rows_affected
+    }
+}
+
+/// The isolation level of a database transaction.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IsolationLevel {
+    /// Statements see only data committed before they began.
+    ReadCommitted,
+    /// All statements in the transaction see the same snapshot of the database.
+    RepeatableRead,
+    /// The transaction behaves as though it were run serially with respect to all other serializable transactions.
+    Serializable,
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+pub struct TransactionBuilder {
+    client: proto::Client,
+    isolation_level: Option<IsolationLevel>,
+    read_only: Option<bool>,
+    deferrable: Option<bool>,
+}
 
 impl TransactionBuilder {
+    fn new(client: proto::Client) -> TransactionBuilder {
+        TransactionBuilder {
+            client,
+            isolation_level: None,
+            read_only: None,
+            deferrable: None,
+        }
+    }
+
+    /// Sets the isolation level of the transaction.
+    ///
+    /// Defaults to the server's `default_transaction_isolation`.
+    pub fn isolation_level(mut self, isolation_level: IsolationLevel) -> TransactionBuilder {
+        self.isolation_level = Some(isolation_level);
+        self
+    }
+
+    /// Sets whether the transaction is read-only.
+    ///
+    /// Defaults to the server's `default_transaction_read_only`.
+    pub fn read_only(mut self, read_only: bool) -> TransactionBuilder {
+        self.read_only = Some(read_only);
+        self
+    }
+
+    /// Sets whether the transaction is deferrable.
+    ///
+    /// A deferrable transaction only has an effect if it is also serializable and read-only, in which case it may
+    /// block when starting but then runs without the possibility of serialization failures. Defaults to the server's
+    /// `default_transaction_deferrable`.
+    pub fn deferrable(mut self, deferrable: bool) -> TransactionBuilder {
+        self.deferrable = Some(deferrable);
+        self
+    }
+
     pub fn build<T>(self, future: T) -> Transaction<T>
     where
         T: Future,
         // FIXME error type?
         T::Error: From<Error>,
     {
-        Transaction(proto::TransactionFuture::new(self.0, future))
+        let begin = self.begin_query();
+        Transaction(proto::TransactionFuture::new(self.client, begin, future))
+    }
+
+    fn begin_query(&self) -> String {
+        __begin_query(self.isolation_level, self.read_only, self.deferrable)
+    }
+}
+
+// WARNING: this function is not considered part of this crate's public API.
+// It is used by the `postgres` crate's transaction builder and is subject to change at any time.
+#[doc(hidden)]
+pub fn __begin_query(
+    isolation_level: Option<IsolationLevel>,
+    read_only: Option<bool>,
+    deferrable: Option<bool>,
+) -> String {
+    let mut query = "BEGIN".to_string();
+
+    match isolation_level {
+        Some(IsolationLevel::ReadCommitted) => query.push_str(" ISOLATION LEVEL READ COMMITTED"),
+        Some(IsolationLevel::RepeatableRead) => query.push_str(" ISOLATION LEVEL REPEATABLE READ"),
+        Some(IsolationLevel::Serializable) => query.push_str(" ISOLATION LEVEL SERIALIZABLE"),
+        Some(IsolationLevel::__NonExhaustive) | None => {}
     }
+
+    match read_only {
+        Some(true) => query.push_str(" READ ONLY"),
+        Some(false) => query.push_str(" READ WRITE"),
+        None => {}
+    }
+
+    match deferrable {
+        Some(true) => query.push_str(" DEFERRABLE"),
+        Some(false) => query.push_str(" NOT DEFERRABLE"),
+        None => {}
+    }
+
+    query
 }
 
 #[must_use = "futures do nothing unless polled"]
@@ -578,6 +1063,18 @@ impl Future for BatchExecute {
     }
 }
 
+#[must_use = "streams do nothing unless polled"]
+pub struct SimpleQuery(proto::SimpleQueryStream);
+
+impl Stream for SimpleQuery {
+    type Item = StringRow;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<StringRow>, Error> {
+        self.0.poll()
+    }
+}
+
 /// An asynchronous notification.
 #[derive(Clone, Debug)]
 pub struct Notification {
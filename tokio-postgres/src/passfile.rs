@@ -0,0 +1,137 @@
+use log::warn;
+use std::env;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::config::Host;
+use crate::Config;
+
+/// Looks up the password for a connection to the host at `idx` in the password file.
+///
+/// Like libpq, the file is silently skipped if it doesn't exist, and skipped with a warning if it isn't a regular
+/// file or is accessible by the group or world.
+pub(crate) fn find_password(config: &Config, idx: usize) -> Option<Vec<u8>> {
+    let path = path(config)?;
+
+    let metadata = fs::metadata(&path).ok()?;
+    if !metadata.is_file() {
+        warn!("password file {} is not a plain file", path.display());
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        if metadata.permissions().mode() & 0o077 != 0 {
+            warn!(
+                "password file {} has group or world access; permissions should be u=rw (0600) or less",
+                path.display()
+            );
+            return None;
+        }
+    }
+
+    let contents = fs::read_to_string(&path).ok()?;
+
+    let host = match &config.0.host[idx] {
+        Host::Tcp(host) => &**host,
+        #[cfg(unix)]
+        Host::Unix(_) => "localhost",
+    };
+    let port = config
+        .0
+        .port
+        .get(idx)
+        .or_else(|| config.0.port.get(0))
+        .unwrap_or(&5432)
+        .to_string();
+    let user = config.0.user.as_ref()?;
+    let dbname = config.0.dbname.as_ref().unwrap_or(user);
+
+    contents
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(split_line)
+        .find(|(fields, _)| {
+            field_matches(fields[0], host)
+                && field_matches(fields[1], &port)
+                && field_matches(fields[2], dbname)
+                && field_matches(fields[3], user)
+        })
+        .map(|(_, password)| unescape(password).into_bytes())
+}
+
+fn path(config: &Config) -> Option<PathBuf> {
+    if let Some(path) = &config.0.passfile {
+        return Some(path.clone());
+    }
+
+    if let Some(path) = env::var_os("PGPASSFILE") {
+        return Some(PathBuf::from(path));
+    }
+
+    default_path()
+}
+
+#[cfg(unix)]
+fn default_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| Path::new(&home).join(".pgpass"))
+}
+
+#[cfg(windows)]
+fn default_path() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(|appdata| Path::new(&appdata).join("postgresql").join("pgpass.conf"))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn default_path() -> Option<PathBuf> {
+    None
+}
+
+// a line consists of `hostname:port:database:username:password`, where `:` and `\` can be escaped with `\`.
+fn split_line(line: &str) -> Option<([&str; 4], &str)> {
+    let mut fields = [""; 4];
+    let mut rest = line;
+    for field in &mut fields {
+        let end = field_end(rest)?;
+        *field = &rest[..end];
+        rest = &rest[end + 1..];
+    }
+
+    Some((fields, rest))
+}
+
+fn field_end(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ':' {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
+fn field_matches(field: &str, value: &str) -> bool {
+    field == "*" || unescape(field) == value
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut it = s.chars();
+    while let Some(c) = it.next() {
+        if c == '\\' {
+            if let Some(c) = it.next() {
+                out.push(c);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
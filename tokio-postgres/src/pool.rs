@@ -0,0 +1,621 @@
+//! A pool of database connections.
+//!
+//! A [`Pool`] maintains a set of open connections to a database, handing them out on request and taking them back
+//! when they are no longer in use. Connections are checked for health before they are handed out, and connections
+//! which have been idle or open for too long are closed. If a connection breaks, it is discarded and a new one is
+//! opened in its place the next time one is needed.
+//!
+//! The pool spawns the `Connection` half of each connection it opens onto the default Tokio executor, so checkouts
+//! must be driven from within a Tokio runtime.
+//!
+//! Requires the `runtime` Cargo feature (enabled by default).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures::Future;
+//! use tokio_postgres::pool::Builder;
+//! use tokio_postgres::NoTls;
+//!
+//! let config = "host=localhost user=postgres".parse().unwrap();
+//! let pool = Builder::new().max_size(15).build(config, NoTls);
+//!
+//! let fut = pool
+//!     .get()
+//!     .and_then(|mut client| client.batch_execute("SELECT 1"))
+//!     .map_err(|e| eprintln!("error: {}", e));
+//!
+//! tokio::run(fut);
+//! ```
+//!
+//! [`Pool`]: struct.Pool.html
+
+use antidote::Mutex;
+use futures::sync::oneshot;
+use futures::{Async, Future, Poll, Stream};
+use log::{debug, error};
+use std::cmp;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio_executor::{DefaultExecutor, Executor};
+use tokio_timer::{Delay, Interval};
+
+use crate::{BatchExecute, Client, Config, Connect, Error, MakeTlsMode, Socket};
+
+const MAX_REAPER_RATE: Duration = Duration::from_secs(30);
+
+/// A builder for [`Pool`]s.
+///
+/// [`Pool`]: struct.Pool.html
+#[derive(Debug, Clone)]
+pub struct Builder {
+    max_size: u32,
+    connection_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    test_query: Option<String>,
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+impl Builder {
+    /// Creates a new builder with the default configuration.
+    pub fn new() -> Builder {
+        Builder {
+            max_size: 10,
+            connection_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+            test_query: None,
+        }
+    }
+
+    /// Sets the maximum number of connections managed by the pool.
+    ///
+    /// Defaults to 10.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0.
+    pub fn max_size(&mut self, max_size: u32) -> &mut Builder {
+        assert!(max_size > 0, "max_size must be positive");
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets the maximum amount of time a checkout will wait for a connection before failing.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn connection_timeout(&mut self, connection_timeout: Duration) -> &mut Builder {
+        self.connection_timeout = connection_timeout;
+        self
+    }
+
+    /// Sets the amount of time after which an idle connection is closed.
+    ///
+    /// If set to `None`, idle connections are never closed. Defaults to 10 minutes.
+    pub fn idle_timeout(&mut self, idle_timeout: Option<Duration>) -> &mut Builder {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Sets the maximum lifetime of a connection, after which it is closed once it is next returned to the pool.
+    ///
+    /// If set to `None`, connections are kept open indefinitely. Defaults to 30 minutes.
+    pub fn max_lifetime(&mut self, max_lifetime: Option<Duration>) -> &mut Builder {
+        self.max_lifetime = max_lifetime;
+        self
+    }
+
+    /// Sets a query which is run on idle connections before they are handed out to check that they are still
+    /// usable.
+    ///
+    /// Connections whose `Connection` future has terminated are always discarded, but a test query can additionally
+    /// catch connections which the server or network has silently dropped. Defaults to no test query.
+    pub fn test_query(&mut self, test_query: &str) -> &mut Builder {
+        self.test_query = Some(test_query.to_string());
+        self
+    }
+
+    /// Creates a pool which connects to the database described by `config`.
+    ///
+    /// No connections are opened until they are first requested.
+    pub fn build<T>(&self, config: Config, make_tls_mode: T) -> Pool<T>
+    where
+        T: MakeTlsMode<Socket>,
+    {
+        Pool(Arc::new(Shared {
+            config,
+            builder: self.clone(),
+            inner: Mutex::new(Inner {
+                make_tls_mode,
+                idle: VecDeque::new(),
+                connections: 0,
+                waiters: VecDeque::new(),
+                reaper_spawned: false,
+            }),
+        }))
+    }
+}
+
+/// A snapshot of the state of a [`Pool`].
+///
+/// [`Pool`]: struct.Pool.html
+#[derive(Debug, Copy, Clone)]
+pub struct State {
+    connections: u32,
+    idle_connections: u32,
+}
+
+impl State {
+    /// Returns the number of connections managed by the pool, including those currently being opened.
+    pub fn connections(&self) -> u32 {
+        self.connections
+    }
+
+    /// Returns the number of idle connections in the pool.
+    pub fn idle_connections(&self) -> u32 {
+        self.idle_connections
+    }
+}
+
+/// A pool of database connections.
+///
+/// Pools are cheap to clone, and clones share the same underlying set of connections.
+pub struct Pool<T>(Arc<Shared<T>>);
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Pool<T> {
+        Pool(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Pool")
+            .field("config", &self.0.config)
+            .field("builder", &self.0.builder)
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+impl<T> Pool<T>
+where
+    T: MakeTlsMode<Socket>,
+{
+    /// Creates a pool with the default configuration.
+    pub fn new(config: Config, make_tls_mode: T) -> Pool<T> {
+        Builder::new().build(config, make_tls_mode)
+    }
+
+    /// Retrieves a connection from the pool.
+    ///
+    /// An idle connection is reused if one is available, otherwise a new one is opened if the pool is not yet at
+    /// its maximum size. If neither is possible, the checkout waits for a connection to be returned to the pool,
+    /// failing if the configured connection timeout elapses first.
+    pub fn get(&self) -> Checkout<T> {
+        Checkout {
+            pool: self.clone(),
+            state: CheckoutState::Start,
+            timeout: Delay::new(Instant::now() + self.0.builder.connection_timeout),
+        }
+    }
+}
+
+impl<T> Pool<T> {
+    /// Returns information about the current state of the pool.
+    pub fn state(&self) -> State {
+        let inner = self.0.inner.lock();
+        State {
+            connections: inner.connections,
+            idle_connections: inner.idle.len() as u32,
+        }
+    }
+}
+
+struct Shared<T> {
+    config: Config,
+    builder: Builder,
+    inner: Mutex<Inner<T>>,
+}
+
+struct Inner<T> {
+    make_tls_mode: T,
+    idle: VecDeque<Idle>,
+    // open connections plus those currently being opened
+    connections: u32,
+    waiters: VecDeque<oneshot::Sender<Slot>>,
+    reaper_spawned: bool,
+}
+
+struct Conn {
+    client: Client,
+    birth: Instant,
+}
+
+struct Idle {
+    conn: Conn,
+    since: Instant,
+}
+
+// What a waiting checkout receives: either a connection returned to the pool, or permission to open a new one in
+// place of a connection which was discarded.
+enum Slot {
+    Conn(Conn),
+    Permit,
+}
+
+impl<T> Shared<T> {
+    fn expired(&self, conn: &Conn, now: Instant) -> bool {
+        match self.builder.max_lifetime {
+            Some(max_lifetime) => now - conn.birth >= max_lifetime,
+            None => false,
+        }
+    }
+
+    fn idle_expired(&self, idle: &Idle, now: Instant) -> bool {
+        let timed_out = match self.builder.idle_timeout {
+            Some(idle_timeout) => now - idle.since >= idle_timeout,
+            None => false,
+        };
+
+        timed_out || self.expired(&idle.conn, now) || idle.conn.client.is_closed()
+    }
+
+    // Hands a slot to the first waiter still interested in it, or returns it to the pool otherwise.
+    fn hand_off(&self, inner: &mut Inner<T>, mut slot: Slot) {
+        while let Some(waiter) = inner.waiters.pop_front() {
+            match waiter.send(slot) {
+                Ok(()) => return,
+                Err(s) => slot = s,
+            }
+        }
+
+        match slot {
+            Slot::Conn(conn) => inner.idle.push_back(Idle {
+                conn,
+                since: Instant::now(),
+            }),
+            Slot::Permit => inner.connections -= 1,
+        }
+    }
+
+    fn put_back(&self, conn: Conn) {
+        let slot = if conn.client.is_closed() || self.expired(&conn, Instant::now()) {
+            Slot::Permit
+        } else {
+            Slot::Conn(conn)
+        };
+
+        let mut inner = self.inner.lock();
+        self.hand_off(&mut inner, slot);
+    }
+
+    fn release(&self) {
+        let mut inner = self.inner.lock();
+        self.hand_off(&mut inner, Slot::Permit);
+    }
+
+    fn reap(&self) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock();
+
+        let mut i = 0;
+        while i < inner.idle.len() {
+            if self.idle_expired(&inner.idle[i], now) {
+                inner.idle.remove(i);
+                self.hand_off(&mut inner, Slot::Permit);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+impl<T> Shared<T>
+where
+    T: MakeTlsMode<Socket> + Clone,
+{
+    fn acquire(&self) -> Result<Slot, oneshot::Receiver<Slot>> {
+        let now = Instant::now();
+        let mut inner = self.inner.lock();
+
+        while let Some(idle) = inner.idle.pop_front() {
+            if self.idle_expired(&idle, now) {
+                debug!("discarding expired or broken pooled connection");
+                inner.connections -= 1;
+            } else {
+                return Ok(Slot::Conn(idle.conn));
+            }
+        }
+
+        if inner.connections < self.builder.max_size {
+            inner.connections += 1;
+            return Ok(Slot::Permit);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        inner.waiters.push_back(tx);
+        Err(rx)
+    }
+
+    fn connect(&self) -> Connect<T> {
+        let make_tls_mode = self.inner.lock().make_tls_mode.clone();
+        self.config.connect(make_tls_mode)
+    }
+}
+
+impl<T> Shared<T>
+where
+    T: Send + 'static,
+{
+    fn spawn_reaper(self: &Arc<Self>) {
+        let rate = match (self.builder.idle_timeout, self.builder.max_lifetime) {
+            (Some(a), Some(b)) => cmp::min(a, b),
+            (Some(a), None) | (None, Some(a)) => a,
+            (None, None) => return,
+        };
+        let rate = cmp::min(rate, MAX_REAPER_RATE);
+
+        {
+            let mut inner = self.inner.lock();
+            if inner.reaper_spawned {
+                return;
+            }
+            inner.reaper_spawned = true;
+        }
+
+        let shared = Arc::downgrade(self);
+        let reaper = Interval::new(Instant::now() + rate, rate)
+            .map_err(|e| error!("pool reaper timer error: {}", e))
+            .for_each(move |_| match Weak::upgrade(&shared) {
+                Some(shared) => {
+                    shared.reap();
+                    Ok(())
+                }
+                None => Err(()),
+            });
+
+        if let Err(e) = DefaultExecutor::current().spawn(Box::new(reaper)) {
+            error!("unable to spawn pool reaper: {}", e);
+            self.inner.lock().reaper_spawned = false;
+        }
+    }
+}
+
+enum CheckoutState<T>
+where
+    T: MakeTlsMode<Socket>,
+{
+    Start,
+    Waiting(oneshot::Receiver<Slot>),
+    Testing(Option<Conn>, BatchExecute),
+    Connecting(Connect<T>),
+    Done,
+}
+
+/// A future which resolves to a connection checked out of a [`Pool`].
+///
+/// [`Pool`]: struct.Pool.html
+#[must_use = "futures do nothing unless polled"]
+pub struct Checkout<T>
+where
+    T: MakeTlsMode<Socket>,
+{
+    pool: Pool<T>,
+    state: CheckoutState<T>,
+    timeout: Delay,
+}
+
+impl<T> Checkout<T>
+where
+    T: MakeTlsMode<Socket> + Clone,
+{
+    fn use_slot(&mut self, slot: Slot) -> Option<Conn> {
+        match slot {
+            Slot::Conn(mut conn) => match &self.pool.0.builder.test_query {
+                Some(test_query) => {
+                    let future = conn.client.batch_execute(test_query);
+                    self.state = CheckoutState::Testing(Some(conn), future);
+                    None
+                }
+                None => Some(conn),
+            },
+            Slot::Permit => {
+                self.state = CheckoutState::Connecting(self.pool.0.connect());
+                None
+            }
+        }
+    }
+
+    fn poll_timeout(&mut self) -> Poll<PooledClient<T>, Error> {
+        match self.timeout.poll() {
+            Ok(Async::Ready(())) => {
+                self.cancel();
+                Err(Error::timeout())
+            }
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(e) => {
+                self.cancel();
+                Err(Error::io(io::Error::new(io::ErrorKind::Other, e)))
+            }
+        }
+    }
+
+    fn ready(&self, conn: Conn) -> Poll<PooledClient<T>, Error> {
+        Ok(Async::Ready(PooledClient {
+            pool: self.pool.clone(),
+            client: Some(conn.client),
+            birth: conn.birth,
+        }))
+    }
+}
+
+impl<T> Checkout<T>
+where
+    T: MakeTlsMode<Socket>,
+{
+    // Gives back whatever slot the checkout is currently holding or waiting on.
+    fn cancel(&mut self) {
+        match mem::replace(&mut self.state, CheckoutState::Done) {
+            CheckoutState::Start | CheckoutState::Done => {}
+            CheckoutState::Waiting(mut rx) => {
+                rx.close();
+                if let Ok(Some(slot)) = rx.try_recv() {
+                    let mut inner = self.pool.0.inner.lock();
+                    self.pool.0.hand_off(&mut inner, slot);
+                }
+            }
+            CheckoutState::Testing(..) | CheckoutState::Connecting(_) => self.pool.0.release(),
+        }
+    }
+}
+
+impl<T> Drop for Checkout<T>
+where
+    T: MakeTlsMode<Socket>,
+{
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl<T> Future for Checkout<T>
+where
+    T: MakeTlsMode<Socket> + Clone + Send + 'static,
+    T::Stream: Send + 'static,
+{
+    type Item = PooledClient<T>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<PooledClient<T>, Error> {
+        loop {
+            match mem::replace(&mut self.state, CheckoutState::Done) {
+                CheckoutState::Start => {
+                    self.pool.0.spawn_reaper();
+                    match self.pool.0.acquire() {
+                        Ok(slot) => {
+                            if let Some(conn) = self.use_slot(slot) {
+                                return self.ready(conn);
+                            }
+                        }
+                        Err(rx) => self.state = CheckoutState::Waiting(rx),
+                    }
+                }
+                CheckoutState::Waiting(mut rx) => match rx.poll() {
+                    Ok(Async::Ready(slot)) => {
+                        if let Some(conn) = self.use_slot(slot) {
+                            return self.ready(conn);
+                        }
+                    }
+                    Ok(Async::NotReady) => {
+                        self.state = CheckoutState::Waiting(rx);
+                        return self.poll_timeout();
+                    }
+                    Err(_) => self.state = CheckoutState::Start,
+                },
+                CheckoutState::Testing(mut conn, mut future) => match future.poll() {
+                    Ok(Async::Ready(())) => return self.ready(conn.take().unwrap()),
+                    Ok(Async::NotReady) => {
+                        self.state = CheckoutState::Testing(conn, future);
+                        return self.poll_timeout();
+                    }
+                    Err(e) => {
+                        debug!("pooled connection failed test query: {}", e);
+                        self.state = CheckoutState::Connecting(self.pool.0.connect());
+                    }
+                },
+                CheckoutState::Connecting(mut future) => match future.poll() {
+                    Ok(Async::Ready((client, connection))) => {
+                        let connection = connection.then(|r| {
+                            if let Err(e) = r {
+                                error!("pooled connection error: {}", e);
+                            }
+                            Ok(())
+                        });
+                        if let Err(e) = DefaultExecutor::current().spawn(Box::new(connection)) {
+                            self.pool.0.release();
+                            return Err(Error::connect(io::Error::new(io::ErrorKind::Other, e)));
+                        }
+
+                        return self.ready(Conn {
+                            client,
+                            birth: Instant::now(),
+                        });
+                    }
+                    Ok(Async::NotReady) => {
+                        self.state = CheckoutState::Connecting(future);
+                        return self.poll_timeout();
+                    }
+                    Err(e) => {
+                        self.pool.0.release();
+                        return Err(e);
+                    }
+                },
+                CheckoutState::Done => panic!("Checkout polled after completion"),
+            }
+        }
+    }
+}
+
+/// A client checked out of a [`Pool`].
+///
+/// The client dereferences to a [`Client`], and is returned to the pool when dropped.
+///
+/// [`Pool`]: struct.Pool.html
+/// [`Client`]: ../struct.Client.html
+pub struct PooledClient<T> {
+    pool: Pool<T>,
+    client: Option<Client>,
+    birth: Instant,
+}
+
+impl<T> PooledClient<T> {
+    // WARNING: this method is not considered part of this crate's public API.
+    #[doc(hidden)]
+    pub fn __take(&mut self) -> Client {
+        self.client.take().unwrap()
+    }
+
+    // WARNING: this method is not considered part of this crate's public API.
+    #[doc(hidden)]
+    pub fn __restore(&mut self, client: Client) {
+        self.client = Some(client);
+    }
+}
+
+impl<T> Deref for PooledClient<T> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for PooledClient<T> {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for PooledClient<T> {
+    fn drop(&mut self) {
+        match self.client.take() {
+            Some(client) => self.pool.0.put_back(Conn {
+                client,
+                birth: self.birth,
+            }),
+            None => self.pool.0.release(),
+        }
+    }
+}
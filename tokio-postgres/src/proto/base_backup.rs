@@ -0,0 +1,171 @@
+use bytes::{BufMut, Bytes};
+use futures::sync::mpsc;
+use futures::{Async, Poll, Stream};
+use postgres_protocol::message::backend::{self, Message};
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::proto::client::{Client, PendingRequest};
+use crate::replication::{BackupPosition, BaseBackupMessage, Tablespace};
+use crate::{Error, StringRow};
+
+enum State {
+    Start {
+        client: Client,
+        request: PendingRequest,
+    },
+    ReadResponse {
+        receiver: mpsc::Receiver<Message>,
+    },
+    Done,
+}
+
+// the response to BASE_BACKUP is a result set with the start position, a result set listing the tablespaces, a single
+// copy containing the archives, and a result set with the end position. Before Postgres 15, each archive and the
+// manifest is instead sent as a separate copy of raw data, in the order the tablespaces were listed.
+#[derive(Copy, Clone, PartialEq)]
+enum Phase {
+    Initial,
+    Start,
+    Tablespaces,
+    Copy,
+    End,
+}
+
+pub struct BaseBackupStream {
+    state: State,
+    phase: Phase,
+    legacy: bool,
+    archives: VecDeque<Bytes>,
+}
+
+impl Stream for BaseBackupStream {
+    type Item = BaseBackupMessage;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<BaseBackupMessage>, Error> {
+        loop {
+            match mem::replace(&mut self.state, State::Done) {
+                State::Start { client, request } => {
+                    let receiver = client.send(request)?;
+                    self.state = State::ReadResponse { receiver };
+                }
+                State::ReadResponse { mut receiver } => {
+                    let message = match receiver.poll() {
+                        Ok(Async::Ready(message)) => message,
+                        Ok(Async::NotReady) => {
+                            self.state = State::ReadResponse { receiver };
+                            return Ok(Async::NotReady);
+                        }
+                        Err(()) => unreachable!("mpsc receiver can't panic"),
+                    };
+
+                    match message {
+                        Some(Message::RowDescription(_)) => {
+                            self.state = State::ReadResponse { receiver };
+                            self.phase = match self.phase {
+                                Phase::Initial => Phase::Start,
+                                Phase::Start => Phase::Tablespaces,
+                                _ => Phase::End,
+                            };
+                        }
+                        Some(Message::DataRow(body)) => {
+                            self.state = State::ReadResponse { receiver };
+                            let row = StringRow::new(body)?;
+                            let message = match self.phase {
+                                Phase::Start => {
+                                    BaseBackupMessage::Start(BackupPosition::from_row(&row)?)
+                                }
+                                Phase::Tablespaces => {
+                                    let tablespace = Tablespace::from_row(&row)?;
+                                    if self.legacy {
+                                        self.archives.push_back(new_archive(&tablespace));
+                                    }
+                                    BaseBackupMessage::Tablespace(tablespace)
+                                }
+                                Phase::End => {
+                                    BaseBackupMessage::End(BackupPosition::from_row(&row)?)
+                                }
+                                Phase::Initial | Phase::Copy => {
+                                    return Err(Error::unexpected_message())
+                                }
+                            };
+                            return Ok(Async::Ready(Some(message)));
+                        }
+                        Some(Message::CopyOutResponse(_)) => {
+                            self.state = State::ReadResponse { receiver };
+                            self.phase = Phase::Copy;
+                            if self.legacy {
+                                // the copies following those of the archives contain the manifest
+                                let message = match self.archives.pop_front() {
+                                    Some(archive) => backend::BaseBackupMessage::parse(&archive)
+                                        .map_err(Error::parse)?,
+                                    None => backend::BaseBackupMessage::Manifest,
+                                };
+                                return Ok(Async::Ready(Some(convert(message)?)));
+                            }
+                        }
+                        Some(Message::CopyData(body)) if self.legacy => {
+                            self.state = State::ReadResponse { receiver };
+                            return Ok(Async::Ready(Some(BaseBackupMessage::Data(
+                                body.into_bytes(),
+                            ))));
+                        }
+                        Some(Message::CopyData(body)) => {
+                            self.state = State::ReadResponse { receiver };
+                            let message = backend::BaseBackupMessage::parse(&body.into_bytes())
+                                .map_err(Error::parse)?;
+                            return Ok(Async::Ready(Some(convert(message)?)));
+                        }
+                        Some(Message::CopyDone) | Some(Message::CommandComplete(_)) => {
+                            self.state = State::ReadResponse { receiver };
+                        }
+                        Some(Message::ErrorResponse(body)) => return Err(Error::db(body)),
+                        Some(Message::ReadyForQuery(_)) => return Ok(Async::Ready(None)),
+                        Some(_) => return Err(Error::unexpected_message()),
+                        None => return Err(Error::closed()),
+                    }
+                }
+                State::Done => return Ok(Async::Ready(None)),
+            }
+        }
+    }
+}
+
+impl BaseBackupStream {
+    pub fn new(client: Client, request: PendingRequest, legacy: bool) -> BaseBackupStream {
+        BaseBackupStream {
+            state: State::Start { client, request },
+            phase: Phase::Initial,
+            legacy,
+            archives: VecDeque::new(),
+        }
+    }
+}
+
+fn convert(message: backend::BaseBackupMessage) -> Result<BaseBackupMessage, Error> {
+    match message {
+        backend::BaseBackupMessage::NewArchive(body) => Ok(BaseBackupMessage::NewArchive(body)),
+        backend::BaseBackupMessage::Manifest => Ok(BaseBackupMessage::Manifest),
+        backend::BaseBackupMessage::Data(data) => Ok(BaseBackupMessage::Data(data)),
+        backend::BaseBackupMessage::Progress(body) => Ok(BaseBackupMessage::Progress(body)),
+        _ => Err(Error::unexpected_message()),
+    }
+}
+
+// builds the new archive message newer servers send for a tablespace, named the way pg_basebackup names its archive
+fn new_archive(tablespace: &Tablespace) -> Bytes {
+    let name = match tablespace.oid() {
+        Some(oid) => format!("{}.tar", oid),
+        None => "base.tar".to_string(),
+    };
+    let location = tablespace.location().unwrap_or("");
+
+    let mut buf = Vec::with_capacity(name.len() + location.len() + 3);
+    buf.put_u8(b'n');
+    buf.put_slice(name.as_bytes());
+    buf.put_u8(0);
+    buf.put_slice(location.as_bytes());
+    buf.put_u8(0);
+    buf.into()
+}
@@ -55,6 +55,7 @@ impl PollBind for Bind {
                 state.name,
                 state.statement,
             ))),
+            Some(Message::ErrorResponse(body)) => Err(state.statement.db_error(body)),
             Some(_) => Err(Error::unexpected_message()),
             None => Err(Error::closed()),
         }
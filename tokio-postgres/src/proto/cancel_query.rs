@@ -1,6 +1,7 @@
 use futures::{try_ready, Future, Poll};
 use state_machine_future::{transition, RentToOwn, StateMachineFuture};
 use std::io;
+use tokio_timer::timer::Handle;
 
 use crate::proto::{CancelQueryRawFuture, ConnectSocketFuture};
 use crate::{Config, Error, Host, MakeTlsMode, Socket};
@@ -17,6 +18,7 @@ where
         config: Config,
         process_id: i32,
         secret_key: i32,
+        timer: Handle,
     },
     #[state_machine_future(transitions(Canceling))]
     ConnectingSocket {
@@ -54,11 +56,11 @@ where
         };
         let tls_mode = state
             .make_tls_mode
-            .make_tls_mode(hostname)
+            .make_tls_mode(hostname, &state.config)
             .map_err(|e| Error::tls(e.into()))?;
 
         transition!(ConnectingSocket {
-            future: ConnectSocketFuture::new(state.config, idx),
+            future: ConnectSocketFuture::new(state.config, idx, state.timer),
             tls_mode,
             process_id: state.process_id,
             secret_key: state.secret_key,
@@ -99,7 +101,8 @@ where
         config: Config,
         process_id: i32,
         secret_key: i32,
+        timer: Handle,
     ) -> CancelQueryFuture<T> {
-        CancelQuery::start(make_tls_mode, idx, config, process_id, secret_key)
+        CancelQuery::start(make_tls_mode, idx, config, process_id, secret_key, timer)
     }
 }
@@ -2,35 +2,68 @@ use antidote::Mutex;
 use bytes::IntoBuf;
 use futures::sync::mpsc;
 use futures::{AsyncSink, Poll, Sink, Stream};
+use linked_hash_map::LinkedHashMap;
 use postgres_protocol;
 use postgres_protocol::message::backend::Message;
 use postgres_protocol::message::frontend;
 use std::collections::HashMap;
 use std::error::Error as StdError;
-use std::sync::{Arc, Weak};
+use std::mem;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+#[cfg(feature = "runtime")]
+use std::time::{Duration, Instant};
 use tokio_io::{AsyncRead, AsyncWrite};
+#[cfg(feature = "runtime")]
+use tokio_timer::timer::Handle;
+#[cfg(feature = "runtime")]
+use tokio_timer::Delay;
 
+use crate::proto::base_backup::BaseBackupStream;
 use crate::proto::bind::BindFuture;
 use crate::proto::connection::{Request, RequestMessages};
+use crate::proto::copy_both::CopyBothStream;
 use crate::proto::copy_in::{CopyInFuture, CopyInReceiver, CopyMessage};
 use crate::proto::copy_out::CopyOutStream;
 use crate::proto::execute::ExecuteFuture;
 use crate::proto::idle::{IdleGuard, IdleState};
+use crate::proto::pipeline::PipelineStream;
 use crate::proto::portal::Portal;
 use crate::proto::prepare::PrepareFuture;
+use crate::proto::prepare_cached::PrepareCachedFuture;
+use crate::proto::progress::Progress;
 use crate::proto::query::QueryStream;
 use crate::proto::simple_query::SimpleQueryStream;
 use crate::proto::statement::Statement;
+use crate::proto::timeout::Timeout;
+#[cfg(feature = "runtime")]
+use crate::proto::timeout::{CancelFuture, MakeCancel};
 #[cfg(feature = "runtime")]
 use crate::proto::CancelQueryFuture;
 use crate::proto::CancelQueryRawFuture;
 use crate::types::{IsNull, Oid, ToSql, Type};
 use crate::{Config, Error, TlsMode};
 #[cfg(feature = "runtime")]
-use crate::{MakeTlsMode, Socket};
+use crate::{MakeTlsMode, NoTls, Socket, SslMode};
 
-pub struct PendingRequest(Result<(RequestMessages, IdleGuard), Error>);
+pub struct PendingRequest {
+    messages: Result<(RequestMessages, IdleGuard), Error>,
+    progress: Option<Progress>,
+}
+
+impl PendingRequest {
+    fn new(messages: Result<(RequestMessages, IdleGuard), Error>) -> PendingRequest {
+        PendingRequest {
+            messages,
+            progress: None,
+        }
+    }
+
+    fn track(mut self, progress: Progress) -> PendingRequest {
+        self.progress = Some(progress);
+        self
+    }
+}
 
 pub struct WeakClient(Weak<Inner>);
 
@@ -40,11 +73,15 @@ impl WeakClient {
     }
 }
 
+const DEFAULT_STATEMENT_CACHE_CAPACITY: usize = 100;
+
 struct State {
     types: HashMap<Oid, Type>,
     typeinfo_query: Option<Statement>,
     typeinfo_enum_query: Option<Statement>,
     typeinfo_composite_query: Option<Statement>,
+    statements: LinkedHashMap<(String, Vec<Oid>), Statement>,
+    statement_cache_capacity: usize,
 }
 
 struct Inner {
@@ -57,6 +94,12 @@ struct Inner {
     config: Config,
     #[cfg_attr(not(feature = "runtime"), allow(dead_code))]
     idx: Option<usize>,
+    server_version: Option<u32>,
+    // the client can be used outside of the context of a timer, so the one it was created in is used instead
+    #[cfg(feature = "runtime")]
+    timer: Handle,
+    #[cfg(feature = "runtime")]
+    timeout_tls: Mutex<Option<Box<dyn MakeCancel>>>,
     next_portal_id: AtomicUsize,
     next_statement_id: AtomicUsize,
 }
@@ -71,6 +114,7 @@ impl Client {
         secret_key: i32,
         config: Config,
         idx: Option<usize>,
+        server_version: Option<u32>,
     ) -> Client {
         Client(Arc::new(Inner {
             state: Mutex::new(State {
@@ -78,6 +122,8 @@ impl Client {
                 typeinfo_query: None,
                 typeinfo_enum_query: None,
                 typeinfo_composite_query: None,
+                statements: LinkedHashMap::new(),
+                statement_cache_capacity: DEFAULT_STATEMENT_CACHE_CAPACITY,
             }),
             idle: IdleState::new(),
             sender,
@@ -85,11 +131,20 @@ impl Client {
             secret_key,
             config,
             idx,
+            server_version,
+            #[cfg(feature = "runtime")]
+            timer: Handle::current(),
+            #[cfg(feature = "runtime")]
+            timeout_tls: Mutex::new(None),
             next_portal_id: AtomicUsize::new(0),
             next_statement_id: AtomicUsize::new(0),
         }))
     }
 
+    pub fn server_version(&self) -> Option<u32> {
+        self.0.server_version
+    }
+
     pub fn is_closed(&self) -> bool {
         self.0.sender.is_closed()
     }
@@ -134,8 +189,53 @@ impl Client {
         self.0.state.lock().typeinfo_composite_query = Some(statement.clone());
     }
 
+    pub fn cached_statement(&self, query: &str, param_types: &[Type]) -> Option<Statement> {
+        let key = statement_key(query, param_types);
+        self.0.state.lock().statements.get_refresh(&key).cloned()
+    }
+
+    pub fn cache_statement(&self, query: &str, param_types: &[Type], statement: &Statement) {
+        let key = statement_key(query, param_types);
+        let evicted = {
+            let mut state = self.0.state.lock();
+            if state.statement_cache_capacity == 0 {
+                return;
+            }
+            state.statements.insert(key, statement.clone());
+            evict_statements(&mut state)
+        };
+        drop(evicted);
+    }
+
+    pub fn uncache_statement(&self, statement: &Statement) {
+        let evicted = {
+            let mut state = self.0.state.lock();
+            let key = state
+                .statements
+                .iter()
+                .find(|(_, s)| s.name() == statement.name())
+                .map(|(key, _)| key.clone());
+            key.and_then(|key| state.statements.remove(&key))
+        };
+        drop(evicted);
+    }
+
+    pub fn set_statement_cache_capacity(&self, capacity: usize) {
+        let evicted = {
+            let mut state = self.0.state.lock();
+            state.statement_cache_capacity = capacity;
+            evict_statements(&mut state)
+        };
+        drop(evicted);
+    }
+
+    pub fn clear_statement_cache(&self) {
+        let evicted = mem::replace(&mut self.0.state.lock().statements, LinkedHashMap::new());
+        drop(evicted);
+    }
+
     pub fn send(&self, request: PendingRequest) -> Result<mpsc::Receiver<Message>, Error> {
-        let (messages, idle) = request.0?;
+        let (messages, idle) = request.messages?;
         let (sender, receiver) = mpsc::channel(1);
         self.0
             .sender
@@ -143,6 +243,7 @@ impl Client {
                 messages,
                 sender,
                 idle: Some(idle),
+                progress: request.progress,
             })
             .map(|_| receiver)
             .map_err(|_| Error::closed())
@@ -157,6 +258,15 @@ impl Client {
         SimpleQueryStream::new(self.clone(), pending)
     }
 
+    pub fn base_backup(&self, query: &str, legacy: bool) -> BaseBackupStream {
+        let pending = self.pending(|buf| {
+            frontend::query(query, buf).map_err(Error::parse)?;
+            Ok(())
+        });
+
+        BaseBackupStream::new(self.clone(), pending, legacy)
+    }
+
     pub fn prepare(&self, name: String, query: &str, param_types: &[Type]) -> PrepareFuture {
         let pending = self.pending(|buf| {
             frontend::parse(&name, query, param_types.iter().map(|t| t.oid()), buf)
@@ -169,29 +279,72 @@ impl Client {
         PrepareFuture::new(self.clone(), pending, name)
     }
 
-    pub fn execute(&self, statement: &Statement, params: &[&dyn ToSql]) -> ExecuteFuture {
-        let pending = PendingRequest(
+    pub fn prepare_cached(&self, query: &str, param_types: &[Type]) -> PrepareCachedFuture {
+        match self.cached_statement(query, param_types) {
+            Some(statement) => PrepareCachedFuture::cached(statement),
+            None => PrepareCachedFuture::new(
+                self.clone(),
+                self.prepare(self.next_statement(), query, param_types),
+                query,
+                param_types,
+            ),
+        }
+    }
+
+    pub fn execute(&self, statement: &Statement, params: &[&dyn ToSql]) -> Timeout<ExecuteFuture> {
+        let progress = Progress::new();
+        let pending = PendingRequest::new(
             self.execute_message(statement, params)
                 .map(|m| (RequestMessages::Single(m), self.0.idle.guard())),
-        );
-        ExecuteFuture::new(self.clone(), pending, statement.clone())
+        )
+        .track(progress.clone());
+        let future = ExecuteFuture::new(self.clone(), pending, statement.clone());
+        Timeout::new(future, self.clone(), progress)
     }
 
     pub fn query(&self, statement: &Statement, params: &[&dyn ToSql]) -> QueryStream<Statement> {
-        let pending = PendingRequest(
+        let pending = PendingRequest::new(
             self.execute_message(statement, params)
                 .map(|m| (RequestMessages::Single(m), self.0.idle.guard())),
         );
         QueryStream::new(self.clone(), pending, statement.clone())
     }
 
+    // like `query`, but subject to the query timeout
+    pub fn query_with_timeout(
+        &self,
+        statement: &Statement,
+        params: &[&dyn ToSql],
+    ) -> Timeout<QueryStream<Statement>> {
+        let progress = Progress::new();
+        let pending = PendingRequest::new(
+            self.execute_message(statement, params)
+                .map(|m| (RequestMessages::Single(m), self.0.idle.guard())),
+        )
+        .track(progress.clone());
+        let stream = QueryStream::new(self.clone(), pending, statement.clone());
+        Timeout::new(stream, self.clone(), progress)
+    }
+
+    pub fn pipeline(
+        &self,
+        messages: Result<Vec<u8>, Error>,
+        statements: Vec<Statement>,
+    ) -> PipelineStream {
+        let pending = PendingRequest::new(messages.map(|mut buf| {
+            frontend::sync(&mut buf);
+            (RequestMessages::Single(buf), self.0.idle.guard())
+        }));
+        PipelineStream::new(self.clone(), pending, statements)
+    }
+
     pub fn bind(&self, statement: &Statement, name: String, params: &[&dyn ToSql]) -> BindFuture {
         let mut buf = self.bind_message(statement, &name, params);
         if let Ok(ref mut buf) = buf {
             frontend::sync(buf);
         }
         let pending =
-            PendingRequest(buf.map(|m| (RequestMessages::Single(m), self.0.idle.guard())));
+            PendingRequest::new(buf.map(|m| (RequestMessages::Single(m), self.0.idle.guard())));
         BindFuture::new(self.clone(), pending, name, statement.clone())
     }
 
@@ -217,7 +370,7 @@ impl Client {
         S::Error: Into<Box<dyn StdError + Sync + Send>>,
     {
         let (mut sender, receiver) = mpsc::channel(1);
-        let pending = PendingRequest(self.execute_message(statement, params).map(|buf| {
+        let pending = PendingRequest::new(self.execute_message(statement, params).map(|buf| {
             match sender.start_send(CopyMessage::Data(buf)) {
                 Ok(AsyncSink::Ready) => {}
                 _ => unreachable!("channel should have capacity"),
@@ -234,13 +387,32 @@ impl Client {
     }
 
     pub fn copy_out(&self, statement: &Statement, params: &[&dyn ToSql]) -> CopyOutStream {
-        let pending = PendingRequest(
+        let pending = PendingRequest::new(
             self.execute_message(statement, params)
                 .map(|m| (RequestMessages::Single(m), self.0.idle.guard())),
         );
         CopyOutStream::new(self.clone(), pending, statement.clone())
     }
 
+    pub fn copy_both(&self, query: &str) -> CopyBothStream {
+        let (sender, receiver) = mpsc::channel(1);
+        let mut buf = vec![];
+        let pending = PendingRequest::new(
+            frontend::query(query, &mut buf)
+                .map_err(Error::parse)
+                .map(|()| {
+                    (
+                        RequestMessages::CopyIn {
+                            receiver: CopyInReceiver::new_copy_both(receiver),
+                            pending_message: Some(buf),
+                        },
+                        self.0.idle.guard(),
+                    )
+                }),
+        );
+        CopyBothStream::new(self.clone(), pending, sender)
+    }
+
     pub fn close_statement(&self, name: &str) {
         self.close(b'S', name)
     }
@@ -260,9 +432,44 @@ impl Client {
             self.0.config.clone(),
             self.0.process_id,
             self.0.secret_key,
+            self.0.timer.clone(),
         )
     }
 
+    #[cfg(feature = "runtime")]
+    pub fn set_timeout_tls(&self, make_tls_mode: Box<dyn MakeCancel>) {
+        *self.0.timeout_tls.lock() = Some(make_tls_mode);
+    }
+
+    #[cfg(feature = "runtime")]
+    pub fn cancel_timed_out_query(&self) -> CancelFuture {
+        if let Some(make_tls_mode) = &mut *self.0.timeout_tls.lock() {
+            return make_tls_mode.cancel_query(self);
+        }
+
+        // like libpq, the cancellation request is sent without TLS by default since it carries no user data
+        let mut config = self.0.config.clone();
+        config.ssl_mode(SslMode::Disable);
+        Box::new(CancelQueryFuture::new(
+            NoTls,
+            self.0.idx,
+            config,
+            self.0.process_id,
+            self.0.secret_key,
+            self.0.timer.clone(),
+        ))
+    }
+
+    #[cfg(feature = "runtime")]
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.0.config.0.query_timeout
+    }
+
+    #[cfg(feature = "runtime")]
+    pub fn delay(&self, timeout: Duration) -> Delay {
+        self.0.timer.delay(Instant::now() + timeout)
+    }
+
     pub fn cancel_query_raw<S, T>(&self, stream: S, tls_mode: T) -> CancelQueryRawFuture<S, T>
     where
         S: AsyncRead + AsyncWrite,
@@ -280,11 +487,15 @@ impl Client {
             messages: RequestMessages::Single(buf),
             sender,
             idle: None,
+            progress: None,
         });
     }
 
     pub fn next_statement(&self) -> String {
-        format!("s{}", self.0.next_statement_id.fetch_add(1, Ordering::Relaxed))
+        format!(
+            "s{}",
+            self.0.next_statement_id.fetch_add(1, Ordering::Relaxed)
+        )
     }
 
     pub fn next_portal(&self) -> String {
@@ -325,13 +536,22 @@ impl Client {
         }
     }
 
-    fn execute_message(
+    pub fn bind_execute_message(
         &self,
         statement: &Statement,
         params: &[&dyn ToSql],
     ) -> Result<Vec<u8>, Error> {
         let mut buf = self.bind_message(statement, "", params)?;
         frontend::execute("", 0, &mut buf).map_err(Error::parse)?;
+        Ok(buf)
+    }
+
+    fn execute_message(
+        &self,
+        statement: &Statement,
+        params: &[&dyn ToSql],
+    ) -> Result<Vec<u8>, Error> {
+        let mut buf = self.bind_execute_message(statement, params)?;
         frontend::sync(&mut buf);
         Ok(buf)
     }
@@ -341,8 +561,25 @@ impl Client {
         F: FnOnce(&mut Vec<u8>) -> Result<(), Error>,
     {
         let mut buf = vec![];
-        PendingRequest(
+        PendingRequest::new(
             messages(&mut buf).map(|()| (RequestMessages::Single(buf), self.0.idle.guard())),
         )
     }
 }
+
+fn statement_key(query: &str, param_types: &[Type]) -> (String, Vec<Oid>) {
+    let param_types = param_types.iter().map(Type::oid).collect();
+    (query.to_string(), param_types)
+}
+
+// Statements are closed when dropped, so evicted entries are returned to be dropped after the state lock is released.
+fn evict_statements(state: &mut State) -> Vec<Statement> {
+    let mut evicted = vec![];
+    while state.statements.len() > state.statement_cache_capacity {
+        match state.statements.pop_front() {
+            Some((_, statement)) => evicted.push(statement),
+            None => break,
+        }
+    }
+    evicted
+}
@@ -1,8 +1,11 @@
 use futures::{Async, Future, Poll};
+use rand::Rng;
 use state_machine_future::{transition, RentToOwn, StateMachineFuture};
 
 use crate::proto::{Client, ConnectOnceFuture, Connection};
-use crate::{Config, Error, Host, MakeTlsMode, Socket};
+use crate::{
+    Config, Error, Host, LoadBalanceHosts, MakeTlsMode, Socket, SslMode, TargetSessionAttrs,
+};
 
 #[derive(StateMachineFuture)]
 pub enum Connect<T>
@@ -17,7 +20,9 @@ where
     #[state_machine_future(transitions(Finished))]
     Connecting {
         future: ConnectOnceFuture<T::TlsMode>,
+        hosts: Vec<usize>,
         idx: usize,
+        tls_retried: bool,
         make_tls_mode: T,
         config: Config,
     },
@@ -34,30 +39,43 @@ where
     fn poll_start<'a>(state: &'a mut RentToOwn<'a, Start<T>>) -> Poll<AfterStart<T>, Error> {
         let mut state = state.take();
 
-        let config = state.config?;
+        let mut config = state.config?;
 
         if config.0.host.is_empty() {
-            return Err(Error::config("host missing".into()));
+            if config.0.hostaddr.is_empty() {
+                return Err(Error::config("host missing".into()));
+            }
+
+            // the addresses stand in for the missing hostnames
+            let hostaddrs = config.0.hostaddr.clone();
+            for hostaddr in hostaddrs {
+                config.host(&hostaddr.to_string());
+            }
+        }
+
+        if !config.0.hostaddr.is_empty() && config.0.hostaddr.len() != config.0.host.len() {
+            return Err(Error::config("invalid number of hostaddrs".into()));
         }
 
         if config.0.port.len() > 1 && config.0.port.len() != config.0.host.len() {
             return Err(Error::config("invalid number of ports".into()));
         }
 
-        let hostname = match &config.0.host[0] {
-            Host::Tcp(host) => &**host,
-            // postgres doesn't support TLS over unix sockets, so the choice here doesn't matter
-            #[cfg(unix)]
-            Host::Unix(_) => "",
-        };
+        let mut hosts = (0..config.0.host.len()).collect::<Vec<_>>();
+        if config.0.load_balance_hosts == Some(LoadBalanceHosts::Random) {
+            rand::thread_rng().shuffle(&mut hosts);
+        }
+
         let tls_mode = state
             .make_tls_mode
-            .make_tls_mode(hostname)
+            .make_tls_mode(hostname(&config.0.host[hosts[0]]), &config)
             .map_err(|e| Error::tls(e.into()))?;
 
         transition!(Connecting {
-            future: ConnectOnceFuture::new(0, tls_mode, config.clone()),
+            future: ConnectOnceFuture::new(hosts[0], tls_mode, config.clone()),
+            hosts,
             idx: 0,
+            tls_retried: false,
             make_tls_mode: state.make_tls_mode,
             config,
         })
@@ -72,31 +90,66 @@ where
                 Ok(Async::NotReady) => return Ok(Async::NotReady),
                 Err(e) => {
                     let state = &mut **state;
+
+                    // with sslmode=allow, a connection rejected by the server is retried with TLS
+                    if !state.tls_retried
+                        && state.config.0.ssl_mode == Some(SslMode::Allow)
+                        && e.code().is_some()
+                    {
+                        state.tls_retried = true;
+
+                        let mut config = state.config.clone();
+                        config.ssl_mode(SslMode::Require);
+                        let idx = state.hosts[state.idx];
+                        // a TLS mode which can't use TLS is no reason to hide why the server rejected us
+                        let tls_mode = match state
+                            .make_tls_mode
+                            .make_tls_mode(hostname(&config.0.host[idx]), &config)
+                        {
+                            Ok(tls_mode) => tls_mode,
+                            Err(_) => return Err(e),
+                        };
+
+                        state.future = ConnectOnceFuture::new(idx, tls_mode, config);
+                        continue;
+                    }
+
                     state.idx += 1;
+                    state.tls_retried = false;
 
-                    let host = match state.config.0.host.get(state.idx) {
-                        Some(host) => host,
-                        None => return Err(e),
-                    };
+                    if state.idx == state.config.0.host.len() {
+                        // with prefer-standby, any server is acceptable once none of them are standbys
+                        if state.config.0.target_session_attrs
+                            != Some(TargetSessionAttrs::PreferStandby)
+                        {
+                            return Err(e);
+                        }
+                        state.config.target_session_attrs(TargetSessionAttrs::Any);
+                        state.idx = 0;
+                    }
+                    let idx = state.hosts[state.idx];
 
-                    let hostname = match host {
-                        Host::Tcp(host) => &**host,
-                        #[cfg(unix)]
-                        Host::Unix(_) => "",
-                    };
                     let tls_mode = state
                         .make_tls_mode
-                        .make_tls_mode(hostname)
+                        .make_tls_mode(hostname(&state.config.0.host[idx]), &state.config)
                         .map_err(|e| Error::tls(e.into()))?;
 
-                    state.future =
-                        ConnectOnceFuture::new(state.idx, tls_mode, state.config.clone());
+                    state.future = ConnectOnceFuture::new(idx, tls_mode, state.config.clone());
                 }
             }
         }
     }
 }
 
+fn hostname(host: &Host) -> &str {
+    match host {
+        Host::Tcp(host) => host,
+        // postgres doesn't support TLS over unix sockets, so the choice here doesn't matter
+        #[cfg(unix)]
+        Host::Unix(_) => "",
+    }
+}
+
 impl<T> ConnectFuture<T>
 where
     T: MakeTlsMode<Socket>,
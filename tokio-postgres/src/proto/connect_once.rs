@@ -3,7 +3,9 @@
 use futures::{try_ready, Async, Future, Poll, Stream};
 use state_machine_future::{transition, RentToOwn, StateMachineFuture};
 use std::io;
+use tokio_timer::timer::Handle;
 
+use crate::passfile;
 use crate::proto::{Client, ConnectRawFuture, ConnectSocketFuture, Connection, SimpleQueryStream};
 use crate::{Config, Error, Socket, TargetSessionAttrs, TlsMode};
 
@@ -33,6 +35,7 @@ where
     #[state_machine_future(transitions(Finished))]
     CheckingSessionAttrs {
         stream: SimpleQueryStream,
+        target_session_attrs: TargetSessionAttrs,
         client: Client,
         connection: Connection<T::Stream>,
     },
@@ -49,11 +52,18 @@ where
     fn poll_start<'a>(state: &'a mut RentToOwn<'a, Start<T>>) -> Poll<AfterStart<T>, Error> {
         let state = state.take();
 
+        let mut config = state.config;
+        if config.0.password.is_none() {
+            if let Some(password) = passfile::find_password(&config, state.idx) {
+                config.password(password);
+            }
+        }
+
         transition!(ConnectingSocket {
-            future: ConnectSocketFuture::new(state.config.clone(), state.idx),
+            future: ConnectSocketFuture::new(config.clone(), state.idx, Handle::default()),
             idx: state.idx,
             tls_mode: state.tls_mode,
-            config: state.config,
+            config,
         })
     }
 
@@ -64,7 +74,11 @@ where
         let state = state.take();
 
         transition!(ConnectingRaw {
-            target_session_attrs: state.config.0.target_session_attrs,
+            target_session_attrs: state
+                .config
+                .0
+                .target_session_attrs
+                .unwrap_or(TargetSessionAttrs::Any),
             future: ConnectRawFuture::new(socket, state.tls_mode, state.config, Some(state.idx)),
         })
     }
@@ -73,16 +87,41 @@ where
         state: &'a mut RentToOwn<'a, ConnectingRaw<T>>,
     ) -> Poll<AfterConnectingRaw<T>, Error> {
         let (client, connection) = try_ready!(state.future.poll());
+        let target_session_attrs = state.target_session_attrs;
 
-        if let TargetSessionAttrs::ReadWrite = state.target_session_attrs {
-            transition!(CheckingSessionAttrs {
-                stream: client.batch_execute("SHOW transaction_read_only"),
-                client,
-                connection,
-            })
-        } else {
-            transition!(Finished((client, connection)))
-        }
+        let query = match target_session_attrs {
+            TargetSessionAttrs::ReadWrite | TargetSessionAttrs::ReadOnly => {
+                // servers since 14 report enough to skip the query
+                match (
+                    connection.parameter("default_transaction_read_only"),
+                    connection.parameter("in_hot_standby"),
+                ) {
+                    (Some(read_only), Some(hot_standby)) => {
+                        let read_only = read_only == "on" || hot_standby == "on";
+                        check_read_only(target_session_attrs, read_only)?;
+                        transition!(Finished((client, connection)))
+                    }
+                    _ => "SHOW transaction_read_only",
+                }
+            }
+            TargetSessionAttrs::Primary
+            | TargetSessionAttrs::Standby
+            | TargetSessionAttrs::PreferStandby => match connection.parameter("in_hot_standby") {
+                Some(hot_standby) => {
+                    check_hot_standby(target_session_attrs, hot_standby == "on")?;
+                    transition!(Finished((client, connection)))
+                }
+                None => "SELECT pg_catalog.pg_is_in_recovery()",
+            },
+            _ => transition!(Finished((client, connection))),
+        };
+
+        transition!(CheckingSessionAttrs {
+            stream: client.batch_execute(query),
+            target_session_attrs,
+            client,
+            connection,
+        })
     }
 
     fn poll_checking_session_attrs<'a>(
@@ -94,21 +133,52 @@ where
 
         match try_ready!(state.stream.poll()) {
             Some(row) => {
-                if row.get(0) == Some("on") {
-                    Err(Error::connect(io::Error::new(
-                        io::ErrorKind::PermissionDenied,
-                        "database does not allow writes",
-                    )))
-                } else {
-                    let state = state.take();
-                    transition!(Finished((state.client, state.connection)))
+                match state.target_session_attrs {
+                    TargetSessionAttrs::ReadWrite | TargetSessionAttrs::ReadOnly => {
+                        check_read_only(state.target_session_attrs, row.get(0) == Some("on"))?
+                    }
+                    _ => check_hot_standby(state.target_session_attrs, row.get(0) == Some("t"))?,
                 }
+
+                let state = state.take();
+                transition!(Finished((state.client, state.connection)))
             }
             None => Err(Error::closed()),
         }
     }
 }
 
+fn check_read_only(target_session_attrs: TargetSessionAttrs, read_only: bool) -> Result<(), Error> {
+    match (target_session_attrs, read_only) {
+        (TargetSessionAttrs::ReadWrite, true) => {
+            Err(session_attrs_error("database does not allow writes"))
+        }
+        (TargetSessionAttrs::ReadOnly, false) => {
+            Err(session_attrs_error("database is not read-only"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_hot_standby(
+    target_session_attrs: TargetSessionAttrs,
+    hot_standby: bool,
+) -> Result<(), Error> {
+    match (target_session_attrs, hot_standby) {
+        (TargetSessionAttrs::Primary, true) => {
+            Err(session_attrs_error("server is in hot standby mode"))
+        }
+        (TargetSessionAttrs::Standby, false) | (TargetSessionAttrs::PreferStandby, false) => {
+            Err(session_attrs_error("server is not in hot standby mode"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn session_attrs_error(message: &str) -> Error {
+    Error::connect(io::Error::new(io::ErrorKind::PermissionDenied, message))
+}
+
 impl<T> ConnectOnceFuture<T>
 where
     T: TlsMode<Socket>,
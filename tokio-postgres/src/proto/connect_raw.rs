@@ -11,8 +11,13 @@ use std::collections::HashMap;
 use tokio_codec::Framed;
 use tokio_io::{AsyncRead, AsyncWrite};
 
+use crate::gss::GssContext;
 use crate::proto::{Client, Connection, PostgresCodec, TlsFuture};
-use crate::{ChannelBinding, Config, Error, TlsMode};
+#[cfg(feature = "runtime")]
+use crate::Host;
+use crate::{
+    AuthMethod, ChannelBinding, ChannelBindingMode, Config, Error, ReplicationMode, TlsMode,
+};
 
 #[derive(StateMachineFuture)]
 pub enum ConnectRaw<S, T>
@@ -33,7 +38,7 @@ where
         idx: Option<usize>,
         channel_binding: ChannelBinding,
     },
-    #[state_machine_future(transitions(ReadingInfo, SendingPassword, SendingSasl))]
+    #[state_machine_future(transitions(ReadingInfo, SendingPassword, SendingSasl, SendingGss))]
     ReadingAuth {
         stream: Framed<T::Stream, PostgresCodec>,
         config: Config,
@@ -60,6 +65,20 @@ where
         config: Config,
         idx: Option<usize>,
     },
+    #[state_machine_future(transitions(ReadingGss, ReadingAuthCompletion))]
+    SendingGss {
+        future: sink::Send<Framed<T::Stream, PostgresCodec>>,
+        gss: GssContext,
+        config: Config,
+        idx: Option<usize>,
+    },
+    #[state_machine_future(transitions(SendingGss, ReadingAuthCompletion))]
+    ReadingGss {
+        stream: Framed<T::Stream, PostgresCodec>,
+        gss: GssContext,
+        config: Config,
+        idx: Option<usize>,
+    },
     #[state_machine_future(transitions(ReadingInfo))]
     ReadingAuthCompletion {
         stream: Framed<T::Stream, PostgresCodec>,
@@ -103,6 +122,11 @@ where
         if let Some(application_name) = &state.config.0.application_name {
             params.push(("application_name", &**application_name));
         }
+        match state.config.0.replication_mode {
+            Some(ReplicationMode::Physical) => params.push(("replication", "true")),
+            Some(ReplicationMode::Logical) => params.push(("replication", "database")),
+            Some(ReplicationMode::__NonExhaustive) | None => {}
+        }
 
         let mut buf = vec![];
         frontend::startup_message(params, &mut buf).map_err(Error::encode)?;
@@ -137,15 +161,19 @@ where
         let state = state.take();
 
         match message {
-            Some(Message::AuthenticationOk) => transition!(ReadingInfo {
-                stream: state.stream,
-                process_id: 0,
-                secret_key: 0,
-                parameters: HashMap::new(),
-                config: state.config,
-                idx: state.idx,
-            }),
+            Some(Message::AuthenticationOk) => {
+                check_auth_method(&state.config, AuthMethod::None, "no")?;
+                transition!(ReadingInfo {
+                    stream: state.stream,
+                    process_id: 0,
+                    secret_key: 0,
+                    parameters: HashMap::new(),
+                    config: state.config,
+                    idx: state.idx,
+                })
+            }
             Some(Message::AuthenticationCleartextPassword) => {
+                check_auth_method(&state.config, AuthMethod::Password, "cleartext password")?;
                 let pass = state
                     .config
                     .0
@@ -161,6 +189,7 @@ where
                 })
             }
             Some(Message::AuthenticationMd5Password(body)) => {
+                check_auth_method(&state.config, AuthMethod::Md5, "MD5 password")?;
                 let user = state
                     .config
                     .0
@@ -183,6 +212,7 @@ where
                 })
             }
             Some(Message::AuthenticationSasl(body)) => {
+                check_auth_method(&state.config, AuthMethod::ScramSha256, "SASL")?;
                 let pass = state
                     .config
                     .0
@@ -201,16 +231,25 @@ where
                     }
                 }
 
-                let channel_binding = if let Some(tls_server_end_point) =
-                    state.channel_binding.tls_server_end_point
-                {
-                    Some(sasl::ChannelBinding::tls_server_end_point(
-                        tls_server_end_point,
-                    ))
-                } else {
-                    None
+                let channel_binding = match (
+                    state.config.get_channel_binding(),
+                    state.channel_binding.tls_server_end_point,
+                ) {
+                    (ChannelBindingMode::Disable, _) | (_, None) => None,
+                    (_, Some(tls_server_end_point)) => Some(
+                        sasl::ChannelBinding::tls_server_end_point(tls_server_end_point),
+                    ),
                 };
 
+                if state.config.get_channel_binding() == ChannelBindingMode::Require
+                    && !(has_scram_plus && channel_binding.is_some())
+                {
+                    return Err(Error::authentication(
+                        "channel binding is required, but is not supported by the connection"
+                            .into(),
+                    ));
+                }
+
                 let (channel_binding, mechanism) = if has_scram_plus {
                     match channel_binding {
                         Some(channel_binding) => (channel_binding, sasl::SCRAM_SHA_256_PLUS),
@@ -240,10 +279,35 @@ where
                     idx: state.idx,
                 })
             }
+            Some(Message::AuthenticationGss) => {
+                check_auth_method(&state.config, AuthMethod::Gss, "GSSAPI")?;
+                let service = state
+                    .config
+                    .0
+                    .krb_srvname
+                    .as_ref()
+                    .map_or("postgres", |s| &**s);
+                let mut gss = GssContext::new(service, gss_host(&state.config, state.idx)?)?;
+                let token = gss.step(None)?;
+
+                let mut buf = vec![];
+                frontend::gss_response(&token, &mut buf).map_err(Error::encode)?;
+
+                transition!(SendingGss {
+                    future: state.stream.send(buf),
+                    gss,
+                    config: state.config,
+                    idx: state.idx,
+                })
+            }
+            Some(Message::AuthenticationSspi) => {
+                check_auth_method(&state.config, AuthMethod::Sspi, "SSPI")?;
+                Err(Error::authentication(
+                    "unsupported authentication method".into(),
+                ))
+            }
             Some(Message::AuthenticationKerberosV5)
-            | Some(Message::AuthenticationScmCredential)
-            | Some(Message::AuthenticationGss)
-            | Some(Message::AuthenticationSspi) => Err(Error::authentication(
+            | Some(Message::AuthenticationScmCredential) => Err(Error::authentication(
                 "unsupported authentication method".into(),
             )),
             Some(Message::ErrorResponse(body)) => Err(Error::db(body)),
@@ -315,6 +379,66 @@ where
         }
     }
 
+    fn poll_sending_gss<'a>(
+        state: &'a mut RentToOwn<'a, SendingGss<S, T>>,
+    ) -> Poll<AfterSendingGss<S, T>, Error> {
+        let stream = try_ready!(state.future.poll().map_err(Error::io));
+        let state = state.take();
+
+        if state.gss.is_complete() {
+            transition!(ReadingAuthCompletion {
+                stream,
+                config: state.config,
+                idx: state.idx,
+            })
+        } else {
+            transition!(ReadingGss {
+                stream,
+                gss: state.gss,
+                config: state.config,
+                idx: state.idx,
+            })
+        }
+    }
+
+    fn poll_reading_gss<'a>(
+        state: &'a mut RentToOwn<'a, ReadingGss<S, T>>,
+    ) -> Poll<AfterReadingGss<S, T>, Error> {
+        let message = try_ready!(state.stream.poll().map_err(Error::io));
+        let mut state = state.take();
+
+        match message {
+            Some(Message::AuthenticationGssContinue(body)) => {
+                let token = state.gss.step(Some(body.data()))?;
+
+                if token.is_empty() {
+                    if !state.gss.is_complete() {
+                        return Err(Error::authentication(
+                            "GSSAPI context not established".into(),
+                        ));
+                    }
+                    transition!(ReadingAuthCompletion {
+                        stream: state.stream,
+                        config: state.config,
+                        idx: state.idx,
+                    })
+                }
+
+                let mut buf = vec![];
+                frontend::gss_response(&token, &mut buf).map_err(Error::encode)?;
+                transition!(SendingGss {
+                    future: state.stream.send(buf),
+                    gss: state.gss,
+                    config: state.config,
+                    idx: state.idx,
+                })
+            }
+            Some(Message::ErrorResponse(body)) => Err(Error::db(body)),
+            Some(_) => Err(Error::unexpected_message()),
+            None => Err(Error::closed()),
+        }
+    }
+
     fn poll_reading_auth_completion<'a>(
         state: &'a mut RentToOwn<'a, ReadingAuthCompletion<S, T>>,
     ) -> Poll<AfterReadingAuthCompletion<S, T>, Error> {
@@ -361,6 +485,10 @@ where
                         state.secret_key,
                         state.config,
                         state.idx,
+                        state
+                            .parameters
+                            .get("server_version")
+                            .and_then(|v| major_version(v)),
                     );
                     let connection = Connection::new(state.stream, state.parameters, receiver);
                     transition!(Finished((client, connection)))
@@ -388,3 +516,58 @@ where
         ConnectRaw::start(TlsFuture::new(stream, tls_mode), config, idx)
     }
 }
+
+// guards against the server downgrading the authentication method
+fn check_auth_method(config: &Config, method: AuthMethod, description: &str) -> Result<(), Error> {
+    if let Some(methods) = &config.0.require_auth {
+        if !methods.contains(&method) {
+            return Err(Error::authentication(
+                format!(
+                    "server requested {} authentication, which is not allowed by require_auth",
+                    description
+                )
+                .into(),
+            ));
+        }
+    }
+
+    if method != AuthMethod::ScramSha256
+        && config.get_channel_binding() == ChannelBindingMode::Require
+    {
+        return Err(Error::authentication(
+            format!(
+                "channel binding is required, but server requested {} authentication",
+                description
+            )
+            .into(),
+        ));
+    }
+
+    Ok(())
+}
+
+// the target of GSSAPI authentication is the host being connected to
+#[cfg(feature = "runtime")]
+fn gss_host(config: &Config, idx: Option<usize>) -> Result<&str, Error> {
+    match idx.map(|idx| &config.0.host[idx]) {
+        Some(Host::Tcp(host)) => Ok(host),
+        _ => Err(Error::authentication(
+            "GSSAPI authentication requires a TCP host".into(),
+        )),
+    }
+}
+
+#[cfg(not(feature = "runtime"))]
+fn gss_host(_: &Config, _: Option<usize>) -> Result<&str, Error> {
+    Err(Error::authentication(
+        "GSSAPI authentication requires a TCP host".into(),
+    ))
+}
+
+// server versions look like `11.2 (Debian 11.2-1.pgdg90+1)`, `9.6.3`, or `15devel`
+fn major_version(version: &str) -> Option<u32> {
+    let end = version
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(version.len());
+    version[..end].parse().ok()
+}
@@ -1,17 +1,37 @@
 use futures::{try_ready, Async, Future, Poll};
 use futures_cpupool::{CpuFuture, CpuPool};
 use lazy_static::{lazy_static, __lazy_static_internal, __lazy_static_create};
+use rand::Rng;
 use state_machine_future::{transition, RentToOwn, StateMachineFuture};
 use std::io;
+use std::mem;
 use std::net::{SocketAddr, ToSocketAddrs};
-use std::time::Instant;
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
+))]
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
 use std::vec;
 use tokio_tcp::TcpStream;
+use tokio_timer::timer::Handle;
 use tokio_timer::Delay;
 #[cfg(unix)]
 use tokio_uds::UnixStream;
 
-use crate::{Config, Error, Host, Socket};
+use crate::{Config, Error, Host, LoadBalanceHosts, Socket};
+
+// how long to wait for a connection attempt before starting one to the next address, from RFC 8305
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+// the default `keepalives_idle`
+const KEEPALIVES_IDLE: Duration = Duration::from_secs(2 * 60 * 60);
 
 lazy_static! {
     static ref DNS_POOL: CpuPool = futures_cpupool::Builder::new()
@@ -23,9 +43,19 @@ lazy_static! {
 #[derive(StateMachineFuture)]
 pub enum ConnectSocket {
     #[state_machine_future(start)]
-    #[cfg_attr(unix, state_machine_future(transitions(ConnectingUnix, ResolvingDns)))]
-    #[cfg_attr(not(unix), state_machine_future(transitions(ResolvingDns)))]
-    Start { config: Config, idx: usize },
+    #[cfg_attr(
+        unix,
+        state_machine_future(transitions(ConnectingUnix, ResolvingDns, ConnectingTcp))
+    )]
+    #[cfg_attr(
+        not(unix),
+        state_machine_future(transitions(ResolvingDns, ConnectingTcp))
+    )]
+    Start {
+        config: Config,
+        idx: usize,
+        timer: Handle,
+    },
     #[cfg(unix)]
     #[state_machine_future(transitions(Finished))]
     ConnectingUnix {
@@ -36,13 +66,16 @@ pub enum ConnectSocket {
     ResolvingDns {
         future: CpuFuture<vec::IntoIter<SocketAddr>, io::Error>,
         config: Config,
+        timer: Handle,
     },
     #[state_machine_future(transitions(Finished))]
     ConnectingTcp {
-        future: tokio_tcp::ConnectFuture,
-        timeout: Option<Delay>,
+        attempts: Vec<ConnectAttempt>,
+        next_attempt: Delay,
         addrs: vec::IntoIter<SocketAddr>,
+        error: io::Error,
         config: Config,
+        timer: Handle,
     },
     #[state_machine_future(ready)]
     Finished(Socket),
@@ -63,12 +96,17 @@ impl PollConnectSocket for ConnectSocket {
             .unwrap_or(&5432);
 
         match &state.config.0.host[state.idx] {
+            Host::Tcp(_) if state.config.0.hostaddr.len() > state.idx => {
+                let addr = SocketAddr::new(state.config.0.hostaddr[state.idx], port);
+                transition!(connecting_tcp(vec![addr], state.config, state.timer))
+            }
             Host::Tcp(host) => transition!(ResolvingDns {
                 future: DNS_POOL.spawn_fn({
                     let host = host.clone();
                     move || (&*host, port).to_socket_addrs()
                 }),
                 config: state.config,
+                timer: state.timer,
             }),
             #[cfg(unix)]
             Host::Unix(host) => {
@@ -77,7 +115,7 @@ impl PollConnectSocket for ConnectSocket {
                     .config
                     .0
                     .connect_timeout
-                    .map(|d| Delay::new(Instant::now() + d));
+                    .map(|d| state.timer.delay(Instant::now() + d));
                 transition!(ConnectingUnix {
                     future: UnixStream::connect(path),
                     timeout,
@@ -107,73 +145,240 @@ impl PollConnectSocket for ConnectSocket {
     fn poll_resolving_dns<'a>(
         state: &'a mut RentToOwn<'a, ResolvingDns>,
     ) -> Poll<AfterResolvingDns, Error> {
-        let mut addrs = try_ready!(state.future.poll().map_err(Error::connect));
+        let addrs = try_ready!(state.future.poll().map_err(Error::connect));
         let state = state.take();
 
-        let addr = match addrs.next() {
-            Some(addr) => addr,
-            None => {
-                return Err(Error::connect(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "resolved 0 addresses",
-                )));
-            }
-        };
-
-        let timeout = state
-            .config
-            .0
-            .connect_timeout
-            .map(|d| Delay::new(Instant::now() + d));
-
-        transition!(ConnectingTcp {
-            future: TcpStream::connect(&addr),
-            addrs,
-            timeout: timeout,
-            config: state.config,
-        })
+        transition!(connecting_tcp(addrs.collect(), state.config, state.timer))
     }
 
     fn poll_connecting_tcp<'a>(
         state: &'a mut RentToOwn<'a, ConnectingTcp>,
     ) -> Poll<AfterConnectingTcp, Error> {
-        let stream = loop {
-            let error = match state.future.poll() {
-                Ok(Async::Ready(stream)) => break stream,
-                Ok(Async::NotReady) => match &mut state.timeout {
-                    Some(timeout) => {
-                        try_ready!(timeout
-                            .poll()
-                            .map_err(|e| Error::connect(io::Error::new(io::ErrorKind::Other, e))));
-                        io::Error::from(io::ErrorKind::TimedOut)
+        loop {
+            let mut failed = false;
+            let mut i = 0;
+            while i < state.attempts.len() {
+                match state.attempts[i].poll() {
+                    Ok(Async::Ready(stream)) => {
+                        stream.set_nodelay(true).map_err(Error::connect)?;
+                        if state.config.0.keepalives.unwrap_or(true) {
+                            let idle = state.config.0.keepalives_idle.unwrap_or(KEEPALIVES_IDLE);
+                            stream.set_keepalive(Some(idle)).map_err(Error::connect)?;
+                        }
+                        set_tcp_options(&stream, &state.config).map_err(Error::connect)?;
+
+                        transition!(Finished(Socket::new_tcp(stream)));
+                    }
+                    Ok(Async::NotReady) => i += 1,
+                    Err(e) => {
+                        state.attempts.swap_remove(i);
+                        state.error = e;
+                        failed = true;
                     }
-                    None => return Ok(Async::NotReady),
-                },
-                Err(e) => e,
-            };
-
-            let addr = state.addrs.next().ok_or_else(|| Error::connect(error))?;
-            state.future = TcpStream::connect(&addr);
-            state.timeout = state
-                .config
+                }
+            }
+
+            // the next address is tried once an attempt fails or the previous ones are taking too long
+            if !failed && !state.attempts.is_empty() {
+                try_ready!(state
+                    .next_attempt
+                    .poll()
+                    .map_err(|e| Error::connect(io::Error::new(io::ErrorKind::Other, e))));
+            }
+
+            match state.addrs.next() {
+                Some(addr) => {
+                    let attempt = ConnectAttempt::new(&addr, &state.config, &state.timer);
+                    state.attempts.push(attempt);
+                    state.next_attempt =
+                        state.timer.delay(Instant::now() + CONNECTION_ATTEMPT_DELAY);
+                }
+                None if state.attempts.is_empty() => {
+                    let error =
+                        mem::replace(&mut state.error, io::Error::from(io::ErrorKind::Other));
+                    return Err(Error::connect(error));
+                }
+                None => return Ok(Async::NotReady),
+            }
+        }
+    }
+}
+
+// options not exposed by the standard library are set directly on the socket, where the platform supports them.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
+))]
+fn set_tcp_options(stream: &TcpStream, config: &Config) -> io::Result<()> {
+    let fd = stream.as_raw_fd();
+
+    if config.0.keepalives.unwrap_or(true) {
+        if let Some(interval) = config.0.keepalives_interval {
+            setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, secs(interval))?;
+        }
+        if let Some(retries) = config.0.keepalives_retries {
+            let retries = retries.min(libc::c_int::MAX as u32) as libc::c_int;
+            setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPCNT, retries)?;
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        if let Some(timeout) = config.0.tcp_user_timeout {
+            let millis = timeout.as_secs() * 1000 + u64::from(timeout.subsec_millis());
+            let millis = millis.min(libc::c_int::MAX as u64) as libc::c_int;
+            setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT, millis)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
+)))]
+fn set_tcp_options(_: &TcpStream, _: &Config) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
+))]
+fn secs(duration: Duration) -> libc::c_int {
+    duration.as_secs().min(libc::c_int::MAX as u64) as libc::c_int
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
+))]
+fn setsockopt(
+    fd: RawFd,
+    level: libc::c_int,
+    name: libc::c_int,
+    value: libc::c_int,
+) -> io::Result<()> {
+    let ret = unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            name,
+            &value as *const libc::c_int as *const libc::c_void,
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+fn connecting_tcp(mut addrs: Vec<SocketAddr>, config: Config, timer: Handle) -> ConnectingTcp {
+    if config.0.load_balance_hosts == Some(LoadBalanceHosts::Random) {
+        rand::thread_rng().shuffle(&mut addrs);
+    }
+
+    ConnectingTcp {
+        attempts: vec![],
+        next_attempt: timer.delay(Instant::now()),
+        addrs: interleave_families(addrs).into_iter(),
+        error: io::Error::new(io::ErrorKind::InvalidData, "resolved 0 addresses"),
+        config,
+        timer,
+    }
+}
+
+// alternates between IPv6 and IPv4 addresses, starting with the family of the first address, so a broken network
+// path for one family doesn't hold up the other.
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_ipv6 = !addrs.is_empty() && addrs[0].is_ipv6();
+    let (first, second): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_ipv6);
+
+    let mut addrs = Vec::with_capacity(first.len() + second.len());
+    let mut first = first.into_iter();
+    let mut second = second.into_iter();
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => break,
+            (a, b) => {
+                addrs.extend(a);
+                addrs.extend(b);
+            }
+        }
+    }
+    addrs
+}
+
+pub struct ConnectAttempt {
+    future: tokio_tcp::ConnectFuture,
+    timeout: Option<Delay>,
+}
+
+impl ConnectAttempt {
+    fn new(addr: &SocketAddr, config: &Config, timer: &Handle) -> ConnectAttempt {
+        ConnectAttempt {
+            future: TcpStream::connect(addr),
+            timeout: config
                 .0
                 .connect_timeout
-                .map(|d| Delay::new(Instant::now() + d));
-        };
-
-        stream.set_nodelay(true).map_err(Error::connect)?;
-        if state.config.0.keepalives {
-            stream
-                .set_keepalive(Some(state.config.0.keepalives_idle))
-                .map_err(Error::connect)?;
+                .map(|d| timer.delay(Instant::now() + d)),
+        }
+    }
+}
+
+impl Future for ConnectAttempt {
+    type Item = TcpStream;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<TcpStream, io::Error> {
+        if let Async::Ready(stream) = self.future.poll()? {
+            return Ok(Async::Ready(stream));
+        }
+
+        if let Some(timeout) = &mut self.timeout {
+            if let Async::Ready(()) = timeout
+                .poll()
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+            {
+                return Err(io::Error::from(io::ErrorKind::TimedOut));
+            }
         }
 
-        transition!(Finished(Socket::new_tcp(stream)));
+        Ok(Async::NotReady)
     }
 }
 
 impl ConnectSocketFuture {
-    pub fn new(config: Config, idx: usize) -> ConnectSocketFuture {
-        ConnectSocket::start(config, idx)
+    pub fn new(config: Config, idx: usize, timer: Handle) -> ConnectSocketFuture {
+        ConnectSocket::start(config, idx, timer)
     }
 }
@@ -11,6 +11,7 @@ use tokio_io::{AsyncRead, AsyncWrite};
 use crate::proto::codec::PostgresCodec;
 use crate::proto::copy_in::CopyInReceiver;
 use crate::proto::idle::IdleGuard;
+use crate::proto::progress::Progress;
 use crate::{AsyncMessage, Notification};
 use crate::{DbError, Error};
 
@@ -26,11 +27,21 @@ pub struct Request {
     pub messages: RequestMessages,
     pub sender: mpsc::Sender<Message>,
     pub idle: Option<IdleGuard>,
+    pub progress: Option<Progress>,
 }
 
 struct Response {
     sender: mpsc::Sender<Message>,
     _idle: Option<IdleGuard>,
+    progress: Option<Progress>,
+}
+
+impl Response {
+    fn start(&self) {
+        if let Some(progress) = &self.progress {
+            progress.start();
+        }
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -137,6 +148,16 @@ where
                 _ => false,
             };
 
+            // the server processes requests in order, so it moves on to the next one once this one is complete
+            if request_complete {
+                if let Some(progress) = &response.progress {
+                    progress.finish();
+                }
+                if let Some(next) = self.responses.front() {
+                    next.start();
+                }
+            }
+
             match response.sender.start_send(message) {
                 // if the receiver's hung up we still need to page through the rest of the messages
                 // designated to it
@@ -164,10 +185,15 @@ where
         match try_ready_receive!(self.receiver.poll()) {
             Some(request) => {
                 trace!("polled new request");
-                self.responses.push_back(Response {
+                let response = Response {
                     sender: request.sender,
                     _idle: request.idle,
-                });
+                    progress: request.progress,
+                };
+                if self.responses.is_empty() {
+                    response.start();
+                }
+                self.responses.push_back(response);
                 Ok(Async::Ready(Some(request.messages)))
             }
             None => Ok(Async::Ready(None)),
@@ -0,0 +1,157 @@
+use bytes::Bytes;
+use futures::sync::mpsc;
+use futures::{Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::mem;
+
+use crate::proto::client::{Client, PendingRequest};
+use crate::proto::copy_in::CopyMessage;
+use crate::Error;
+
+enum State {
+    Start {
+        client: Client,
+        request: PendingRequest,
+    },
+    ReadingCopyBothResponse {
+        receiver: mpsc::Receiver<Message>,
+    },
+    ReadingCopyData {
+        receiver: mpsc::Receiver<Message>,
+    },
+    Done,
+}
+
+pub struct CopyBothStream {
+    state: State,
+    sender: mpsc::Sender<CopyMessage>,
+    done_sent: bool,
+}
+
+impl CopyBothStream {
+    pub fn new(
+        client: Client,
+        request: PendingRequest,
+        sender: mpsc::Sender<CopyMessage>,
+    ) -> CopyBothStream {
+        CopyBothStream {
+            state: State::Start { client, request },
+            sender,
+            done_sent: false,
+        }
+    }
+
+    fn start(&mut self) -> Result<(), Error> {
+        if let State::Start { .. } = self.state {
+            match mem::replace(&mut self.state, State::Done) {
+                State::Start { client, request } => {
+                    let receiver = client.send(request)?;
+                    self.state = State::ReadingCopyBothResponse { receiver };
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Stream for CopyBothStream {
+    type Item = Bytes;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Bytes>, Error> {
+        self.start()?;
+
+        loop {
+            match mem::replace(&mut self.state, State::Done) {
+                State::Start { .. } => unreachable!(),
+                State::ReadingCopyBothResponse { mut receiver } => {
+                    let message = match receiver.poll() {
+                        Ok(Async::Ready(message)) => message,
+                        Ok(Async::NotReady) => {
+                            self.state = State::ReadingCopyBothResponse { receiver };
+                            break Ok(Async::NotReady);
+                        }
+                        Err(()) => unreachable!("mpsc::Receiver doesn't return errors"),
+                    };
+
+                    match message {
+                        Some(Message::CopyBothResponse(_)) => {
+                            self.state = State::ReadingCopyData { receiver };
+                        }
+                        Some(Message::ErrorResponse(body)) => break Err(Error::db(body)),
+                        Some(_) => break Err(Error::unexpected_message()),
+                        None => break Err(Error::closed()),
+                    }
+                }
+                State::ReadingCopyData { mut receiver } => {
+                    let message = match receiver.poll() {
+                        Ok(Async::Ready(message)) => message,
+                        Ok(Async::NotReady) => {
+                            self.state = State::ReadingCopyData { receiver };
+                            break Ok(Async::NotReady);
+                        }
+                        Err(()) => unreachable!("mpsc::Receiver doesn't return errors"),
+                    };
+
+                    match message {
+                        Some(Message::CopyData(body)) => {
+                            self.state = State::ReadingCopyData { receiver };
+                            break Ok(Async::Ready(Some(body.into_bytes())));
+                        }
+                        // after the copy completes the server may send a result set (e.g. the next timeline in
+                        // physical replication) before the command completes.
+                        Some(Message::CopyDone)
+                        | Some(Message::RowDescription(_))
+                        | Some(Message::DataRow(_))
+                        | Some(Message::CommandComplete(_)) => {
+                            self.state = State::ReadingCopyData { receiver };
+                        }
+                        Some(Message::ReadyForQuery(_)) => break Ok(Async::Ready(None)),
+                        Some(Message::ErrorResponse(body)) => break Err(Error::db(body)),
+                        Some(_) => break Err(Error::unexpected_message()),
+                        None => break Err(Error::closed()),
+                    }
+                }
+                State::Done => break Ok(Async::Ready(None)),
+            }
+        }
+    }
+}
+
+impl Sink for CopyBothStream {
+    type SinkItem = Bytes;
+    type SinkError = Error;
+
+    fn start_send(&mut self, item: Bytes) -> StartSend<Bytes, Error> {
+        self.start()?;
+
+        let mut buf = vec![];
+        frontend::copy_data(&item, &mut buf).map_err(Error::encode)?;
+        match self.sender.start_send(CopyMessage::Data(buf)) {
+            Ok(AsyncSink::Ready) => Ok(AsyncSink::Ready),
+            Ok(AsyncSink::NotReady(_)) => Ok(AsyncSink::NotReady(item)),
+            Err(_) => Err(Error::closed()),
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Error> {
+        self.sender.poll_complete().map_err(|_| Error::closed())
+    }
+
+    fn close(&mut self) -> Poll<(), Error> {
+        self.start()?;
+
+        if !self.done_sent {
+            match self.sender.start_send(CopyMessage::Done) {
+                Ok(AsyncSink::Ready) => self.done_sent = true,
+                Ok(AsyncSink::NotReady(_)) => return Ok(Async::NotReady),
+                Err(_) => return Err(Error::closed()),
+            }
+        }
+
+        self.sender.close().map_err(|_| Error::closed())
+    }
+}
@@ -19,6 +19,8 @@ pub enum CopyMessage {
 pub struct CopyInReceiver {
     receiver: mpsc::Receiver<CopyMessage>,
     done: bool,
+    // copy_both requests are made with a simple query, so they must not be followed by a Sync
+    sync: bool,
 }
 
 impl CopyInReceiver {
@@ -26,6 +28,15 @@ impl CopyInReceiver {
         CopyInReceiver {
             receiver,
             done: false,
+            sync: true,
+        }
+    }
+
+    pub fn new_copy_both(receiver: mpsc::Receiver<CopyMessage>) -> CopyInReceiver {
+        CopyInReceiver {
+            receiver,
+            done: false,
+            sync: false,
         }
     }
 }
@@ -45,14 +56,20 @@ impl Stream for CopyInReceiver {
                 self.done = true;
                 let mut buf = vec![];
                 frontend::copy_done(&mut buf);
-                frontend::sync(&mut buf);
+                if self.sync {
+                    frontend::sync(&mut buf);
+                }
                 Ok(Async::Ready(Some(buf)))
             }
             Async::Ready(None) => {
                 self.done = true;
                 let mut buf = vec![];
-                frontend::copy_fail("", &mut buf).unwrap();
-                frontend::sync(&mut buf);
+                if self.sync {
+                    frontend::copy_fail("", &mut buf).unwrap();
+                    frontend::sync(&mut buf);
+                } else {
+                    frontend::copy_done(&mut buf);
+                }
                 Ok(Async::Ready(Some(buf)))
             }
             Async::NotReady => Ok(Async::NotReady),
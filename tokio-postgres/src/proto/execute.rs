@@ -16,7 +16,10 @@ pub enum Execute {
         statement: Statement,
     },
     #[state_machine_future(transitions(Finished))]
-    ReadResponse { receiver: mpsc::Receiver<Message> },
+    ReadResponse {
+        receiver: mpsc::Receiver<Message>,
+        statement: Statement,
+    },
     #[state_machine_future(ready)]
     Finished(u64),
     #[state_machine_future(error)]
@@ -28,8 +31,10 @@ impl PollExecute for Execute {
         let state = state.take();
         let receiver = state.client.send(state.request)?;
 
-        // the statement can drop after this point, since its close will queue up after the execution
-        transition!(ReadResponse { receiver })
+        transition!(ReadResponse {
+            receiver,
+            statement: state.statement,
+        })
     }
 
     fn poll_read_response<'a>(
@@ -41,7 +46,7 @@ impl PollExecute for Execute {
             match message {
                 Some(Message::BindComplete) => {}
                 Some(Message::DataRow(_)) => {}
-                Some(Message::ErrorResponse(body)) => return Err(Error::db(body)),
+                Some(Message::ErrorResponse(body)) => return Err(state.statement.db_error(body)),
                 Some(Message::CommandComplete(body)) => {
                     let rows = body
                         .tag()
@@ -18,6 +18,7 @@ macro_rules! try_ready_closed {
     };
 }
 
+mod base_backup;
 mod bind;
 #[cfg(feature = "runtime")]
 mod cancel_query;
@@ -32,21 +33,28 @@ mod connect_raw;
 #[cfg(feature = "runtime")]
 mod connect_socket;
 mod connection;
+mod copy_both;
 mod copy_in;
 mod copy_out;
 mod execute;
 mod idle;
+mod pipeline;
 mod portal;
 mod prepare;
+mod prepare_cached;
+mod progress;
 mod query;
 mod simple_query;
+mod split;
 mod statement;
+mod timeout;
 mod tls;
 mod transaction;
 mod typeinfo;
 mod typeinfo_composite;
 mod typeinfo_enum;
 
+pub use crate::proto::base_backup::BaseBackupStream;
 pub use crate::proto::bind::BindFuture;
 #[cfg(feature = "runtime")]
 pub use crate::proto::cancel_query::CancelQueryFuture;
@@ -61,13 +69,18 @@ pub use crate::proto::connect_raw::ConnectRawFuture;
 #[cfg(feature = "runtime")]
 pub use crate::proto::connect_socket::ConnectSocketFuture;
 pub use crate::proto::connection::Connection;
+pub use crate::proto::copy_both::CopyBothStream;
 pub use crate::proto::copy_in::CopyInFuture;
 pub use crate::proto::copy_out::CopyOutStream;
 pub use crate::proto::execute::ExecuteFuture;
+pub use crate::proto::pipeline::PipelineStream;
 pub use crate::proto::portal::Portal;
 pub use crate::proto::prepare::PrepareFuture;
+pub use crate::proto::prepare_cached::PrepareCachedFuture;
 pub use crate::proto::query::QueryStream;
 pub use crate::proto::simple_query::SimpleQueryStream;
+pub use crate::proto::split::{AsyncMessages, ConnectionDriver};
 pub use crate::proto::statement::Statement;
+pub use crate::proto::timeout::Timeout;
 pub use crate::proto::tls::TlsFuture;
 pub use crate::proto::transaction::TransactionFuture;
@@ -0,0 +1,144 @@
+use futures::sync::mpsc;
+use futures::{Async, Poll, Stream};
+use postgres_protocol::message::backend::Message;
+use std::mem;
+use std::vec;
+
+use crate::proto::client::{Client, PendingRequest};
+use crate::proto::statement::Statement;
+use crate::{Error, PipelineResult, Row};
+
+enum State {
+    Start {
+        client: Client,
+        request: PendingRequest,
+        statements: Vec<Statement>,
+    },
+    ReadingResponse {
+        receiver: mpsc::Receiver<Message>,
+        statements: vec::IntoIter<Statement>,
+        current: Option<Statement>,
+        rows: Vec<Row>,
+    },
+    Done,
+}
+
+pub struct PipelineStream(State);
+
+impl Stream for PipelineStream {
+    type Item = PipelineResult;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<PipelineResult>, Error> {
+        loop {
+            match mem::replace(&mut self.0, State::Done) {
+                State::Start {
+                    client,
+                    request,
+                    statements,
+                } => {
+                    let receiver = client.send(request)?;
+                    self.0 = State::ReadingResponse {
+                        receiver,
+                        statements: statements.into_iter(),
+                        current: None,
+                        rows: vec![],
+                    };
+                }
+                State::ReadingResponse {
+                    mut receiver,
+                    mut statements,
+                    mut current,
+                    mut rows,
+                } => {
+                    let message = match receiver.poll() {
+                        Ok(Async::Ready(message)) => message,
+                        Ok(Async::NotReady) => {
+                            self.0 = State::ReadingResponse {
+                                receiver,
+                                statements,
+                                current,
+                                rows,
+                            };
+                            break Ok(Async::NotReady);
+                        }
+                        Err(()) => unreachable!("mpsc::Receiver doesn't return errors"),
+                    };
+
+                    let rows_affected = match message {
+                        Some(Message::BindComplete) => {
+                            current = statements.next();
+                            self.0 = State::ReadingResponse {
+                                receiver,
+                                statements,
+                                current,
+                                rows,
+                            };
+                            continue;
+                        }
+                        Some(Message::DataRow(body)) => {
+                            let statement = current
+                                .as_ref()
+                                .ok_or_else(Error::unexpected_message)?
+                                .clone();
+                            rows.push(Row::new(statement, body)?);
+                            self.0 = State::ReadingResponse {
+                                receiver,
+                                statements,
+                                current,
+                                rows,
+                            };
+                            continue;
+                        }
+                        Some(Message::CommandComplete(body)) => body
+                            .tag()
+                            .map_err(Error::parse)?
+                            .rsplit(' ')
+                            .next()
+                            .unwrap()
+                            .parse()
+                            .unwrap_or(0),
+                        Some(Message::EmptyQueryResponse) => 0,
+                        // the server skips the rest of the pipeline, so nothing follows the error
+                        Some(Message::ErrorResponse(body)) => {
+                            // an error in Bind arrives before its BindComplete would have
+                            break match current.or_else(|| statements.next()) {
+                                Some(statement) => Err(statement.db_error(body)),
+                                None => Err(Error::db(body)),
+                            };
+                        }
+                        Some(Message::ReadyForQuery(_)) => break Ok(Async::Ready(None)),
+                        Some(_) => break Err(Error::unexpected_message()),
+                        None => break Err(Error::closed()),
+                    };
+
+                    self.0 = State::ReadingResponse {
+                        receiver,
+                        statements,
+                        current: None,
+                        rows: vec![],
+                    };
+                    break Ok(Async::Ready(Some(PipelineResult {
+                        rows,
+                        rows_affected,
+                    })));
+                }
+                State::Done => break Ok(Async::Ready(None)),
+            }
+        }
+    }
+}
+
+impl PipelineStream {
+    pub fn new(
+        client: Client,
+        request: PendingRequest,
+        statements: Vec<Statement>,
+    ) -> PipelineStream {
+        PipelineStream(State::Start {
+            client,
+            request,
+            statements,
+        })
+    }
+}
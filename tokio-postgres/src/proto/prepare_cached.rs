@@ -0,0 +1,67 @@
+#![allow(clippy::large_enum_variant)]
+
+use futures::{try_ready, Async, Future, Poll};
+
+use crate::proto::client::Client;
+use crate::proto::prepare::PrepareFuture;
+use crate::proto::statement::Statement;
+use crate::types::Type;
+use crate::Error;
+
+enum State {
+    Cached(Statement),
+    Preparing {
+        client: Client,
+        future: PrepareFuture,
+        query: String,
+        param_types: Vec<Type>,
+    },
+    Done,
+}
+
+pub struct PrepareCachedFuture(State);
+
+impl Future for PrepareCachedFuture {
+    type Item = Statement;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Statement, Error> {
+        let statement = match &mut self.0 {
+            State::Cached(statement) => statement.clone(),
+            State::Preparing {
+                client,
+                future,
+                query,
+                param_types,
+            } => {
+                let statement = try_ready!(future.poll());
+                client.cache_statement(query, param_types, &statement);
+                statement
+            }
+            State::Done => panic!("PrepareCachedFuture polled after completion"),
+        };
+        self.0 = State::Done;
+
+        Ok(Async::Ready(statement))
+    }
+}
+
+impl PrepareCachedFuture {
+    pub fn new(
+        client: Client,
+        future: PrepareFuture,
+        query: &str,
+        param_types: &[Type],
+    ) -> PrepareCachedFuture {
+        PrepareCachedFuture(State::Preparing {
+            client,
+            future,
+            query: query.to_string(),
+            param_types: param_types.to_vec(),
+        })
+    }
+
+    pub fn cached(statement: Statement) -> PrepareCachedFuture {
+        PrepareCachedFuture(State::Cached(statement))
+    }
+}
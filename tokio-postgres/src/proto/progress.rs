@@ -0,0 +1,58 @@
+use futures::task::AtomicTask;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const QUEUED: usize = 0;
+const RUNNING: usize = 1;
+const FINISHED: usize = 2;
+
+struct Inner {
+    state: AtomicUsize,
+    task: AtomicTask,
+}
+
+// Requests are sent to the server as soon as they're made, but it processes them one at a time, so a request may wait
+// behind the others made before it. The connection reports when the server starts and finishes processing one.
+#[derive(Clone)]
+pub struct Progress(Arc<Inner>);
+
+impl Progress {
+    pub fn new() -> Progress {
+        Progress(Arc::new(Inner {
+            state: AtomicUsize::new(QUEUED),
+            task: AtomicTask::new(),
+        }))
+    }
+
+    // like `is_started`, but notifies the current task once the request starts
+    #[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+    pub fn poll_started(&self) -> bool {
+        self.0.task.register();
+        self.is_started()
+    }
+
+    #[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+    pub fn is_started(&self) -> bool {
+        self.0.state.load(Ordering::SeqCst) != QUEUED
+    }
+
+    #[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+    pub fn is_finished(&self) -> bool {
+        self.0.state.load(Ordering::SeqCst) == FINISHED
+    }
+
+    pub fn start(&self) {
+        if self
+            .0
+            .state
+            .compare_exchange(QUEUED, RUNNING, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            self.0.task.notify();
+        }
+    }
+
+    pub fn finish(&self) {
+        self.0.state.store(FINISHED, Ordering::SeqCst);
+    }
+}
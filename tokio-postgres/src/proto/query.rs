@@ -83,7 +83,9 @@ where
                                 statement,
                             };
                         }
-                        Some(Message::ErrorResponse(body)) => break Err(Error::db(body)),
+                        Some(Message::ErrorResponse(body)) => {
+                            break Err(statement.statement().db_error(body));
+                        }
                         Some(Message::DataRow(body)) => {
                             let row = Row::new(statement.statement().clone(), body)?;
                             self.0 = State::ReadingResponse {
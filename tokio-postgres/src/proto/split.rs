@@ -0,0 +1,177 @@
+use antidote::Mutex;
+use futures::task::{self, Task};
+use futures::{Async, Future, Poll, Stream};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio_io::{AsyncRead, AsyncWrite};
+
+use crate::proto::connection::Connection;
+use crate::{AsyncMessage, Error, Overflow};
+
+struct Shared {
+    messages: VecDeque<AsyncMessage>,
+    driver_task: Option<Task>,
+    stream_task: Option<Task>,
+    driver_closed: bool,
+    stream_closed: bool,
+}
+
+pub struct ConnectionDriver<S> {
+    connection: Connection<S>,
+    shared: Arc<Mutex<Shared>>,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+impl<S> ConnectionDriver<S> {
+    pub fn new(
+        connection: Connection<S>,
+        capacity: usize,
+        overflow: Overflow,
+    ) -> (ConnectionDriver<S>, AsyncMessages) {
+        assert!(capacity > 0, "message buffer capacity must be nonzero");
+
+        let shared = Arc::new(Mutex::new(Shared {
+            messages: VecDeque::new(),
+            driver_task: None,
+            stream_task: None,
+            driver_closed: false,
+            stream_closed: false,
+        }));
+
+        let driver = ConnectionDriver {
+            connection,
+            shared: shared.clone(),
+            capacity,
+            overflow,
+        };
+        (driver, AsyncMessages { shared })
+    }
+
+    // returns false if the driver has to wait for the stream to make room first
+    fn poll_capacity(&self) -> bool {
+        let mut shared = self.shared.lock();
+        match self.overflow {
+            Overflow::Wait if !shared.stream_closed && shared.messages.len() >= self.capacity => {
+                shared.driver_task = Some(task::current());
+                false
+            }
+            _ => true,
+        }
+    }
+
+    fn push(&self, message: AsyncMessage) {
+        let mut shared = self.shared.lock();
+        if shared.stream_closed {
+            return;
+        }
+
+        if shared.messages.len() >= self.capacity {
+            match self.overflow {
+                Overflow::DropOldest => {
+                    shared.messages.pop_front();
+                }
+                Overflow::DropNewest => return,
+                Overflow::Wait | Overflow::__NonExhaustive => {}
+            }
+        }
+
+        shared.messages.push_back(message);
+        if let Some(task) = shared.stream_task.take() {
+            task.notify();
+        }
+    }
+
+    fn close(&self) {
+        let mut shared = self.shared.lock();
+        shared.driver_closed = true;
+        if let Some(task) = shared.stream_task.take() {
+            task.notify();
+        }
+    }
+}
+
+impl<S> ConnectionDriver<S>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.connection.parameter(name)
+    }
+}
+
+impl<S> Future for ConnectionDriver<S>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    type Item = ();
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<(), Error> {
+        loop {
+            if !self.poll_capacity() {
+                return Ok(Async::NotReady);
+            }
+
+            let message = match self.connection.poll_message() {
+                Ok(Async::Ready(message)) => message,
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.close();
+                    return Err(e);
+                }
+            };
+
+            match message {
+                Some(message) => self.push(message),
+                None => {
+                    self.close();
+                    return Ok(Async::Ready(()));
+                }
+            }
+        }
+    }
+}
+
+impl<S> Drop for ConnectionDriver<S> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+pub struct AsyncMessages {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Stream for AsyncMessages {
+    type Item = AsyncMessage;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<AsyncMessage>, Error> {
+        let mut shared = self.shared.lock();
+        match shared.messages.pop_front() {
+            Some(message) => {
+                if let Some(task) = shared.driver_task.take() {
+                    task.notify();
+                }
+                Ok(Async::Ready(Some(message)))
+            }
+            None if shared.driver_closed => Ok(Async::Ready(None)),
+            None => {
+                shared.stream_task = Some(task::current());
+                Ok(Async::NotReady)
+            }
+        }
+    }
+}
+
+impl Drop for AsyncMessages {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock();
+        shared.stream_closed = true;
+        shared.messages.clear();
+        if let Some(task) = shared.driver_task.take() {
+            task.notify();
+        }
+    }
+}
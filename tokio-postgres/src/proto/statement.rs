@@ -1,8 +1,10 @@
+use postgres_protocol::message::backend::ErrorResponseBody;
 use std::sync::Arc;
 
+use crate::error::SqlState;
 use crate::proto::client::WeakClient;
 use crate::types::Type;
-use crate::Column;
+use crate::{Column, Error};
 
 pub struct StatementInner {
     client: WeakClient,
@@ -48,4 +50,17 @@ impl Statement {
     pub fn columns(&self) -> &[Column] {
         &self.0.columns
     }
+
+    // Converts an error response to a request using this statement. If the server reports that the statement's plan
+    // is no longer valid (e.g. "cached plan must not change result type"), the statement is also evicted from the
+    // client's statement cache so it will be prepared again next time.
+    pub fn db_error(&self, body: ErrorResponseBody) -> Error {
+        let error = Error::db(body);
+        if error.code() == Some(&SqlState::FEATURE_NOT_SUPPORTED) {
+            if let Some(client) = self.0.client.upgrade() {
+                client.uncache_statement(self);
+            }
+        }
+        error
+    }
 }
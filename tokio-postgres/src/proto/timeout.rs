@@ -0,0 +1,192 @@
+use futures::{Async, Future, Poll, Stream};
+#[cfg(feature = "runtime")]
+use std::io;
+#[cfg(feature = "runtime")]
+use std::time::Duration;
+#[cfg(feature = "runtime")]
+use tokio_timer::Delay;
+
+use crate::proto::client::Client;
+use crate::proto::progress::Progress;
+use crate::Error;
+#[cfg(feature = "runtime")]
+use crate::{error::SqlState, MakeTlsMode, Socket, TlsMode};
+
+#[cfg(feature = "runtime")]
+pub type CancelFuture = Box<dyn Future<Item = (), Error = Error> + Send>;
+
+// the `MakeTlsMode` used for the cancellation requests of timed out queries, with its type erased so it can be stored
+// in the client
+#[cfg(feature = "runtime")]
+pub trait MakeCancel: Send {
+    fn cancel_query(&mut self, client: &Client) -> CancelFuture;
+}
+
+#[cfg(feature = "runtime")]
+impl<T> MakeCancel for T
+where
+    T: MakeTlsMode<Socket> + Clone + 'static + Send,
+    T::TlsMode: Send,
+    T::Stream: Send,
+    <T::TlsMode as TlsMode<Socket>>::Future: Send,
+{
+    fn cancel_query(&mut self, client: &Client) -> CancelFuture {
+        Box::new(client.cancel_query(self.clone()))
+    }
+}
+
+enum State {
+    Running,
+    // the request may wait behind others made on the same connection, so the deadline starts once the server begins
+    // processing it rather than when it's made
+    #[cfg(feature = "runtime")]
+    Queued(Duration),
+    #[cfg(feature = "runtime")]
+    Waiting(Delay),
+    // the server only reports the cancellation once it has abandoned the query, so the request keeps being polled
+    // until then, leaving the connection ready for the next one
+    #[cfg(feature = "runtime")]
+    Canceling(CancelFuture),
+    #[cfg(feature = "runtime")]
+    Canceled,
+    #[cfg(feature = "runtime")]
+    Done,
+}
+
+pub struct Timeout<T> {
+    inner: T,
+    #[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+    client: Client,
+    #[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+    progress: Progress,
+    #[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+    state: State,
+}
+
+impl<T> Timeout<T> {
+    pub fn new(inner: T, client: Client, progress: Progress) -> Timeout<T> {
+        #[cfg(feature = "runtime")]
+        let state = match client.query_timeout() {
+            Some(timeout) => State::Queued(timeout),
+            None => State::Running,
+        };
+        #[cfg(not(feature = "runtime"))]
+        let state = State::Running;
+
+        Timeout {
+            inner,
+            client,
+            progress,
+            state,
+        }
+    }
+
+    #[cfg(feature = "runtime")]
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.state = State::Queued(timeout);
+    }
+
+    #[cfg(feature = "runtime")]
+    fn poll_deadline(&mut self) -> Result<(), Error> {
+        loop {
+            match &mut self.state {
+                State::Queued(timeout) => {
+                    if !self.progress.poll_started() {
+                        return Ok(());
+                    }
+                    self.state = State::Waiting(self.client.delay(*timeout));
+                }
+                State::Waiting(delay) => match delay.poll() {
+                    // a cancellation request stops whatever the server is currently processing, which is no longer
+                    // this request once it has finished, so the remainder of its response is simply read
+                    Ok(Async::Ready(())) if self.progress.is_finished() => {
+                        self.state = State::Running
+                    }
+                    Ok(Async::Ready(())) => {
+                        self.state = State::Canceling(self.client.cancel_timed_out_query());
+                    }
+                    Ok(Async::NotReady) => return Ok(()),
+                    Err(e) => return Err(Error::io(io::Error::new(io::ErrorKind::Other, e))),
+                },
+                State::Canceling(future) => match future.poll() {
+                    Ok(Async::Ready(())) => self.state = State::Canceled,
+                    Ok(Async::NotReady) => return Ok(()),
+                    // the query can't be stopped, so give up on it rather than waiting indefinitely
+                    Err(e) => {
+                        self.state = State::Done;
+                        return Err(Error::query_timeout(e));
+                    }
+                },
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    #[cfg(not(feature = "runtime"))]
+    fn poll_deadline(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[cfg(feature = "runtime")]
+    fn map_error(&mut self, e: Error) -> Error {
+        match self.state {
+            State::Canceling(_) | State::Canceled
+                if e.code() == Some(&SqlState::QUERY_CANCELED) =>
+            {
+                self.state = State::Done;
+                Error::query_timeout(e)
+            }
+            _ => e,
+        }
+    }
+
+    #[cfg(not(feature = "runtime"))]
+    fn map_error(&mut self, e: Error) -> Error {
+        e
+    }
+}
+
+impl<F> Future for Timeout<F>
+where
+    F: Future<Error = Error>,
+{
+    type Item = F::Item;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<F::Item, Error> {
+        match self.inner.poll() {
+            Ok(Async::Ready(v)) => return Ok(Async::Ready(v)),
+            Ok(Async::NotReady) => {}
+            Err(e) => return Err(self.map_error(e)),
+        }
+
+        self.poll_deadline()?;
+        Ok(Async::NotReady)
+    }
+}
+
+impl<S> Stream for Timeout<S>
+where
+    S: Stream<Error = Error>,
+{
+    type Item = S::Item;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, Error> {
+        #[cfg(feature = "runtime")]
+        {
+            if let State::Done = self.state {
+                return Ok(Async::Ready(None));
+            }
+        }
+
+        match self.inner.poll() {
+            Ok(Async::Ready(v)) => return Ok(Async::Ready(v)),
+            Ok(Async::NotReady) => {}
+            Err(e) => return Err(self.map_error(e)),
+        }
+
+        self.poll_deadline()?;
+        Ok(Async::NotReady)
+    }
+}
@@ -12,7 +12,11 @@ where
     E: From<Error>,
 {
     #[state_machine_future(start, transitions(Beginning))]
-    Start { client: Client, future: F },
+    Start {
+        client: Client,
+        begin: String,
+        future: F,
+    },
     #[state_machine_future(transitions(Running))]
     Beginning {
         client: Client,
@@ -42,7 +46,7 @@ where
     ) -> Poll<AfterStart<F, T, E>, E> {
         let state = state.take();
         transition!(Beginning {
-            begin: state.client.batch_execute("BEGIN"),
+            begin: state.client.batch_execute(&state.begin),
             client: state.client,
             future: state.future,
         })
@@ -102,7 +106,7 @@ where
     F: Future<Item = T, Error = E>,
     E: From<Error>,
 {
-    pub fn new(client: Client, future: F) -> TransactionFuture<F, T, E> {
-        Transaction::start(client, future)
+    pub fn new(client: Client, begin: String, future: F) -> TransactionFuture<F, T, E> {
+        Transaction::start(client, begin, future)
     }
 }
@@ -0,0 +1,677 @@
+//! Streaming replication support.
+//!
+//! Replication commands can only be issued on connections opened in a replication mode (see
+//! [`Config::replication_mode`]). [`Client::start_logical_replication`] streams changes from a logical replication
+//! slot, while a physical replication connection can stream raw WAL with [`Client::start_physical_replication`] or
+//! take a backup of the database cluster with [`Client::base_backup`]. Other commands, like
+//! `CREATE_REPLICATION_SLOT`, can be run with [`Client::simple_query`].
+//!
+//! The server periodically sends keepalive messages while streaming, and will terminate the connection if it does not
+//! receive a [`StandbyStatusUpdate`] in time, particularly when a keepalive requests a reply.
+//!
+//! [`Config::replication_mode`]: ../struct.Config.html#method.replication_mode
+//! [`Client::simple_query`]: ../struct.Client.html#method.simple_query
+//! [`Client::start_logical_replication`]: ../struct.Client.html#method.start_logical_replication
+//! [`Client::start_physical_replication`]: ../struct.Client.html#method.start_physical_replication
+//! [`Client::base_backup`]: ../struct.Client.html#method.base_backup
+//! [`StandbyStatusUpdate`]: struct.StandbyStatusUpdate.html
+
+use bytes::Bytes;
+use futures::{try_ready, Async, AsyncSink, Future, Poll, Sink, StartSend, Stream};
+use postgres_protocol::message::frontend;
+use std::error;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::proto;
+use crate::types::Oid;
+use crate::{CopyBoth, Error, SimpleQuery, StringRow};
+
+pub use crate::types::Lsn;
+pub use postgres_protocol::message::backend::{
+    BeginBody, Column, CommitBody, DeleteBody, InsertBody, LogicalReplicationMessage,
+    NewArchiveBody, OriginBody, PrimaryKeepAliveBody, ProgressBody, RelationBody, ReplicaIdentity,
+    ReplicationMessage, TruncateBody, Tuple, TupleData, TypeBody, UpdateBody, XLogDataBody,
+};
+
+// seconds between the Unix epoch and the Postgres epoch, 2000-01-01
+const PG_EPOCH_OFFSET: u64 = 946_684_800;
+
+/// Converts a timestamp sent by the server during replication to a `SystemTime`.
+///
+/// Replication timestamps are in microseconds since midnight on 2000-01-01.
+pub fn timestamp_to_system_time(timestamp: i64) -> SystemTime {
+    let epoch = UNIX_EPOCH + Duration::from_secs(PG_EPOCH_OFFSET);
+    if timestamp < 0 {
+        epoch - Duration::from_micros(-timestamp as u64)
+    } else {
+        epoch + Duration::from_micros(timestamp as u64)
+    }
+}
+
+fn system_time_to_timestamp(time: SystemTime) -> i64 {
+    let epoch = UNIX_EPOCH + Duration::from_secs(PG_EPOCH_OFFSET);
+    match time.duration_since(epoch) {
+        Ok(d) => d.as_secs() as i64 * 1_000_000 + i64::from(d.subsec_micros()),
+        Err(e) => {
+            let d = e.duration();
+            -(d.as_secs() as i64 * 1_000_000 + i64::from(d.subsec_micros()))
+        }
+    }
+}
+
+fn quote_identifier(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+fn quote_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn column<'a>(row: &'a StringRow, idx: usize, name: &str) -> Result<Option<&'a str>, Error> {
+    match row.try_get(idx)? {
+        Some(value) => Ok(value),
+        None => Err(Error::from_sql_column(
+            "missing column".into(),
+            name.to_string(),
+        )),
+    }
+}
+
+fn parse_column<T>(row: &StringRow, idx: usize, name: &str) -> Result<Option<T>, Error>
+where
+    T: FromStr,
+    T::Err: error::Error + Sync + Send + 'static,
+{
+    match column(row, idx, name)? {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|e| Error::from_sql_column(Box::new(e), name.to_string())),
+        None => Ok(None),
+    }
+}
+
+fn non_null<T>(value: Option<T>, name: &str) -> Result<T, Error> {
+    value.ok_or_else(|| Error::from_sql_column("unexpected null".into(), name.to_string()))
+}
+
+pub(crate) fn physical_replication_query(
+    slot_name: Option<&str>,
+    start_lsn: Lsn,
+    timeline: Option<u32>,
+) -> String {
+    let mut query = "START_REPLICATION".to_string();
+    if let Some(slot_name) = slot_name {
+        query.push_str(" SLOT ");
+        query.push_str(&quote_identifier(slot_name));
+    }
+    query.push_str(&format!(" PHYSICAL {}", start_lsn));
+    if let Some(timeline) = timeline {
+        query.push_str(&format!(" TIMELINE {}", timeline));
+    }
+    query
+}
+
+pub(crate) fn logical_replication_query(
+    slot_name: &str,
+    start_lsn: Lsn,
+    options: &[(&str, &str)],
+) -> String {
+    let mut query = format!(
+        "START_REPLICATION SLOT {} LOGICAL {}",
+        quote_identifier(slot_name),
+        start_lsn
+    );
+
+    if !options.is_empty() {
+        let options = options
+            .iter()
+            .map(|(key, value)| format!("{} {}", quote_identifier(key), quote_literal(value)))
+            .collect::<Vec<_>>();
+        query.push_str(" (");
+        query.push_str(&options.join(", "));
+        query.push(')');
+    }
+
+    query
+}
+
+/// A standby status update, reporting the replication progress of the client to the server.
+#[derive(Debug, Copy, Clone)]
+pub struct StandbyStatusUpdate {
+    write_lsn: Lsn,
+    flush_lsn: Lsn,
+    apply_lsn: Lsn,
+    timestamp: SystemTime,
+    reply: bool,
+}
+
+impl StandbyStatusUpdate {
+    /// Creates a new status update.
+    ///
+    /// `write_lsn`, `flush_lsn`, and `apply_lsn` are the positions one past the last WAL byte written to disk,
+    /// flushed to disk, and applied by the client respectively. If `reply` is true, the server will respond with a
+    /// keepalive message immediately. The update is timestamped with the current time.
+    pub fn new(write_lsn: Lsn, flush_lsn: Lsn, apply_lsn: Lsn, reply: bool) -> StandbyStatusUpdate {
+        StandbyStatusUpdate {
+            write_lsn,
+            flush_lsn,
+            apply_lsn,
+            timestamp: SystemTime::now(),
+            reply,
+        }
+    }
+
+    /// Returns the position of the last WAL byte written by the client.
+    pub fn write_lsn(&self) -> Lsn {
+        self.write_lsn
+    }
+
+    /// Returns the position of the last WAL byte flushed by the client.
+    pub fn flush_lsn(&self) -> Lsn {
+        self.flush_lsn
+    }
+
+    /// Returns the position of the last WAL byte applied by the client.
+    pub fn apply_lsn(&self) -> Lsn {
+        self.apply_lsn
+    }
+
+    /// Returns the time at which the update was created.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Returns whether the client requests an immediate reply from the server.
+    pub fn reply(&self) -> bool {
+        self.reply
+    }
+
+    fn serialize(&self) -> Bytes {
+        let mut buf = vec![];
+        frontend::standby_status_update(
+            self.write_lsn.as_u64(),
+            self.flush_lsn.as_u64(),
+            self.apply_lsn.as_u64(),
+            system_time_to_timestamp(self.timestamp),
+            self.reply as u8,
+            &mut buf,
+        );
+        Bytes::from(buf)
+    }
+}
+
+/// A stream of replication messages sent by the server, and a sink for status updates sent to it.
+#[must_use = "streams do nothing unless polled"]
+pub struct ReplicationStream(CopyBoth);
+
+impl ReplicationStream {
+    /// Creates a replication stream from a `copy_both` request which started replication.
+    pub fn new(copy_both: CopyBoth) -> ReplicationStream {
+        ReplicationStream(copy_both)
+    }
+}
+
+impl Stream for ReplicationStream {
+    type Item = ReplicationMessage<Bytes>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<ReplicationMessage<Bytes>>, Error> {
+        match try_ready!(self.0.poll()) {
+            Some(buf) => {
+                let message = ReplicationMessage::parse(&buf).map_err(Error::parse)?;
+                Ok(Async::Ready(Some(message)))
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+impl Sink for ReplicationStream {
+    type SinkItem = StandbyStatusUpdate;
+    type SinkError = Error;
+
+    fn start_send(&mut self, item: StandbyStatusUpdate) -> StartSend<StandbyStatusUpdate, Error> {
+        match self.0.start_send(item.serialize())? {
+            AsyncSink::Ready => Ok(AsyncSink::Ready),
+            AsyncSink::NotReady(_) => Ok(AsyncSink::NotReady(item)),
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Error> {
+        self.0.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Error> {
+        self.0.close()
+    }
+}
+
+/// A replication stream whose WAL data has been decoded by the `pgoutput` logical decoding plugin.
+#[must_use = "streams do nothing unless polled"]
+pub struct LogicalReplicationStream(ReplicationStream);
+
+impl LogicalReplicationStream {
+    /// Creates a logical replication stream from a `copy_both` request which started logical replication using
+    /// the `pgoutput` plugin.
+    pub fn new(copy_both: CopyBoth) -> LogicalReplicationStream {
+        LogicalReplicationStream(ReplicationStream::new(copy_both))
+    }
+}
+
+impl Stream for LogicalReplicationStream {
+    type Item = ReplicationMessage<LogicalReplicationMessage>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<ReplicationMessage<LogicalReplicationMessage>>, Error> {
+        let message = match try_ready!(self.0.poll()) {
+            Some(ReplicationMessage::XLogData(body)) => {
+                let body = body
+                    .map_data(|buf| LogicalReplicationMessage::parse(&buf))
+                    .map_err(Error::parse)?;
+                ReplicationMessage::XLogData(body)
+            }
+            Some(ReplicationMessage::PrimaryKeepAlive(body)) => {
+                ReplicationMessage::PrimaryKeepAlive(body)
+            }
+            Some(_) => return Err(Error::unexpected_message()),
+            None => return Ok(Async::Ready(None)),
+        };
+
+        Ok(Async::Ready(Some(message)))
+    }
+}
+
+impl Sink for LogicalReplicationStream {
+    type SinkItem = StandbyStatusUpdate;
+    type SinkError = Error;
+
+    fn start_send(&mut self, item: StandbyStatusUpdate) -> StartSend<StandbyStatusUpdate, Error> {
+        self.0.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Error> {
+        self.0.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Error> {
+        self.0.close()
+    }
+}
+
+/// The response to an `IDENTIFY_SYSTEM` command.
+#[derive(Debug, Clone)]
+pub struct IdentifySystemResponse {
+    systemid: String,
+    timeline: u32,
+    xlogpos: Lsn,
+    dbname: Option<String>,
+}
+
+impl IdentifySystemResponse {
+    fn from_row(row: &StringRow) -> Result<IdentifySystemResponse, Error> {
+        Ok(IdentifySystemResponse {
+            systemid: non_null(parse_column(row, 0, "systemid")?, "systemid")?,
+            timeline: non_null(parse_column(row, 1, "timeline")?, "timeline")?,
+            xlogpos: non_null(parse_column(row, 2, "xlogpos")?, "xlogpos")?,
+            dbname: parse_column(row, 3, "dbname")?,
+        })
+    }
+
+    /// Returns the unique identifier of the database cluster.
+    pub fn systemid(&self) -> &str {
+        &self.systemid
+    }
+
+    /// Returns the current timeline ID.
+    pub fn timeline(&self) -> u32 {
+        self.timeline
+    }
+
+    /// Returns the current WAL flush location.
+    pub fn xlogpos(&self) -> Lsn {
+        self.xlogpos
+    }
+
+    /// Returns the database connected to, if this is a logical replication connection.
+    pub fn dbname(&self) -> Option<&str> {
+        self.dbname.as_ref().map(|s| &**s)
+    }
+}
+
+/// A future which identifies the server.
+#[must_use = "futures do nothing unless polled"]
+pub struct IdentifySystem {
+    stream: SimpleQuery,
+    row: Option<StringRow>,
+}
+
+impl IdentifySystem {
+    pub(crate) fn new(stream: SimpleQuery) -> IdentifySystem {
+        IdentifySystem { stream, row: None }
+    }
+}
+
+impl Future for IdentifySystem {
+    type Item = IdentifySystemResponse;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<IdentifySystemResponse, Error> {
+        while let Some(row) = try_ready!(self.stream.poll()) {
+            self.row = Some(row);
+        }
+
+        match self.row.take() {
+            Some(row) => IdentifySystemResponse::from_row(&row).map(Async::Ready),
+            None => Err(Error::unexpected_message()),
+        }
+    }
+}
+
+/// The response to a `TIMELINE_HISTORY` command.
+#[derive(Debug, Clone)]
+pub struct TimelineHistoryResponse {
+    filename: String,
+    content: Vec<u8>,
+}
+
+impl TimelineHistoryResponse {
+    fn from_row(row: &StringRow) -> Result<TimelineHistoryResponse, Error> {
+        Ok(TimelineHistoryResponse {
+            filename: non_null(column(row, 0, "filename")?, "filename")?.to_string(),
+            content: non_null(column(row, 1, "content")?, "content")?
+                .as_bytes()
+                .to_vec(),
+        })
+    }
+
+    /// Returns the file name of the timeline history file, e.g. `00000002.history`.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Returns the contents of the timeline history file.
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+}
+
+/// A future which retrieves the history file of a timeline.
+#[must_use = "futures do nothing unless polled"]
+pub struct TimelineHistory {
+    stream: SimpleQuery,
+    row: Option<StringRow>,
+}
+
+impl TimelineHistory {
+    pub(crate) fn new(stream: SimpleQuery) -> TimelineHistory {
+        TimelineHistory { stream, row: None }
+    }
+}
+
+impl Future for TimelineHistory {
+    type Item = TimelineHistoryResponse;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<TimelineHistoryResponse, Error> {
+        while let Some(row) = try_ready!(self.stream.poll()) {
+            self.row = Some(row);
+        }
+
+        match self.row.take() {
+            Some(row) => TimelineHistoryResponse::from_row(&row).map(Async::Ready),
+            None => Err(Error::unexpected_message()),
+        }
+    }
+}
+
+/// Options for a `BASE_BACKUP` command.
+#[derive(Debug, Clone, Default)]
+pub struct BaseBackupOptions {
+    label: Option<String>,
+    progress: bool,
+    fast_checkpoint: bool,
+    wal: bool,
+    wait: bool,
+    max_rate: Option<u32>,
+    tablespace_map: bool,
+    manifest: bool,
+}
+
+impl BaseBackupOptions {
+    /// Creates a new set of options with the server's defaults.
+    pub fn new() -> BaseBackupOptions {
+        BaseBackupOptions {
+            wait: true,
+            ..BaseBackupOptions::default()
+        }
+    }
+
+    /// Sets the label of the backup.
+    ///
+    /// Defaults to `base backup`.
+    pub fn label(&mut self, label: &str) -> &mut BaseBackupOptions {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Controls whether the server reports the size of each tablespace and, on Postgres 15 and newer, the progress of
+    /// the backup.
+    ///
+    /// Defaults to `false`.
+    pub fn progress(&mut self, progress: bool) -> &mut BaseBackupOptions {
+        self.progress = progress;
+        self
+    }
+
+    /// Controls whether the server requests an immediate checkpoint rather than spreading it out over time.
+    ///
+    /// Defaults to `false`.
+    pub fn fast_checkpoint(&mut self, fast_checkpoint: bool) -> &mut BaseBackupOptions {
+        self.fast_checkpoint = fast_checkpoint;
+        self
+    }
+
+    /// Controls whether the WAL needed to restore the backup is included in the main archive.
+    ///
+    /// Defaults to `false`.
+    pub fn wal(&mut self, wal: bool) -> &mut BaseBackupOptions {
+        self.wal = wal;
+        self
+    }
+
+    /// Controls whether the server waits for the WAL needed by the backup to be archived before completing.
+    ///
+    /// Defaults to `true`.
+    pub fn wait(&mut self, wait: bool) -> &mut BaseBackupOptions {
+        self.wait = wait;
+        self
+    }
+
+    /// Sets the maximum transfer rate of the backup, in kilobytes per second.
+    ///
+    /// Defaults to no limit.
+    pub fn max_rate(&mut self, max_rate: u32) -> &mut BaseBackupOptions {
+        self.max_rate = Some(max_rate);
+        self
+    }
+
+    /// Controls whether the server includes a `tablespace_map` file describing the tablespaces in the main archive.
+    ///
+    /// Defaults to `false`.
+    pub fn tablespace_map(&mut self, tablespace_map: bool) -> &mut BaseBackupOptions {
+        self.tablespace_map = tablespace_map;
+        self
+    }
+
+    /// Controls whether the server sends a backup manifest after the archives.
+    ///
+    /// Requires Postgres 13 or newer. Defaults to `false`.
+    pub fn manifest(&mut self, manifest: bool) -> &mut BaseBackupOptions {
+        self.manifest = manifest;
+        self
+    }
+}
+
+pub(crate) fn base_backup_query(options: &BaseBackupOptions, legacy: bool) -> String {
+    // servers before Postgres 15 only understand the older syntax without parentheses
+    if legacy {
+        let mut query = "BASE_BACKUP".to_string();
+        if let Some(label) = &options.label {
+            query.push_str(&format!(" LABEL {}", quote_literal(label)));
+        }
+        if options.progress {
+            query.push_str(" PROGRESS");
+        }
+        if options.fast_checkpoint {
+            query.push_str(" FAST");
+        }
+        if options.wal {
+            query.push_str(" WAL");
+        }
+        if !options.wait {
+            query.push_str(" NOWAIT");
+        }
+        if let Some(max_rate) = options.max_rate {
+            query.push_str(&format!(" MAX_RATE {}", max_rate));
+        }
+        if options.tablespace_map {
+            query.push_str(" TABLESPACE_MAP");
+        }
+        if options.manifest {
+            query.push_str(" MANIFEST 'yes'");
+        }
+        return query;
+    }
+
+    let mut parts = vec![];
+    if let Some(label) = &options.label {
+        parts.push(format!("LABEL {}", quote_literal(label)));
+    }
+    if options.progress {
+        parts.push("PROGRESS".to_string());
+    }
+    if options.fast_checkpoint {
+        parts.push("CHECKPOINT 'fast'".to_string());
+    }
+    if options.wal {
+        parts.push("WAL".to_string());
+    }
+    if !options.wait {
+        parts.push("WAIT false".to_string());
+    }
+    if let Some(max_rate) = options.max_rate {
+        parts.push(format!("MAX_RATE {}", max_rate));
+    }
+    if options.tablespace_map {
+        parts.push("TABLESPACE_MAP".to_string());
+    }
+    if options.manifest {
+        parts.push("MANIFEST 'yes'".to_string());
+    }
+
+    if parts.is_empty() {
+        "BASE_BACKUP".to_string()
+    } else {
+        format!("BASE_BACKUP ({})", parts.join(", "))
+    }
+}
+
+/// A position in the WAL reported at the start and end of a base backup.
+#[derive(Debug, Copy, Clone)]
+pub struct BackupPosition {
+    lsn: Lsn,
+    timeline: u32,
+}
+
+impl BackupPosition {
+    pub(crate) fn from_row(row: &StringRow) -> Result<BackupPosition, Error> {
+        Ok(BackupPosition {
+            lsn: non_null(parse_column(row, 0, "recptr")?, "recptr")?,
+            timeline: non_null(parse_column(row, 1, "tli")?, "tli")?,
+        })
+    }
+
+    /// Returns the WAL location.
+    pub fn lsn(&self) -> Lsn {
+        self.lsn
+    }
+
+    /// Returns the timeline ID.
+    pub fn timeline(&self) -> u32 {
+        self.timeline
+    }
+}
+
+/// A tablespace included in a base backup.
+#[derive(Debug, Clone)]
+pub struct Tablespace {
+    oid: Option<Oid>,
+    location: Option<String>,
+    size: Option<i64>,
+}
+
+impl Tablespace {
+    pub(crate) fn from_row(row: &StringRow) -> Result<Tablespace, Error> {
+        Ok(Tablespace {
+            oid: parse_column(row, 0, "spcoid")?,
+            location: parse_column(row, 1, "spclocation")?,
+            size: parse_column(row, 2, "size")?,
+        })
+    }
+
+    /// Returns the OID of the tablespace, or `None` for the main data directory.
+    pub fn oid(&self) -> Option<Oid> {
+        self.oid
+    }
+
+    /// Returns the path of the tablespace's directory, or `None` for the main data directory.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_ref().map(|s| &**s)
+    }
+
+    /// Returns the approximate size of the tablespace in kilobytes, if progress reporting was requested.
+    pub fn size(&self) -> Option<i64> {
+        self.size
+    }
+}
+
+/// A message sent by the server while streaming a base backup.
+pub enum BaseBackupMessage {
+    /// The backup has started.
+    Start(BackupPosition),
+    /// A tablespace which will be included in the backup.
+    Tablespace(Tablespace),
+    /// The start of a new tar archive, whose contents are sent in subsequent `Data` messages.
+    NewArchive(NewArchiveBody),
+    /// The start of the backup manifest, whose contents are sent in subsequent `Data` messages.
+    Manifest,
+    /// A chunk of the current archive or manifest.
+    Data(Bytes),
+    /// The progress of the backup, if progress reporting was requested and the server is Postgres 15 or newer.
+    Progress(ProgressBody),
+    /// The backup has completed.
+    End(BackupPosition),
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+/// A stream of the contents of a base backup.
+#[must_use = "streams do nothing unless polled"]
+pub struct BaseBackup(proto::BaseBackupStream);
+
+impl BaseBackup {
+    pub(crate) fn new(stream: proto::BaseBackupStream) -> BaseBackup {
+        BaseBackup(stream)
+    }
+}
+
+impl Stream for BaseBackup {
+    type Item = BaseBackupMessage;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<BaseBackupMessage>, Error> {
+        self.0.poll()
+    }
+}
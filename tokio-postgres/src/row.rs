@@ -65,6 +65,39 @@ where
     }
 }
 
+/// A trait for types which can be created from a `Row`.
+///
+/// If the `derive` Cargo feature is enabled, this can be derived for structs. Fields of structs
+/// with named fields are looked up by column name, while fields of tuple structs are looked up by
+/// column index. The following field attributes are supported:
+///
+/// * `#[postgres(name = "...")]` - looks the field up by a different column name.
+/// * `#[postgres(index = N)]` - looks the field up by column index.
+/// * `#[postgres(flatten)]` - builds the field from the same row with its own `FromRow`
+///   implementation.
+/// * `#[postgres(default)]` - uses `Default::default()` if the column is not present in the row.
+///
+/// The struct itself accepts a `#[postgres(crate = "...")]` attribute, described in the
+/// `postgres-derive` documentation.
+///
+/// ```ignore
+/// #[derive(FromRow)]
+/// struct User {
+///     id: i32,
+///     #[postgres(name = "username")]
+///     name: String,
+///     email: Option<String>,
+///     #[postgres(default)]
+///     nickname: Option<String>,
+/// }
+/// ```
+pub trait FromRow: Sized {
+    /// Creates a new value from a row.
+    ///
+    /// Errors name the column that was missing or failed to deserialize.
+    fn from_row(row: &Row) -> Result<Self, Error>;
+}
+
 pub struct Row {
     statement: proto::Statement,
     body: DataRowBody,
@@ -114,6 +147,49 @@ impl Row {
         self.get_inner(&idx)
     }
 
+    // WARNING: this method is not considered part of this crate's public API.
+    // It is used by code generated by `postgres-derive` and is subject to change at any time.
+    #[doc(hidden)]
+    pub fn __get<'a, I, T>(&'a self, idx: I) -> Result<T, Error>
+    where
+        I: RowIndex + fmt::Display,
+        T: FromSql<'a>,
+    {
+        match self.__try_get(&idx)? {
+            Some(value) => Ok(value),
+            None => Err(Error::column(idx.to_string())),
+        }
+    }
+
+    // WARNING: this method is not considered part of this crate's public API.
+    // It is used by code generated by `postgres-derive` and is subject to change at any time.
+    #[doc(hidden)]
+    pub fn __try_get<'a, I, T>(&'a self, idx: I) -> Result<Option<T>, Error>
+    where
+        I: RowIndex,
+        T: FromSql<'a>,
+    {
+        let idx = match idx.__idx(self.columns()) {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+
+        let column = &self.columns()[idx];
+        let ty = column.type_();
+        if !T::accepts(ty) {
+            return Err(Error::from_sql_column(
+                Box::new(WrongType::new(ty.clone())),
+                column.name().to_string(),
+            ));
+        }
+
+        let buf = self.ranges[idx].clone().map(|r| &self.body.buffer()[r]);
+        let value = FromSql::from_sql_nullable(ty, buf);
+        value
+            .map(Some)
+            .map_err(|e| Error::from_sql_column(e, column.name().to_string()))
+    }
+
     fn get_inner<'a, I, T>(&'a self, idx: &I) -> Result<Option<T>, Error>
     where
         I: RowIndex,
@@ -0,0 +1,121 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// Looks up the settings of a service in the connection service files.
+///
+/// Like libpq, the user's service file is searched first, followed by the system-wide file if the service isn't
+/// defined there.
+pub(crate) fn find_service(name: &str) -> Result<Vec<(String, String)>, Error> {
+    let paths = user_path()
+        .into_iter()
+        .chain(env::var_os("PGSYSCONFDIR").map(|dir| Path::new(&dir).join("pg_service.conf")));
+
+    for path in paths {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(Error::config_parse(Box::new(e))),
+        };
+
+        if let Some(settings) = parse_service(&path, &contents, name)? {
+            return Ok(settings);
+        }
+    }
+
+    Err(Error::config_parse(
+        format!("definition of service \"{}\" not found", name).into(),
+    ))
+}
+
+fn user_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("PGSERVICEFILE") {
+        return Some(PathBuf::from(path));
+    }
+
+    default_user_path()
+}
+
+#[cfg(unix)]
+fn default_user_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| Path::new(&home).join(".pg_service.conf"))
+}
+
+#[cfg(windows)]
+fn default_user_path() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(|appdata| {
+        Path::new(&appdata)
+            .join("postgresql")
+            .join(".pg_service.conf")
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn default_user_path() -> Option<PathBuf> {
+    None
+}
+
+// the file is in an INI-like format, with sections named after services containing `key=value` lines.
+fn parse_service(
+    path: &Path,
+    contents: &str,
+    name: &str,
+) -> Result<Option<Vec<(String, String)>>, Error> {
+    let mut settings = None;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            if settings.is_some() {
+                break;
+            }
+            if line.ends_with(']') && line[1..line.len() - 1] == *name {
+                settings = Some(vec![]);
+            }
+            continue;
+        }
+
+        let settings = match &mut settings {
+            Some(settings) => settings,
+            None => continue,
+        };
+
+        let mut it = line.splitn(2, '=');
+        let key = it.next().unwrap().trim();
+        let value = match it.next() {
+            Some(value) => value.trim(),
+            None => {
+                return Err(Error::config_parse(
+                    format!(
+                        "syntax error in service file {}, line {}",
+                        path.display(),
+                        i + 1
+                    )
+                    .into(),
+                ));
+            }
+        };
+
+        if key == "service" {
+            return Err(Error::config_parse(
+                format!(
+                    "nested service specifications not supported in service file {}, line {}",
+                    path.display(),
+                    i + 1
+                )
+                .into(),
+            ));
+        }
+
+        settings.push((key.to_string(), value.to_string()));
+    }
+
+    Ok(settings)
+}
@@ -7,6 +7,10 @@ use std::io::{self, Read, Write};
 use tokio_io::{AsyncRead, AsyncWrite};
 use void::Void;
 
+#[cfg(feature = "runtime")]
+use crate::Config;
+use crate::SslMode;
+
 pub struct ChannelBinding {
     pub(crate) tls_server_end_point: Option<Vec<u8>>,
 }
@@ -31,7 +35,11 @@ pub trait MakeTlsMode<S> {
     type TlsMode: TlsMode<S, Stream = Self::Stream>;
     type Error: Into<Box<dyn Error + Sync + Send>>;
 
-    fn make_tls_mode(&mut self, domain: &str) -> Result<Self::TlsMode, Self::Error>;
+    fn make_tls_mode(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<Self::TlsMode, Self::Error>;
 }
 
 pub trait TlsMode<S> {
@@ -50,7 +58,11 @@ pub trait MakeTlsConnect<S> {
     type TlsConnect: TlsConnect<S, Stream = Self::Stream>;
     type Error: Into<Box<dyn Error + Sync + Send>>;
 
-    fn make_tls_connect(&mut self, domain: &str) -> Result<Self::TlsConnect, Self::Error>;
+    fn make_tls_connect(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<Self::TlsConnect, Self::Error>;
 }
 
 pub trait TlsConnect<S> {
@@ -71,10 +83,19 @@ where
 {
     type Stream = S;
     type TlsMode = NoTls;
-    type Error = Void;
-
-    fn make_tls_mode(&mut self, _: &str) -> Result<NoTls, Void> {
-        Ok(NoTls)
+    type Error = SslModeUnsupportedError;
+
+    fn make_tls_mode(
+        &mut self,
+        _: &str,
+        config: &Config,
+    ) -> Result<NoTls, SslModeUnsupportedError> {
+        match config.get_ssl_mode() {
+            Some(ssl_mode @ SslMode::Require)
+            | Some(ssl_mode @ SslMode::VerifyCa)
+            | Some(ssl_mode @ SslMode::VerifyFull) => Err(SslModeUnsupportedError(ssl_mode)),
+            _ => Ok(NoTls),
+        }
     }
 }
 
@@ -107,11 +128,15 @@ where
     S: AsyncRead + AsyncWrite,
 {
     type Stream = MaybeTlsStream<T::Stream, S>;
-    type TlsMode = PreferTls<T::TlsConnect>;
+    type TlsMode = SslModeTls<T::TlsConnect>;
     type Error = T::Error;
 
-    fn make_tls_mode(&mut self, domain: &str) -> Result<PreferTls<T::TlsConnect>, T::Error> {
-        self.0.make_tls_connect(domain).map(PreferTls)
+    fn make_tls_mode(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<SslModeTls<T::TlsConnect>, T::Error> {
+        SslModeTls::from_config(config, || self.0.make_tls_connect(domain, config))
     }
 }
 
@@ -261,10 +286,23 @@ where
 {
     type Stream = T::Stream;
     type TlsMode = RequireTls<T::TlsConnect>;
-    type Error = T::Error;
+    type Error = Box<dyn Error + Sync + Send>;
 
-    fn make_tls_mode(&mut self, domain: &str) -> Result<RequireTls<T::TlsConnect>, T::Error> {
-        self.0.make_tls_connect(domain).map(RequireTls)
+    fn make_tls_mode(
+        &mut self,
+        domain: &str,
+        config: &Config,
+    ) -> Result<RequireTls<T::TlsConnect>, Box<dyn Error + Sync + Send>> {
+        match config.get_ssl_mode() {
+            Some(ssl_mode @ SslMode::Disable) | Some(ssl_mode @ SslMode::Allow) => {
+                Err(SslModeUnsupportedError(ssl_mode).into())
+            }
+            _ => self
+                .0
+                .make_tls_connect(domain, config)
+                .map(RequireTls)
+                .map_err(Into::into),
+        }
     }
 }
 
@@ -302,6 +340,30 @@ impl fmt::Display for TlsUnsupportedError {
 
 impl Error for TlsUnsupportedError {}
 
+/// An error returned when a `MakeTlsMode` implementation can't honor the `sslmode` of the connection's
+/// configuration.
+///
+/// `NoTls` can't honor the modes which require TLS, and `RequireTls` can't honor the `disable` and `allow` modes.
+#[derive(Debug)]
+pub struct SslModeUnsupportedError(SslMode);
+
+impl fmt::Display for SslModeUnsupportedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ssl_mode = match self.0 {
+            SslMode::Disable => "disable",
+            SslMode::Allow => "allow",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full",
+            SslMode::__NonExhaustive => unreachable!(),
+        };
+        write!(fmt, "sslmode={} is not supported by the TLS mode", ssl_mode)
+    }
+}
+
+impl Error for SslModeUnsupportedError {}
+
 pub struct RequireTlsFuture<T> {
     f: Option<Result<T, Box<dyn Error + Sync + Send>>>,
 }
@@ -327,3 +389,85 @@ where
         }
     }
 }
+
+/// A `TlsMode` which uses TLS as directed by an `SslMode`.
+///
+/// TLS backends can implement `MakeTlsMode` by creating this mode with `SslModeTls::from_config`, which follows the
+/// `sslmode` of the connection's configuration, treating an unset mode as `Prefer`. Whether the server's certificate
+/// is verified is up to the backend.
+#[derive(Debug, Copy, Clone)]
+pub struct SslModeTls<T> {
+    tls_connect: Option<T>,
+    ssl_mode: SslMode,
+}
+
+impl<T> SslModeTls<T> {
+    /// Creates a new `SslModeTls`.
+    pub fn new(tls_connect: T, ssl_mode: SslMode) -> SslModeTls<T> {
+        SslModeTls {
+            tls_connect: Some(tls_connect),
+            ssl_mode,
+        }
+    }
+
+    /// Creates a new `SslModeTls` following the `sslmode` of a connection's configuration.
+    ///
+    /// `make_tls_connect` is only called if TLS may be used, so no TLS connector needs to be built for the `disable`
+    /// and `allow` modes.
+    #[cfg(feature = "runtime")]
+    pub fn from_config<F, E>(config: &Config, make_tls_connect: F) -> Result<SslModeTls<T>, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        match config.get_ssl_mode() {
+            // a connection with sslmode=allow is retried with sslmode=require if TLS turns out to be needed
+            Some(ssl_mode @ SslMode::Disable) | Some(ssl_mode @ SslMode::Allow) => Ok(SslModeTls {
+                tls_connect: None,
+                ssl_mode,
+            }),
+            ssl_mode => Ok(SslModeTls::new(
+                make_tls_connect()?,
+                ssl_mode.unwrap_or(SslMode::Prefer),
+            )),
+        }
+    }
+}
+
+impl<T, S> TlsMode<S> for SslModeTls<T>
+where
+    T: TlsConnect<S>,
+    S: AsyncRead + AsyncWrite,
+{
+    type Stream = MaybeTlsStream<T::Stream, S>;
+    type Error = Box<dyn Error + Sync + Send>;
+    type Future = RequireTlsFuture<PreferTlsFuture<T::Future, S>>;
+
+    fn request_tls(&self) -> bool {
+        match self.ssl_mode {
+            SslMode::Disable | SslMode::Allow => false,
+            _ => self.tls_connect.is_some(),
+        }
+    }
+
+    fn handle_tls(
+        self,
+        use_tls: bool,
+        stream: S,
+    ) -> RequireTlsFuture<PreferTlsFuture<T::Future, S>> {
+        let required = match self.ssl_mode {
+            SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => true,
+            _ => false,
+        };
+
+        let f = match self.tls_connect {
+            _ if required && !use_tls => Err(TlsUnsupportedError(()).into()),
+            Some(tls_connect) => Ok(PreferTls(tls_connect).handle_tls(use_tls, stream)),
+            None => {
+                debug_assert!(!use_tls);
+                Ok(PreferTlsFuture(PreferTlsFutureInner::Raw(Some(stream))))
+            }
+        };
+
+        RequireTlsFuture { f: Some(f) }
+    }
+}
@@ -0,0 +1,96 @@
+use bigdecimal_04::num_bigint::{BigInt, BigUint, Sign};
+use bigdecimal_04::BigDecimal;
+use postgres_protocol::types::{self, NumericSign};
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::types::{FromSql, IsNull, ToSql, Type};
+
+impl<'a> FromSql<'a> for BigDecimal {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<BigDecimal, Box<dyn Error + Sync + Send>> {
+        let numeric = types::numeric_from_sql(raw)?;
+        let sign = match numeric.sign() {
+            NumericSign::Positive => Sign::Plus,
+            NumericSign::Negative => Sign::Minus,
+            _ => return Err("BigDecimal cannot represent non-finite values".into()),
+        };
+
+        let digits = numeric.digits();
+        // the base-10000 exponent of the least significant digit
+        let exponent = i64::from(numeric.weight()) + 1 - digits.len() as i64;
+
+        let mut s = String::from("0");
+        for digit in digits {
+            write!(s, "{:04}", digit).unwrap();
+        }
+        let magnitude = BigUint::parse_bytes(s.as_bytes(), 10).unwrap();
+
+        let value = BigDecimal::new(BigInt::from_biguint(sign, magnitude), -exponent * 4);
+        // digits past the value's scale are always zero, so this never loses precision
+        Ok(value.with_scale(i64::from(numeric.scale())))
+    }
+
+    accepts!(NUMERIC);
+}
+
+impl ToSql for BigDecimal {
+    fn to_sql(&self, _: &Type, w: &mut Vec<u8>) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let (mantissa, scale) = self.as_bigint_and_exponent();
+        let sign = match mantissa.sign() {
+            Sign::Minus => NumericSign::Negative,
+            Sign::NoSign | Sign::Plus => NumericSign::Positive,
+        };
+
+        // Postgres doesn't support negative scales, so expand those into trailing zeros
+        let mut s = mantissa.magnitude().to_str_radix(10);
+        let scale = if scale < 0 {
+            if mantissa.sign() != Sign::NoSign {
+                // the exponent can be arbitrarily large, so the size of the value is checked before expanding it
+                let len = s.len() as i128 - i128::from(scale);
+                if (len - 1) / 4 > i128::from(i16::MAX) {
+                    return Err("value too large for NUMERIC".into());
+                }
+                s.push_str(&"0".repeat((-i128::from(scale)) as usize));
+            }
+            0
+        } else {
+            scale
+        };
+        if scale > i64::from(i16::MAX) {
+            return Err("value too precise for NUMERIC".into());
+        }
+        let scale = scale as usize;
+
+        // pad the digits so that the decimal point falls on a base-10000 digit boundary
+        let padding = (4 - scale % 4) % 4;
+        s.push_str(&"0".repeat(padding));
+        let s = "0".repeat((4 - s.len() % 4) % 4) + &s;
+        let fractional_digits = (scale + padding) / 4;
+
+        let mut digits = s
+            .as_bytes()
+            .chunks(4)
+            .map(|c| c.iter().fold(0, |acc, &b| acc * 10 + i16::from(b - b'0')))
+            .collect::<Vec<_>>();
+
+        let leading_zeros = digits.iter().take_while(|&&d| d == 0).count();
+        digits.drain(..leading_zeros);
+        let trailing_zeros = digits.iter().rev().take_while(|&&d| d == 0).count();
+        digits.truncate(digits.len() - trailing_zeros);
+
+        let weight = if digits.is_empty() {
+            0
+        } else {
+            (s.len() / 4) as i64 - leading_zeros as i64 - fractional_digits as i64 - 1
+        };
+        if weight > i64::from(i16::MAX) {
+            return Err("value too large for NUMERIC".into());
+        }
+
+        types::numeric_to_sql(sign, weight as i16, scale as u16, digits, w)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(NUMERIC);
+    to_sql_checked!();
+}
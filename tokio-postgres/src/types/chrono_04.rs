@@ -1,8 +1,9 @@
 use chrono_04::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use postgres_protocol::types;
+use std::convert::TryFrom;
 use std::error::Error;
 
-use crate::types::{FromSql, IsNull, ToSql, Type};
+use crate::types::{FromSql, Interval, IsNull, ToSql, TryFromIntervalError, Type};
 
 fn base() -> NaiveDateTime {
     NaiveDate::from_ymd(2000, 1, 1).and_hms(0, 0, 0)
@@ -147,3 +148,32 @@ impl ToSql for NaiveTime {
     accepts!(TIME);
     to_sql_checked!();
 }
+
+impl TryFrom<Interval> for Duration {
+    type Error = TryFromIntervalError;
+
+    fn try_from(interval: Interval) -> Result<Duration, TryFromIntervalError> {
+        if interval.months != 0 {
+            return Err(TryFromIntervalError(()));
+        }
+
+        Duration::days(i64::from(interval.days))
+            .checked_add(&Duration::microseconds(interval.microseconds))
+            .ok_or(TryFromIntervalError(()))
+    }
+}
+
+impl TryFrom<Duration> for Interval {
+    type Error = TryFromIntervalError;
+
+    fn try_from(duration: Duration) -> Result<Interval, TryFromIntervalError> {
+        let microseconds = duration
+            .num_microseconds()
+            .ok_or(TryFromIntervalError(()))?;
+        if Duration::microseconds(microseconds) != duration {
+            return Err(TryFromIntervalError(()));
+        }
+
+        Ok(Interval::new(0, 0, microseconds))
+    }
+}
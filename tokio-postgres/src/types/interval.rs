@@ -0,0 +1,110 @@
+use postgres_protocol::types;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use crate::types::{FromSql, IsNull, ToSql, Type};
+
+const USEC_PER_DAY: i128 = 86_400_000_000;
+const NSEC_PER_USEC: u32 = 1_000;
+
+/// A Postgres `INTERVAL` value.
+///
+/// Postgres stores the month, day, and sub-day components of an interval separately since the
+/// length of a month or a day can vary. Conversions to and from duration types are provided
+/// through `TryFrom`, and fail if they would lose information. For those conversions a day is
+/// treated as exactly 24 hours, while intervals with a nonzero month component are rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Interval {
+    /// The number of months.
+    pub months: i32,
+    /// The number of days.
+    pub days: i32,
+    /// The number of microseconds.
+    pub microseconds: i64,
+}
+
+impl Interval {
+    /// Creates a new `Interval`.
+    pub fn new(months: i32, days: i32, microseconds: i64) -> Interval {
+        Interval {
+            months,
+            days,
+            microseconds,
+        }
+    }
+
+    /// Returns the total number of microseconds in the interval, treating a day as 24 hours.
+    ///
+    /// Returns `None` if the interval has a nonzero month component.
+    pub(crate) fn total_microseconds(&self) -> Option<i128> {
+        if self.months != 0 {
+            return None;
+        }
+
+        Some(i128::from(self.days) * USEC_PER_DAY + i128::from(self.microseconds))
+    }
+}
+
+impl<'a> FromSql<'a> for Interval {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Interval, Box<dyn Error + Sync + Send>> {
+        let interval = types::interval_from_sql(raw)?;
+        Ok(Interval {
+            months: interval.months(),
+            days: interval.days(),
+            microseconds: interval.microseconds(),
+        })
+    }
+
+    accepts!(INTERVAL);
+}
+
+impl ToSql for Interval {
+    fn to_sql(&self, _: &Type, w: &mut Vec<u8>) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::interval_to_sql(self.months, self.days, self.microseconds, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INTERVAL);
+    to_sql_checked!();
+}
+
+impl TryFrom<Interval> for Duration {
+    type Error = TryFromIntervalError;
+
+    fn try_from(interval: Interval) -> Result<Duration, TryFromIntervalError> {
+        let microseconds = interval
+            .total_microseconds()
+            .ok_or(TryFromIntervalError(()))?;
+        let microseconds = u64::try_from(microseconds).map_err(|_| TryFromIntervalError(()))?;
+        Ok(Duration::from_micros(microseconds))
+    }
+}
+
+impl TryFrom<Duration> for Interval {
+    type Error = TryFromIntervalError;
+
+    fn try_from(duration: Duration) -> Result<Interval, TryFromIntervalError> {
+        if duration.subsec_micros() * NSEC_PER_USEC != duration.subsec_nanos() {
+            return Err(TryFromIntervalError(()));
+        }
+
+        let microseconds =
+            i64::try_from(duration.as_micros()).map_err(|_| TryFromIntervalError(()))?;
+        Ok(Interval::new(0, 0, microseconds))
+    }
+}
+
+/// The error type returned when a conversion between an `Interval` and a duration would lose
+/// information or overflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryFromIntervalError(pub(crate) ());
+
+impl fmt::Display for TryFromIntervalError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("interval conversion would lose information or overflow")
+    }
+}
+
+impl Error for TryFromIntervalError {}
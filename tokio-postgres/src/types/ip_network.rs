@@ -0,0 +1,81 @@
+use postgres_protocol::types;
+use std::error::Error;
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::types::{FromSql, IsNull, ToSql, Type};
+
+/// An IP address along with a netmask, corresponding to the `Type::INET` and `Type::CIDR` types.
+///
+/// Unlike `IpAddr`, this preserves the netmask of the value, so a `CIDR` such as `10.0.0.0/8`
+/// round trips without losing its prefix length.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    netmask: u8,
+}
+
+impl IpNetwork {
+    /// Creates a new `IpNetwork` from an address and a netmask, as a number of bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the netmask is larger than the number of bits in the address.
+    pub fn new(addr: IpAddr, netmask: u8) -> IpNetwork {
+        let max = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        assert!(netmask <= max, "netmask {} out of range", netmask);
+
+        IpNetwork { addr, netmask }
+    }
+
+    /// Returns the IP address.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the netmask, as a number of bits.
+    pub fn netmask(&self) -> u8 {
+        self.netmask
+    }
+}
+
+impl From<IpAddr> for IpNetwork {
+    fn from(addr: IpAddr) -> IpNetwork {
+        let netmask = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        IpNetwork { addr, netmask }
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}/{}", self.addr, self.netmask)
+    }
+}
+
+impl<'a> FromSql<'a> for IpNetwork {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<IpNetwork, Box<dyn Error + Sync + Send>> {
+        let inet = types::inet_from_sql(raw)?;
+        Ok(IpNetwork {
+            addr: inet.addr(),
+            netmask: inet.netmask(),
+        })
+    }
+
+    accepts!(INET, CIDR);
+}
+
+impl ToSql for IpNetwork {
+    fn to_sql(&self, _: &Type, w: &mut Vec<u8>) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::inet_to_sql(self.addr, self.netmask, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INET, CIDR);
+    to_sql_checked!();
+}
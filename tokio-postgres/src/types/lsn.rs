@@ -0,0 +1,90 @@
+use postgres_protocol::types;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::types::{FromSql, IsNull, ToSql, Type};
+
+/// A position in the write-ahead log, corresponding to the `Type::PG_LSN` type.
+///
+/// LSNs are displayed and parsed in Postgres's `XXXXXXXX/XXXXXXXX` format, with the high and low 32 bits of the
+/// position in hexadecimal.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lsn(u64);
+
+impl Lsn {
+    /// Creates an LSN from its raw 64 bit representation.
+    pub fn new(lsn: u64) -> Lsn {
+        Lsn(lsn)
+    }
+
+    /// Returns the raw 64 bit representation of the LSN.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for Lsn {
+    fn from(lsn: u64) -> Lsn {
+        Lsn(lsn)
+    }
+}
+
+impl From<Lsn> for u64 {
+    fn from(lsn: Lsn) -> u64 {
+        lsn.0
+    }
+}
+
+impl fmt::Display for Lsn {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{:X}/{:X}", self.0 >> 32, self.0 & 0xFFFF_FFFF)
+    }
+}
+
+impl FromStr for Lsn {
+    type Err = ParseLsnError;
+
+    fn from_str(s: &str) -> Result<Lsn, ParseLsnError> {
+        let mut it = s.splitn(2, '/');
+        let hi = it.next().ok_or(ParseLsnError(()))?;
+        let lo = it.next().ok_or(ParseLsnError(()))?;
+        if hi.is_empty() || hi.len() > 8 || lo.is_empty() || lo.len() > 8 {
+            return Err(ParseLsnError(()));
+        }
+        let hi = u64::from_str_radix(hi, 16).map_err(|_| ParseLsnError(()))?;
+        let lo = u64::from_str_radix(lo, 16).map_err(|_| ParseLsnError(()))?;
+        Ok(Lsn(hi << 32 | lo))
+    }
+}
+
+impl<'a> FromSql<'a> for Lsn {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Lsn, Box<dyn Error + Sync + Send>> {
+        let lsn = types::int8_from_sql(raw)?;
+        Ok(Lsn(lsn as u64))
+    }
+
+    accepts!(PG_LSN);
+}
+
+impl ToSql for Lsn {
+    fn to_sql(&self, _: &Type, w: &mut Vec<u8>) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::int8_to_sql(self.0 as i64, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(PG_LSN);
+    to_sql_checked!();
+}
+
+/// An error returned when parsing an `Lsn` from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLsnError(());
+
+impl fmt::Display for ParseLsnError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("invalid LSN")
+    }
+}
+
+impl Error for ParseLsnError {}
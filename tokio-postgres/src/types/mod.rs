@@ -1,4 +1,10 @@
 //! Types.
+//!
+//! # Derive
+//!
+//! If the `derive` Cargo feature is enabled, `ToSql` and `FromSql` can be derived for C-like
+//! enums (mapped to Postgres enums), newtype structs (mapped to domains), and structs with named
+//! fields (mapped to composite types). See the `postgres-derive` crate for details.
 
 use fallible_iterator::FallibleIterator;
 use postgres_protocol;
@@ -8,6 +14,7 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::hash::BuildHasher;
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -16,6 +23,14 @@ use crate::types::type_gen::{Inner, Other};
 #[doc(inline)]
 pub use postgres_protocol::Oid;
 
+#[cfg(feature = "derive")]
+#[doc(inline)]
+pub use postgres_derive::{FromSql, ToSql};
+
+pub use crate::types::interval::{Interval, TryFromIntervalError};
+pub use crate::types::ip_network::IpNetwork;
+pub use crate::types::lsn::{Lsn, ParseLsnError};
+pub use crate::types::range::{Range, RangeBound};
 pub use crate::types::special::{Date, Timestamp};
 
 // Number of seconds from 1970-01-01 to 2000-01-01
@@ -72,6 +87,73 @@ where
     v.to_sql(ty, out)
 }
 
+// WARNING: this function is not considered part of this crate's public API.
+// It is used by code generated by `postgres-derive` and is subject to change at any time.
+#[doc(hidden)]
+pub fn __read_be_i32(buf: &mut &[u8]) -> Result<i32, Box<dyn Error + Sync + Send>> {
+    if buf.len() < 4 {
+        return Err("invalid buffer size".into());
+    }
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&buf[..4]);
+    *buf = &buf[4..];
+    Ok(i32::from_be_bytes(bytes))
+}
+
+// WARNING: this function is not considered part of this crate's public API.
+// It is used by code generated by `postgres-derive` and is subject to change at any time.
+#[doc(hidden)]
+pub fn __read_composite_field<'a, T>(
+    type_: &Type,
+    buf: &mut &'a [u8],
+) -> Result<T, Box<dyn Error + Sync + Send>>
+where
+    T: FromSql<'a>,
+{
+    let oid = __read_be_i32(buf)? as Oid;
+    if oid != type_.oid() {
+        return Err(format!("unexpected field type OID {}", oid).into());
+    }
+
+    let len = __read_be_i32(buf)?;
+    let value = if len < 0 {
+        None
+    } else {
+        let len = len as usize;
+        if buf.len() < len {
+            return Err("invalid buffer size".into());
+        }
+        let (head, tail) = buf.split_at(len);
+        *buf = tail;
+        Some(head)
+    };
+    T::from_sql_nullable(type_, value)
+}
+
+// WARNING: this function is not considered part of this crate's public API.
+// It is used by code generated by `postgres-derive` and is subject to change at any time.
+#[doc(hidden)]
+pub fn __write_composite_field<T>(
+    v: &T,
+    type_: &Type,
+    buf: &mut Vec<u8>,
+) -> Result<(), Box<dyn Error + Sync + Send>>
+where
+    T: ToSql,
+{
+    buf.extend_from_slice(&type_.oid().to_be_bytes());
+    let base = buf.len();
+    buf.extend_from_slice(&[0; 4]);
+    let len = match v.to_sql(type_, buf)? {
+        IsNull::Yes => -1,
+        IsNull::No => downcast(buf.len() - base - 4)?,
+    };
+    buf[base..base + 4].copy_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+#[cfg(feature = "with-bigdecimal-0.4")]
+mod bigdecimal_04;
 #[cfg(feature = "with-bit-vec-0.5")]
 mod bit_vec_05;
 #[cfg(feature = "with-chrono-0.4")]
@@ -80,11 +162,17 @@ mod chrono_04;
 mod eui48_04;
 #[cfg(feature = "with-geo-0.10")]
 mod geo_010;
+#[cfg(feature = "with-rust_decimal-1")]
+mod rust_decimal_1;
 #[cfg(feature = "with-serde_json-1")]
 mod serde_json_1;
 #[cfg(feature = "with-uuid-0.7")]
 mod uuid_07;
 
+mod interval;
+mod ip_network;
+mod lsn;
+mod range;
 mod special;
 mod type_gen;
 
@@ -260,6 +348,9 @@ impl WrongType {
 /// | `&[u8]`/`Vec<u8>`                 | BYTEA                                         |
 /// | `HashMap<String, Option<String>>` | HSTORE                                        |
 /// | `SystemTime`                      | TIMESTAMP, TIMESTAMP WITH TIME ZONE           |
+/// | `IpAddr`                          | INET                                          |
+/// | `IpNetwork`                       | INET, CIDR                                    |
+/// | `Interval`                        | INTERVAL                                      |
 ///
 /// In addition, some implementations are provided for types in third party
 /// crates. These are disabled by default; to opt into one of these
@@ -282,6 +373,8 @@ impl WrongType {
 /// | `uuid::Uuid`                    | UUID                                |
 /// | `bit_vec::BitVec`               | BIT, VARBIT                         |
 /// | `eui48::MacAddress`             | MACADDR                             |
+/// | `rust_decimal::Decimal`         | NUMERIC                             |
+/// | `bigdecimal::BigDecimal`        | NUMERIC                             |
 ///
 /// # Nullability
 ///
@@ -293,6 +386,11 @@ impl WrongType {
 ///
 /// `FromSql` is implemented for `Vec<T>` where `T` implements `FromSql`, and
 /// corresponds to one-dimensional Postgres arrays.
+///
+/// # Ranges
+///
+/// `FromSql` is implemented for `Range<T>` where `T` implements `FromSql`, and
+/// corresponds to Postgres range types.
 pub trait FromSql<'a>: Sized {
     /// Creates a new value of this type from a buffer of data of the specified
     /// Postgres `Type` in its binary format.
@@ -480,6 +578,15 @@ impl<'a> FromSql<'a> for SystemTime {
     accepts!(TIMESTAMP, TIMESTAMPTZ);
 }
 
+impl<'a> FromSql<'a> for IpAddr {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<IpAddr, Box<dyn Error + Sync + Send>> {
+        let inet = types::inet_from_sql(raw)?;
+        Ok(inet.addr())
+    }
+
+    accepts!(INET);
+}
+
 /// An enum representing the nullability of a Postgres value.
 pub enum IsNull {
     /// The value is NULL.
@@ -509,6 +616,9 @@ pub enum IsNull {
 /// | `&[u8]`/Vec<u8>`                  | BYTEA                                |
 /// | `HashMap<String, Option<String>>` | HSTORE                               |
 /// | `SystemTime`                      | TIMESTAMP, TIMESTAMP WITH TIME ZONE           |
+/// | `IpAddr`                          | INET                                          |
+/// | `IpNetwork`                       | INET, CIDR                                    |
+/// | `Interval`                        | INTERVAL                                      |
 ///
 /// In addition, some implementations are provided for types in third party
 /// crates. These are disabled by default; to opt into one of these
@@ -530,6 +640,8 @@ pub enum IsNull {
 /// | `uuid::Uuid`                    | UUID                                |
 /// | `bit_vec::BitVec`               | BIT, VARBIT                         |
 /// | `eui48::MacAddress`             | MACADDR                             |
+/// | `rust_decimal::Decimal`         | NUMERIC                             |
+/// | `bigdecimal::BigDecimal`        | NUMERIC                             |
 ///
 /// # Nullability
 ///
@@ -541,6 +653,11 @@ pub enum IsNull {
 ///
 /// `ToSql` is implemented for `Vec<T>` and `&[T]` where `T` implements `ToSql`,
 /// and corresponds to one-dimentional Postgres arrays with an index offset of 1.
+///
+/// # Ranges
+///
+/// `ToSql` is implemented for `Range<T>` where `T` implements `ToSql`, and
+/// corresponds to Postgres range types.
 pub trait ToSql: fmt::Debug {
     /// Converts the value of `self` into the binary format of the specified
     /// Postgres `Type`, appending it to `out`.
@@ -780,6 +897,20 @@ impl ToSql for SystemTime {
     to_sql_checked!();
 }
 
+impl ToSql for IpAddr {
+    fn to_sql(&self, _: &Type, w: &mut Vec<u8>) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let netmask = match self {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        types::inet_to_sql(*self, netmask, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INET);
+    to_sql_checked!();
+}
+
 fn downcast(len: usize) -> Result<i32, Box<dyn Error + Sync + Send>> {
     if len > i32::max_value() as usize {
         Err("value too large to transmit".into())
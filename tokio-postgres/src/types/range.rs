@@ -0,0 +1,121 @@
+use postgres_protocol;
+use postgres_protocol::types;
+use std::error::Error;
+
+use crate::types::{FromSql, IsNull, Kind, ToSql, Type};
+
+/// A Postgres range value, corresponding to any type with a `Kind::Range` kind.
+///
+/// This includes the built-in `int4range`, `int8range`, `numrange`, `tsrange`, `tstzrange`, and
+/// `daterange` types as well as user-defined range types. `T` is the Rust type of the range's
+/// subtype.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Range<T> {
+    /// An empty range.
+    Empty,
+    /// A nonempty range.
+    Nonempty(RangeBound<T>, RangeBound<T>),
+}
+
+/// One side of a range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RangeBound<T> {
+    /// An inclusive bound.
+    Inclusive(T),
+    /// An exclusive bound.
+    Exclusive(T),
+    /// No bound.
+    Unbounded,
+}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for Range<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Range<T>, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Range(ref member) => member,
+            _ => panic!("expected range type"),
+        };
+
+        match types::range_from_sql(raw)? {
+            types::Range::Empty => Ok(Range::Empty),
+            types::Range::Nonempty(lower, upper) => {
+                let lower = bound_from_sql(member_type, lower)?;
+                let upper = bound_from_sql(member_type, upper)?;
+                Ok(Range::Nonempty(lower, upper))
+            }
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Range(ref member) => T::accepts(member),
+            _ => false,
+        }
+    }
+}
+
+fn bound_from_sql<'a, T>(
+    ty: &Type,
+    bound: types::RangeBound<Option<&'a [u8]>>,
+) -> Result<RangeBound<T>, Box<dyn Error + Sync + Send>>
+where
+    T: FromSql<'a>,
+{
+    match bound {
+        types::RangeBound::Inclusive(value) => {
+            T::from_sql_nullable(ty, value).map(RangeBound::Inclusive)
+        }
+        types::RangeBound::Exclusive(value) => {
+            T::from_sql_nullable(ty, value).map(RangeBound::Exclusive)
+        }
+        types::RangeBound::Unbounded => Ok(RangeBound::Unbounded),
+    }
+}
+
+impl<T: ToSql> ToSql for Range<T> {
+    fn to_sql(&self, ty: &Type, w: &mut Vec<u8>) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Range(ref member) => member,
+            _ => panic!("expected range type"),
+        };
+
+        match *self {
+            Range::Empty => types::empty_range_to_sql(w),
+            Range::Nonempty(ref lower, ref upper) => types::range_to_sql(
+                |w| bound_to_sql(member_type, lower, w),
+                |w| bound_to_sql(member_type, upper, w),
+                w,
+            )?,
+        }
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Range(ref member) => T::accepts(member),
+            _ => false,
+        }
+    }
+
+    to_sql_checked!();
+}
+
+fn bound_to_sql<T>(
+    ty: &Type,
+    bound: &RangeBound<T>,
+    w: &mut Vec<u8>,
+) -> Result<types::RangeBound<postgres_protocol::IsNull>, Box<dyn Error + Sync + Send>>
+where
+    T: ToSql,
+{
+    let to_sql = |value: &T, w: &mut Vec<u8>| match value.to_sql(ty, w)? {
+        IsNull::No => Ok(postgres_protocol::IsNull::No),
+        IsNull::Yes => Ok(postgres_protocol::IsNull::Yes),
+    };
+
+    match *bound {
+        RangeBound::Inclusive(ref value) => to_sql(value, w).map(types::RangeBound::Inclusive),
+        RangeBound::Exclusive(ref value) => to_sql(value, w).map(types::RangeBound::Exclusive),
+        RangeBound::Unbounded => Ok(types::RangeBound::Unbounded),
+    }
+}
@@ -0,0 +1,97 @@
+use postgres_protocol::types::{self, NumericSign};
+use rust_decimal_1::Decimal;
+use std::error::Error;
+
+use crate::types::{FromSql, IsNull, ToSql, Type};
+
+const NBASE: i128 = 10000;
+
+impl<'a> FromSql<'a> for Decimal {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Decimal, Box<dyn Error + Sync + Send>> {
+        let numeric = types::numeric_from_sql(raw)?;
+        let negative = match numeric.sign() {
+            NumericSign::Positive => false,
+            NumericSign::Negative => true,
+            _ => return Err("Decimal cannot represent non-finite values".into()),
+        };
+
+        let digits = numeric.digits();
+        // the base-10000 exponent of the least significant digit
+        let mut exponent = i32::from(numeric.weight()) + 1 - digits.len() as i32;
+
+        let mut mantissa = 0i128;
+        for digit in digits {
+            mantissa = mantissa
+                .checked_mul(NBASE)
+                .map(|m| m + i128::from(digit))
+                .ok_or("value too large for Decimal")?;
+        }
+        while exponent > 0 {
+            mantissa = mantissa
+                .checked_mul(NBASE)
+                .ok_or("value too large for Decimal")?;
+            exponent -= 1;
+        }
+
+        // digits past the value's scale are always zero, so this never loses precision
+        let mut scale = -exponent * 4;
+        let target_scale = i32::from(numeric.scale());
+        while scale > target_scale {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        while scale < target_scale {
+            mantissa = mantissa
+                .checked_mul(10)
+                .ok_or("value too large for Decimal")?;
+            scale += 1;
+        }
+
+        if negative {
+            mantissa = -mantissa;
+        }
+
+        let decimal = Decimal::try_from_i128_with_scale(mantissa, scale as u32)?;
+        Ok(decimal)
+    }
+
+    accepts!(NUMERIC);
+}
+
+impl ToSql for Decimal {
+    fn to_sql(&self, _: &Type, w: &mut Vec<u8>) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let sign = if self.is_sign_negative() && !self.is_zero() {
+            NumericSign::Negative
+        } else {
+            NumericSign::Positive
+        };
+
+        // shift the mantissa so that the decimal point falls on a base-10000 digit boundary
+        let scale = self.scale();
+        let padding = (4 - scale % 4) % 4;
+        let mut mantissa = self.mantissa().unsigned_abs() * 10u128.pow(padding);
+        let fractional_digits = ((scale + padding) / 4) as i16;
+
+        let mut digits = vec![];
+        while mantissa != 0 {
+            digits.push((mantissa % NBASE as u128) as i16);
+            mantissa /= NBASE as u128;
+        }
+
+        let weight = if digits.is_empty() {
+            0
+        } else {
+            digits.len() as i16 - fractional_digits - 1
+        };
+
+        let trailing_zeros = digits.iter().take_while(|&&d| d == 0).count();
+        digits.drain(..trailing_zeros);
+        digits.reverse();
+
+        types::numeric_to_sql(sign, weight, scale as u16, digits, w)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(NUMERIC);
+    to_sql_checked!();
+}
@@ -0,0 +1,57 @@
+//! GSSAPI authentication is configured through the `KRB5_CONFIG` and `KRB5CCNAME` environment
+//! variables, which are process-wide. This test lives in its own test binary so that setting them
+//! can't affect any other test.
+#![cfg(all(feature = "gssapi", feature = "runtime"))]
+#![warn(rust_2018_idioms)]
+
+use futures::Future;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+use tokio::runtime::current_thread::Runtime;
+use tokio_postgres::NoTls;
+
+// Uses the KDC in the test image, and requires `kinit` from the MIT Kerberos client tools.
+#[test]
+fn gss() {
+    let krb5_config = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../docker/krb5.conf");
+    let ccache = env::temp_dir().join(format!("tokio-postgres-krb5cc-{}", process::id()));
+    let _ = fs::remove_file(&ccache);
+    env::set_var("KRB5_CONFIG", &krb5_config);
+    env::set_var("KRB5CCNAME", format!("FILE:{}", ccache.display()));
+
+    let conn = "host=localhost port=5433 user=gss_user dbname=postgres krbsrvname=postgres";
+
+    let mut runtime = Runtime::new().unwrap();
+    let e = runtime
+        .block_on(tokio_postgres::connect(conn, NoTls))
+        .err()
+        .unwrap();
+    assert!(e.to_string().contains("GSSAPI"), "{}", e);
+
+    let mut kinit = process::Command::new("kinit")
+        .arg("gss_user@PG.TEST")
+        .stdin(process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    kinit
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"password\n")
+        .unwrap();
+    assert!(kinit.wait().unwrap().success());
+
+    let (mut client, connection) = runtime
+        .block_on(tokio_postgres::connect(conn, NoTls))
+        .unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let execute = client.batch_execute("SELECT 1");
+    runtime.block_on(execute).unwrap();
+
+    fs::remove_file(&ccache).unwrap();
+}
@@ -0,0 +1,123 @@
+use futures::{Future, Stream};
+use tokio::runtime::current_thread::Runtime;
+use tokio_postgres::{FromRow, Row};
+
+use crate::connect;
+
+fn query_row(query: &str) -> Row {
+    let mut runtime = Runtime::new().unwrap();
+
+    let handshake = connect("user=postgres");
+    let (mut client, connection) = runtime.block_on(handshake).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let prepare = client.prepare(query);
+    let stmt = runtime.block_on(prepare).unwrap();
+    let query = client.query(&stmt, &[]).collect();
+    let mut rows = runtime.block_on(query).unwrap();
+    rows.pop().unwrap()
+}
+
+#[test]
+fn named_fields() {
+    #[derive(Debug, PartialEq, FromRow)]
+    struct Address {
+        city: String,
+        #[postgres(name = "zip_code")]
+        zip: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, FromRow)]
+    struct User {
+        id: i32,
+        #[postgres(name = "username")]
+        name: String,
+        email: Option<String>,
+        #[postgres(default)]
+        nickname: Option<String>,
+        #[postgres(index = 0)]
+        first: i32,
+        #[postgres(flatten)]
+        address: Address,
+    }
+
+    let row = query_row(
+        "SELECT 1::INT4 AS id, 'steven'::TEXT AS username, NULL::TEXT AS email, \
+         'Oakland'::TEXT AS city, '94607'::TEXT AS zip_code",
+    );
+    let user = User::from_row(&row).unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 1,
+            name: "steven".to_string(),
+            email: None,
+            nickname: None,
+            first: 1,
+            address: Address {
+                city: "Oakland".to_string(),
+                zip: Some("94607".to_string()),
+            },
+        }
+    );
+}
+
+#[test]
+fn tuple_struct() {
+    #[derive(Debug, PartialEq, FromRow)]
+    struct Pair(i32, String);
+
+    let row = query_row("SELECT 1::INT4, 'hello'::TEXT");
+    assert_eq!(Pair::from_row(&row).unwrap(), Pair(1, "hello".to_string()));
+}
+
+#[test]
+fn missing_column() {
+    #[derive(Debug, FromRow)]
+    struct User {
+        #[allow(dead_code)]
+        id: i32,
+    }
+
+    let row = query_row("SELECT 1::INT4 AS user_id");
+    let err = User::from_row(&row).unwrap_err();
+    assert_eq!(err.to_string(), "invalid column `id`");
+}
+
+#[test]
+fn wrong_type() {
+    #[derive(Debug, FromRow)]
+    struct User {
+        #[allow(dead_code)]
+        #[postgres(index = 0)]
+        id: String,
+    }
+
+    let row = query_row("SELECT 1::INT4 AS user_id");
+    let err = User::from_row(&row).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("error deserializing column `user_id`: "),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn unexpected_null() {
+    #[derive(Debug, FromRow)]
+    struct User {
+        #[allow(dead_code)]
+        id: i32,
+    }
+
+    let row = query_row("SELECT NULL::INT4 AS id");
+    let err = User::from_row(&row).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("error deserializing column `id`: "),
+        "{}",
+        err
+    );
+}
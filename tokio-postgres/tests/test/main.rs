@@ -12,10 +12,15 @@ use tokio::runtime::current_thread::Runtime;
 use tokio::timer::Delay;
 use tokio_postgres::error::SqlState;
 use tokio_postgres::types::{Kind, Type};
-use tokio_postgres::{AsyncMessage, Client, Connection, NoTls};
+use tokio_postgres::{AsyncMessage, Client, Connection, IsolationLevel, NoTls, Overflow};
 
+#[cfg(feature = "derive")]
+mod from_row;
 mod parse;
 #[cfg(feature = "runtime")]
+mod pool;
+mod replication;
+#[cfg(feature = "runtime")]
 mod runtime;
 mod types;
 
@@ -131,6 +136,43 @@ fn scram_password_ok() {
     smoke_test("user=scram_user password=password dbname=postgres");
 }
 
+#[test]
+fn require_auth_ok() {
+    smoke_test("user=scram_user password=password dbname=postgres require_auth=scram-sha-256");
+    smoke_test("user=postgres require_auth=!password,!md5");
+}
+
+#[test]
+fn require_auth_downgrade() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let handshake = connect("user=pass_user password=password dbname=postgres require_auth=md5");
+    runtime.block_on(handshake).err().unwrap();
+
+    let handshake = connect("user=postgres require_auth=scram-sha-256");
+    runtime.block_on(handshake).err().unwrap();
+}
+
+#[test]
+fn channel_binding_required() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    // channel binding requires TLS
+    let handshake =
+        connect("user=scram_user password=password dbname=postgres channel_binding=require");
+    runtime.block_on(handshake).err().unwrap();
+
+    let handshake = connect("user=postgres channel_binding=require");
+    runtime.block_on(handshake).err().unwrap();
+}
+
+#[test]
+fn channel_binding_disable() {
+    smoke_test("user=scram_user password=password dbname=postgres channel_binding=disable");
+}
+
 #[test]
 fn pipelined_prepare() {
     let _ = env_logger::try_init();
@@ -458,6 +500,67 @@ fn notifications() {
     assert_eq!(notifications[1].payload(), "world");
 }
 
+#[test]
+fn split_notifications() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let (driver, messages) = connection.split(16, Overflow::Wait);
+    let driver = driver.map_err(|e| panic!("{}", e));
+    runtime.handle().spawn(driver).unwrap();
+
+    runtime
+        .block_on(client.batch_execute(
+            "LISTEN split_notifications;
+             NOTIFY split_notifications, 'hello';
+             NOTIFY split_notifications, 'world';",
+        ))
+        .unwrap();
+
+    drop(client);
+    runtime.run().unwrap();
+
+    let messages = runtime.block_on(messages.collect()).unwrap();
+    let payloads = messages
+        .iter()
+        .filter_map(|m| match m {
+            AsyncMessage::Notification(n) => Some(n.payload()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(payloads, ["hello", "world"]);
+}
+
+#[test]
+fn split_drop_oldest() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let (driver, messages) = connection.split(1, Overflow::DropOldest);
+    let driver = driver.map_err(|e| panic!("{}", e));
+    runtime.handle().spawn(driver).unwrap();
+
+    runtime
+        .block_on(client.batch_execute("LISTEN split_drop_oldest"))
+        .unwrap();
+    for payload in &["a", "b", "c"] {
+        let query = format!("NOTIFY split_drop_oldest, '{}'", payload);
+        runtime.block_on(client.batch_execute(&query)).unwrap();
+    }
+
+    drop(client);
+    runtime.run().unwrap();
+
+    let messages = runtime.block_on(messages.collect()).unwrap();
+    assert_eq!(messages.len(), 1);
+    match &messages[0] {
+        AsyncMessage::Notification(n) => assert_eq!(n.payload(), "c"),
+        _ => panic!("unexpected message"),
+    }
+}
+
 #[test]
 fn transaction_commit() {
     let _ = env_logger::try_init();
@@ -491,6 +594,38 @@ fn transaction_commit() {
     assert_eq!(rows[0].get::<_, &str>(0), "steven");
 }
 
+#[test]
+fn transaction_options() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.handle().spawn(connection).unwrap();
+
+    let stmt = runtime
+        .block_on(client.prepare(
+            "SELECT current_setting('transaction_isolation'),
+                current_setting('transaction_read_only'),
+                current_setting('transaction_deferrable')",
+        ))
+        .unwrap();
+
+    let f = client.query(&stmt, &[]).collect();
+    let transaction = client
+        .transaction()
+        .isolation_level(IsolationLevel::Serializable)
+        .read_only(true)
+        .deferrable(true)
+        .build(f);
+    let rows = runtime.block_on(transaction).unwrap();
+
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get::<_, &str>(0), "serializable");
+    assert_eq!(rows[0].get::<_, &str>(1), "on");
+    assert_eq!(rows[0].get::<_, &str>(2), "on");
+}
+
 #[test]
 fn transaction_abort() {
     let _ = env_logger::try_init();
@@ -526,6 +661,89 @@ fn transaction_abort() {
     assert_eq!(rows.len(), 0);
 }
 
+#[test]
+fn pipeline() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.handle().spawn(connection).unwrap();
+
+    runtime
+        .block_on(client.batch_execute("CREATE TEMPORARY TABLE foo (id SERIAL, name TEXT)"))
+        .unwrap();
+
+    let insert = client.prepare("INSERT INTO foo (name) VALUES ($1)");
+    let select = client.prepare("SELECT name FROM foo ORDER BY id");
+    let (insert, select) = runtime.block_on(insert.join(select)).unwrap();
+
+    let mut pipeline = client.pipeline();
+    pipeline
+        .add(&insert, &[&"alice"])
+        .add(&insert, &[&"bob"])
+        .add(&select, &[]);
+    assert_eq!(pipeline.len(), 3);
+    let results = runtime.block_on(pipeline.run().collect()).unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].rows_affected(), 1);
+    assert_eq!(results[1].rows_affected(), 1);
+    assert_eq!(results[2].rows_affected(), 2);
+    let rows = results[2].rows();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<_, &str>(0), "alice");
+    assert_eq!(rows[1].get::<_, &str>(0), "bob");
+
+    let results = runtime.block_on(client.pipeline().run().collect()).unwrap();
+    assert!(results.is_empty());
+}
+
+#[test]
+fn pipeline_error() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.handle().spawn(connection).unwrap();
+
+    runtime
+        .block_on(client.batch_execute("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY)"))
+        .unwrap();
+
+    let insert = runtime
+        .block_on(client.prepare("INSERT INTO foo (id) VALUES ($1)"))
+        .unwrap();
+
+    let mut pipeline = client.pipeline();
+    pipeline
+        .add(&insert, &[&1i32])
+        .add(&insert, &[&1i32])
+        .add(&insert, &[&2i32]);
+    let (result, results) = runtime
+        .block_on(pipeline.run().into_future())
+        .map_err(|e| e.0)
+        .unwrap();
+    assert_eq!(result.unwrap().rows_affected(), 1);
+    let error = runtime
+        .block_on(results.into_future())
+        .map_err(|e| e.0)
+        .err()
+        .unwrap();
+    assert_eq!(error.code(), Some(&SqlState::UNIQUE_VIOLATION));
+
+    // the pipeline ran in a single implicit transaction, so the first insert was rolled back as well
+    let rows = runtime
+        .block_on(
+            client
+                .prepare("SELECT id FROM foo")
+                .and_then(|s| client.query(&s, &[]).collect()),
+        )
+        .unwrap();
+    assert_eq!(rows.len(), 0);
+}
+
 #[test]
 fn copy_in() {
     let _ = env_logger::try_init();
@@ -777,3 +995,77 @@ fn poll_idle_new() {
     };
     runtime.block_on(future).unwrap();
 }
+
+#[test]
+fn prepare_cached() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.handle().spawn(connection).unwrap();
+
+    client.set_statement_cache_capacity(2);
+
+    runtime.block_on(client.prepare_cached("SELECT 1")).unwrap();
+    runtime.block_on(client.prepare_cached("SELECT 1")).unwrap();
+    runtime
+        .block_on(client.prepare_typed_cached("SELECT $1", &[Type::INT4]))
+        .unwrap();
+    runtime
+        .block_on(client.prepare_typed_cached("SELECT $1", &[Type::INT8]))
+        .unwrap();
+
+    let count_statements = client.prepare("SELECT count(*) FROM pg_prepared_statements");
+    let count_statements = runtime.block_on(count_statements).unwrap();
+    let rows = runtime
+        .block_on(client.query(&count_statements, &[]).collect())
+        .unwrap();
+    // the two most recently used cached statements and count_statements itself
+    assert_eq!(rows[0].get::<_, i64>(0), 3);
+
+    client.clear_statement_cache();
+    let rows = runtime
+        .block_on(client.query(&count_statements, &[]).collect())
+        .unwrap();
+    assert_eq!(rows[0].get::<_, i64>(0), 1);
+}
+
+#[test]
+fn prepare_cached_invalidated() {
+    let _ = env_logger::try_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.handle().spawn(connection).unwrap();
+
+    runtime
+        .block_on(client.batch_execute("CREATE TEMPORARY TABLE foo (id INT)"))
+        .unwrap();
+
+    let stmt = runtime
+        .block_on(client.prepare_cached("SELECT * FROM foo"))
+        .unwrap();
+    runtime
+        .block_on(client.query(&stmt, &[]).collect())
+        .unwrap();
+
+    runtime
+        .block_on(client.batch_execute("ALTER TABLE foo ADD COLUMN name TEXT"))
+        .unwrap();
+
+    let err = runtime
+        .block_on(client.query(&stmt, &[]).collect())
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), Some(&SqlState::FEATURE_NOT_SUPPORTED));
+
+    let stmt = runtime
+        .block_on(client.prepare_cached("SELECT * FROM foo"))
+        .unwrap();
+    assert_eq!(stmt.columns().len(), 2);
+    runtime
+        .block_on(client.query(&stmt, &[]).collect())
+        .unwrap();
+}
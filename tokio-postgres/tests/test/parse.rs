@@ -1,8 +1,11 @@
+use std::env;
+use std::fs;
+use std::process;
 #[cfg(feature = "runtime")]
 use std::time::Duration;
-use tokio_postgres::Config;
+use tokio_postgres::{AuthMethod, ChannelBindingMode, Config, ReplicationMode, SslMode};
 #[cfg(feature = "runtime")]
-use tokio_postgres::TargetSessionAttrs;
+use tokio_postgres::{LoadBalanceHosts, TargetSessionAttrs};
 
 fn check(s: &str, config: &Config) {
     assert_eq!(s.parse::<Config>().expect(s), *config, "`{}`", s);
@@ -27,17 +30,150 @@ fn pairs_ws() {
     );
 }
 
+#[test]
+fn replication() {
+    check(
+        "replication=database",
+        Config::new().replication_mode(ReplicationMode::Logical),
+    );
+    check(
+        "replication=true",
+        Config::new().replication_mode(ReplicationMode::Physical),
+    );
+    check("replication=off", &Config::new());
+    assert!("replication=foo".parse::<Config>().is_err());
+}
+
+#[test]
+fn ssl() {
+    check(
+        "sslmode=verify-full sslrootcert=root.crt sslcert=client.crt sslkey=client.key \
+         sslcrl=root.crl sslpassword=hunter2",
+        Config::new()
+            .ssl_mode(SslMode::VerifyFull)
+            .ssl_root_cert("root.crt")
+            .ssl_cert("client.crt")
+            .ssl_key("client.key")
+            .ssl_crl("root.crl")
+            .ssl_password("hunter2"),
+    );
+    check("sslmode=disable", Config::new().ssl_mode(SslMode::Disable));
+    check("sslmode=allow", Config::new().ssl_mode(SslMode::Allow));
+    check("sslmode=prefer", Config::new().ssl_mode(SslMode::Prefer));
+    check("sslmode=require", Config::new().ssl_mode(SslMode::Require));
+    check(
+        "sslmode=verify-ca",
+        Config::new().ssl_mode(SslMode::VerifyCa),
+    );
+    assert!("sslmode=foo".parse::<Config>().is_err());
+}
+
+#[test]
+fn auth() {
+    check(
+        "channel_binding=require require_auth=scram-sha-256,none",
+        Config::new()
+            .channel_binding(ChannelBindingMode::Require)
+            .require_auth(&[AuthMethod::ScramSha256, AuthMethod::None]),
+    );
+    check(
+        "channel_binding=disable require_auth=!password,!md5",
+        Config::new()
+            .channel_binding(ChannelBindingMode::Disable)
+            .require_auth(&[
+                AuthMethod::Gss,
+                AuthMethod::Sspi,
+                AuthMethod::ScramSha256,
+                AuthMethod::None,
+            ]),
+    );
+    check(
+        "channel_binding=prefer",
+        Config::new().channel_binding(ChannelBindingMode::Prefer),
+    );
+    assert_eq!(
+        Config::new().get_channel_binding(),
+        ChannelBindingMode::Prefer
+    );
+    assert!("channel_binding=foo".parse::<Config>().is_err());
+    assert!("require_auth=md5,!password".parse::<Config>().is_err());
+    assert!("require_auth=md5,md5".parse::<Config>().is_err());
+    assert!("require_auth=foo".parse::<Config>().is_err());
+}
+
+#[test]
+fn gss() {
+    check(
+        "krbsrvname=pgsql gsslib=gssapi",
+        Config::new().krb_srvname("pgsql"),
+    );
+    assert!("gsslib=sspi".parse::<Config>().is_err());
+}
+
 #[test]
 #[cfg(feature = "runtime")]
 fn settings() {
     check(
-        "connect_timeout=3 keepalives=0 keepalives_idle=30 target_session_attrs=read-write",
+        "connect_timeout=3 keepalives=0 keepalives_idle=30 target_session_attrs=read-write \
+         passfile=/home/postgres/.pgpass",
         Config::new()
             .connect_timeout(Duration::from_secs(3))
             .keepalives(false)
             .keepalives_idle(Duration::from_secs(30))
-            .target_session_attrs(TargetSessionAttrs::ReadWrite),
+            .target_session_attrs(TargetSessionAttrs::ReadWrite)
+            .passfile("/home/postgres/.pgpass"),
+    );
+    check(
+        "keepalives_interval=5 keepalives_retries=3 tcp_user_timeout=10000",
+        Config::new()
+            .keepalives_interval(Duration::from_secs(5))
+            .keepalives_retries(3)
+            .tcp_user_timeout(Duration::from_millis(10000)),
+    );
+    check("keepalives_interval=0 tcp_user_timeout=0", &Config::new());
+    check(
+        "query_timeout=30",
+        Config::new().query_timeout(Duration::from_secs(30)),
+    );
+    check("query_timeout=0", &Config::new());
+    assert!("keepalives_retries=many".parse::<Config>().is_err());
+    check(
+        "target_session_attrs=read-only",
+        Config::new().target_session_attrs(TargetSessionAttrs::ReadOnly),
+    );
+    check(
+        "target_session_attrs=primary",
+        Config::new().target_session_attrs(TargetSessionAttrs::Primary),
+    );
+    check(
+        "target_session_attrs=standby",
+        Config::new().target_session_attrs(TargetSessionAttrs::Standby),
+    );
+    check(
+        "target_session_attrs=prefer-standby",
+        Config::new().target_session_attrs(TargetSessionAttrs::PreferStandby),
     );
+    assert!("target_session_attrs=replica".parse::<Config>().is_err());
+}
+
+#[test]
+#[cfg(feature = "runtime")]
+fn load_balancing() {
+    check(
+        "host=host1,host2 hostaddr=10.0.0.1,::1 load_balance_hosts=random",
+        Config::new()
+            .host("host1")
+            .host("host2")
+            .hostaddr("10.0.0.1".parse().unwrap())
+            .hostaddr("::1".parse().unwrap())
+            .load_balance_hosts(LoadBalanceHosts::Random),
+    );
+    check(
+        "load_balance_hosts=disable",
+        Config::new().load_balance_hosts(LoadBalanceHosts::Disable),
+    );
+    assert!("hostaddr=host1".parse::<Config>().is_err());
+    assert!("load_balance_hosts=round-robin".parse::<Config>().is_err());
 }
 
 #[test]
@@ -123,3 +259,171 @@ fn url() {
             .dbname("dbname"),
     )
 }
+
+// the service test is the only other one which touches PG* environment variables, and it uses different ones
+#[test]
+#[cfg(feature = "runtime")]
+fn env() {
+    let vars = [
+        ("PGHOST", "localhost,/var/run/postgresql"),
+        ("PGPORT", "5433"),
+        ("PGUSER", "postgres"),
+        ("PGDATABASE", "db"),
+        ("PGPASSWORD", "password"),
+        ("PGAPPNAME", "app"),
+        ("PGCONNECT_TIMEOUT", "3"),
+        ("PGOPTIONS", "-c statement_timeout=5s"),
+        ("PGSSLMODE", "require"),
+        ("PGTARGETSESSIONATTRS", "read-write"),
+        ("PGLOADBALANCEHOSTS", "random"),
+    ];
+    for (var, value) in &vars {
+        env::set_var(var, value);
+    }
+
+    let config = Config::from_env();
+    let mut merged = "host=otherhost user=ssl_user sslmode=disable"
+        .parse::<Config>()
+        .unwrap();
+    let merged = merged.merge_env().map(|c| c.clone());
+    let mut explicit = "target_session_attrs=any load_balance_hosts=disable"
+        .parse::<Config>()
+        .unwrap();
+    let explicit = explicit.merge_env().map(|c| c.clone());
+    env::set_var("PGPORT", "foo");
+    let invalid = Config::from_env();
+
+    for (var, _) in &vars {
+        env::remove_var(var);
+    }
+
+    assert_eq!(
+        config.unwrap(),
+        *Config::new()
+            .host("localhost")
+            .host("/var/run/postgresql")
+            .port(5433)
+            .user("postgres")
+            .dbname("db")
+            .password("password")
+            .application_name("app")
+            .connect_timeout(Duration::from_secs(3))
+            .options("-c statement_timeout=5s")
+            .ssl_mode(SslMode::Require)
+            .target_session_attrs(TargetSessionAttrs::ReadWrite)
+            .load_balance_hosts(LoadBalanceHosts::Random),
+    );
+    assert_eq!(
+        merged.unwrap(),
+        *Config::new()
+            .host("otherhost")
+            .port(5433)
+            .user("ssl_user")
+            .dbname("db")
+            .password("password")
+            .application_name("app")
+            .connect_timeout(Duration::from_secs(3))
+            .options("-c statement_timeout=5s")
+            .ssl_mode(SslMode::Disable)
+            .target_session_attrs(TargetSessionAttrs::ReadWrite)
+            .load_balance_hosts(LoadBalanceHosts::Random),
+    );
+    // explicitly set defaults aren't overridden
+    assert_eq!(
+        explicit.unwrap(),
+        *Config::new()
+            .host("localhost")
+            .host("/var/run/postgresql")
+            .port(5433)
+            .user("postgres")
+            .dbname("db")
+            .password("password")
+            .application_name("app")
+            .connect_timeout(Duration::from_secs(3))
+            .options("-c statement_timeout=5s")
+            .ssl_mode(SslMode::Require)
+            .target_session_attrs(TargetSessionAttrs::Any)
+            .load_balance_hosts(LoadBalanceHosts::Disable),
+    );
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn service() {
+    let dir = env::temp_dir().join(format!("service-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let user_file = dir.join("user_service.conf");
+    fs::write(
+        &user_file,
+        "# user services\n\
+         [user_service]\n\
+         user = postgres\n\
+         dbname=userdb\n\
+         \n\
+         [bad_service]\n\
+         dbname\n\
+         [nested_service]\n\
+         service=user_service\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("pg_service.conf"),
+        "[user_service]\n\
+         application_name=ignored\n\
+         [system_service]\n\
+         user=ssl_user\n\
+         application_name=system\n\
+         [keepalive_service]\n\
+         keepalives=0\n\
+         keepalives_idle=30\n",
+    )
+    .unwrap();
+    env::set_var("PGSERVICEFILE", &user_file);
+    env::set_var("PGSYSCONFDIR", &dir);
+
+    check(
+        "service=user_service dbname=db",
+        Config::new()
+            .user("postgres")
+            .dbname("db")
+            .service("user_service")
+            .unwrap(),
+    );
+    check(
+        "service=system_service",
+        Config::new()
+            .user("ssl_user")
+            .application_name("system")
+            .service("system_service")
+            .unwrap(),
+    );
+    check(
+        "postgresql:///db?service=system_service&application_name=url",
+        Config::new()
+            .user("ssl_user")
+            .dbname("db")
+            .application_name("url")
+            .service("system_service")
+            .unwrap(),
+    );
+    #[cfg(feature = "runtime")]
+    check(
+        "service=keepalive_service keepalives=1 keepalives_idle=7200",
+        Config::new()
+            .keepalives(true)
+            .keepalives_idle(Duration::from_secs(7200))
+            .service("keepalive_service")
+            .unwrap(),
+    );
+    let missing = "service=missing_service".parse::<Config>();
+    let bad = "service=bad_service".parse::<Config>();
+    let nested = "service=nested_service".parse::<Config>();
+
+    env::remove_var("PGSERVICEFILE");
+    env::remove_var("PGSYSCONFDIR");
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(missing.is_err());
+    assert!(bad.is_err());
+    assert!(nested.is_err());
+}
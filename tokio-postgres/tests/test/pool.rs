@@ -0,0 +1,101 @@
+use futures::Stream;
+use std::time::Duration;
+use tokio::runtime::current_thread::Runtime;
+use tokio_postgres::pool::{Builder, Pool};
+use tokio_postgres::NoTls;
+
+fn config() -> tokio_postgres::Config {
+    "host=localhost port=5433 user=postgres".parse().unwrap()
+}
+
+#[test]
+fn reuse() {
+    let mut runtime = Runtime::new().unwrap();
+    let pool = Pool::new(config(), NoTls);
+
+    let mut client = runtime.block_on(pool.get()).unwrap();
+    runtime
+        .block_on(client.batch_execute("SET application_name = 'pooled'"))
+        .unwrap();
+    assert_eq!(pool.state().connections(), 1);
+    assert_eq!(pool.state().idle_connections(), 0);
+    drop(client);
+    assert_eq!(pool.state().idle_connections(), 1);
+
+    let mut client = runtime.block_on(pool.get()).unwrap();
+    let stmt = runtime
+        .block_on(client.prepare("SELECT current_setting('application_name')"))
+        .unwrap();
+    let rows = runtime
+        .block_on(client.query(&stmt, &[]).collect())
+        .unwrap();
+    assert_eq!(rows[0].get::<_, &str>(0), "pooled");
+    assert_eq!(pool.state().connections(), 1);
+}
+
+#[test]
+fn max_size() {
+    let mut runtime = Runtime::new().unwrap();
+    let pool = Builder::new()
+        .max_size(1)
+        .connection_timeout(Duration::from_millis(100))
+        .build(config(), NoTls);
+
+    let client = runtime.block_on(pool.get()).unwrap();
+    let err = runtime.block_on(pool.get()).err().unwrap();
+    assert_eq!(err.to_string(), "timed out");
+
+    let checkout = pool.get();
+    drop(client);
+    runtime.block_on(checkout).unwrap();
+    assert_eq!(pool.state().connections(), 1);
+}
+
+#[test]
+fn broken_connection() {
+    let mut runtime = Runtime::new().unwrap();
+    let pool = Builder::new()
+        .max_size(2)
+        .test_query("SELECT 1")
+        .build(config(), NoTls);
+
+    let mut client = runtime.block_on(pool.get()).unwrap();
+    let mut other = runtime.block_on(pool.get()).unwrap();
+    let stmt = runtime
+        .block_on(client.prepare("SELECT pg_backend_pid()"))
+        .unwrap();
+    let rows = runtime
+        .block_on(client.query(&stmt, &[]).collect())
+        .unwrap();
+    let pid = rows[0].get::<_, i32>(0);
+    drop(client);
+
+    let terminate = format!("SELECT pg_terminate_backend({})", pid);
+    runtime.block_on(other.batch_execute(&terminate)).unwrap();
+    drop(other);
+
+    let mut client = runtime.block_on(pool.get()).unwrap();
+    runtime.block_on(client.batch_execute("SELECT 1")).unwrap();
+    let mut client2 = runtime.block_on(pool.get()).unwrap();
+    runtime.block_on(client2.batch_execute("SELECT 1")).unwrap();
+    assert_eq!(pool.state().connections(), 2);
+}
+
+#[test]
+fn idle_timeout() {
+    let mut runtime = Runtime::new().unwrap();
+    let pool = Builder::new()
+        .idle_timeout(Some(Duration::from_millis(100)))
+        .build(config(), NoTls);
+
+    let client = runtime.block_on(pool.get()).unwrap();
+    drop(client);
+    assert_eq!(pool.state().idle_connections(), 1);
+
+    runtime
+        .block_on(tokio::timer::Delay::new(
+            std::time::Instant::now() + Duration::from_millis(500),
+        ))
+        .unwrap();
+    assert_eq!(pool.state().connections(), 0);
+}
@@ -0,0 +1,273 @@
+use futures::{future, Future, Sink, Stream};
+use tokio::runtime::current_thread::Runtime;
+use tokio_postgres::replication::{
+    BaseBackupMessage, BaseBackupOptions, LogicalReplicationMessage, Lsn, ReplicationMessage,
+    StandbyStatusUpdate, TupleData,
+};
+
+use crate::connect;
+
+#[test]
+fn identify_system() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime
+        .block_on(connect("user=postgres replication=database"))
+        .unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let rows = runtime
+        .block_on(client.simple_query("IDENTIFY_SYSTEM").collect())
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get(3), Some("postgres"));
+
+    let response = runtime.block_on(client.identify_system()).unwrap();
+    assert_eq!(response.systemid(), rows[0].get(0).unwrap());
+    assert!(response.timeline() >= 1);
+    assert!(response.xlogpos() > Lsn::new(0));
+    assert_eq!(response.dbname(), Some("postgres"));
+}
+
+#[test]
+fn physical_replication() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let (mut repl_client, connection) = runtime
+        .block_on(connect("user=postgres replication=true"))
+        .unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let system = runtime.block_on(repl_client.identify_system()).unwrap();
+    assert_eq!(system.dbname(), None);
+
+    let mut stream = repl_client.start_physical_replication(None, system.xlogpos(), None);
+
+    runtime
+        .block_on(client.batch_execute("SELECT pg_logical_emit_message(false, 'test', 'hello')"))
+        .unwrap();
+
+    let end = loop {
+        let (message, s) = runtime
+            .block_on(stream.into_future())
+            .map_err(|e| e.0)
+            .unwrap();
+        stream = s;
+        if let ReplicationMessage::XLogData(body) = message.unwrap() {
+            assert_eq!(body.wal_start(), system.xlogpos().as_u64());
+            assert!(!body.data().is_empty());
+            break Lsn::new(body.wal_end());
+        }
+    };
+
+    let update = StandbyStatusUpdate::new(end, end, end, false);
+    let mut stream = runtime.block_on(stream.send(update)).unwrap();
+    runtime
+        .block_on(future::poll_fn(|| stream.close()))
+        .unwrap();
+    runtime.block_on(stream.for_each(|_| Ok(()))).unwrap();
+}
+
+#[test]
+fn timeline_history() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime
+        .block_on(connect("user=postgres replication=true"))
+        .unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    // a cluster which has never been promoted has no history for its first timeline
+    runtime.block_on(client.timeline_history(1)).err().unwrap();
+}
+
+#[test]
+fn base_backup() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime
+        .block_on(connect("user=postgres replication=true"))
+        .unwrap();
+    // manifests were added in Postgres 13
+    let manifest_supported = connection
+        .parameter("server_version")
+        .and_then(|v| v.split('.').next())
+        .and_then(|v| v.parse::<u32>().ok())
+        .map(|v| v >= 13)
+        .unwrap_or(false);
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let messages = client.base_backup(
+        BaseBackupOptions::new()
+            .label("base_backup test")
+            .progress(true)
+            .fast_checkpoint(true)
+            .manifest(manifest_supported),
+    );
+    let messages = runtime.block_on(messages.collect()).unwrap();
+
+    let start = match &messages[0] {
+        BaseBackupMessage::Start(position) => *position,
+        _ => panic!("expected start"),
+    };
+    let end = match messages.last().unwrap() {
+        BaseBackupMessage::End(position) => *position,
+        _ => panic!("expected end"),
+    };
+    assert!(end.lsn() >= start.lsn());
+
+    let mut tablespaces = 0;
+    let mut archives = vec![];
+    let mut manifest = None;
+    for message in &messages {
+        match message {
+            BaseBackupMessage::Tablespace(tablespace) => {
+                tablespaces += 1;
+                assert!(tablespace.size().is_some());
+            }
+            BaseBackupMessage::NewArchive(body) => {
+                archives.push((body.name().unwrap().to_string(), vec![]));
+            }
+            BaseBackupMessage::Manifest => manifest = Some(vec![]),
+            BaseBackupMessage::Data(data) => match &mut manifest {
+                Some(manifest) => manifest.extend_from_slice(data),
+                None => archives.last_mut().unwrap().1.extend_from_slice(data),
+            },
+            _ => {}
+        }
+    }
+
+    assert!(tablespaces >= 1);
+    assert_eq!(archives[0].0, "base.tar");
+    // the first entry in the archive is the backup_label file
+    assert_eq!(&archives[0].1[..12], b"backup_label");
+    if manifest_supported {
+        let manifest = String::from_utf8(manifest.unwrap()).unwrap();
+        assert!(manifest.contains("\"PostgreSQL-Backup-Manifest-Version\""));
+    }
+}
+
+#[test]
+fn logical_replication() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut client, connection) = runtime.block_on(connect("user=postgres")).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    runtime
+        .block_on(client.batch_execute(
+            "DROP PUBLICATION IF EXISTS logical_replication_pub;
+             DROP TABLE IF EXISTS logical_replication_test;
+             CREATE TABLE logical_replication_test (id INT PRIMARY KEY, name TEXT);
+             CREATE PUBLICATION logical_replication_pub FOR TABLE logical_replication_test;",
+        ))
+        .unwrap();
+
+    let (mut repl_client, connection) = runtime
+        .block_on(connect("user=postgres replication=database"))
+        .unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    runtime
+        .block_on(
+            repl_client
+                .simple_query(
+                    "CREATE_REPLICATION_SLOT logical_replication_slot TEMPORARY LOGICAL pgoutput",
+                )
+                .collect(),
+        )
+        .unwrap();
+
+    let mut stream = repl_client.start_logical_replication(
+        "logical_replication_slot",
+        Lsn::new(0),
+        &[
+            ("proto_version", "1"),
+            ("publication_names", "logical_replication_pub"),
+        ],
+    );
+
+    runtime
+        .block_on(
+            client.batch_execute(
+                "INSERT INTO logical_replication_test (id, name) VALUES (1, 'steven')",
+            ),
+        )
+        .unwrap();
+
+    let mut messages = vec![];
+    loop {
+        let (message, s) = runtime
+            .block_on(stream.into_future())
+            .map_err(|e| e.0)
+            .unwrap();
+        stream = s;
+        let body = match message.unwrap() {
+            ReplicationMessage::XLogData(body) => body,
+            _ => continue,
+        };
+        let done = match body.data() {
+            LogicalReplicationMessage::Commit(_) => true,
+            _ => false,
+        };
+        messages.push(body.into_data());
+        if done {
+            break;
+        }
+    }
+
+    assert_eq!(messages.len(), 4);
+    match &messages[0] {
+        LogicalReplicationMessage::Begin(_) => {}
+        _ => panic!("expected begin"),
+    }
+    let rel_id = match &messages[1] {
+        LogicalReplicationMessage::Relation(body) => {
+            assert_eq!(body.name().unwrap(), "logical_replication_test");
+            assert_eq!(body.columns().len(), 2);
+            body.rel_id()
+        }
+        _ => panic!("expected relation"),
+    };
+    match &messages[2] {
+        LogicalReplicationMessage::Insert(body) => {
+            assert_eq!(body.rel_id(), rel_id);
+            match body.tuple().tuple_data() {
+                [TupleData::Text(id), TupleData::Text(name)] => {
+                    assert_eq!(&id[..], b"1");
+                    assert_eq!(&name[..], b"steven");
+                }
+                _ => panic!("unexpected tuple data"),
+            }
+        }
+        _ => panic!("expected insert"),
+    }
+    let end_lsn = match &messages[3] {
+        LogicalReplicationMessage::Commit(body) => Lsn::new(body.end_lsn()),
+        _ => panic!("expected commit"),
+    };
+
+    let update = StandbyStatusUpdate::new(end_lsn, end_lsn, end_lsn, true);
+    let mut stream = runtime.block_on(stream.send(update)).unwrap();
+    loop {
+        let (message, s) = runtime
+            .block_on(stream.into_future())
+            .map_err(|e| e.0)
+            .unwrap();
+        stream = s;
+        if let ReplicationMessage::PrimaryKeepAlive(body) = message.unwrap() {
+            assert!(Lsn::new(body.wal_end()) >= end_lsn);
+            break;
+        }
+    }
+}
@@ -1,8 +1,14 @@
-use futures::Future;
+use futures::{Future, Stream};
+use std::env;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process;
 use std::time::{Duration, Instant};
 use tokio::runtime::current_thread::Runtime;
 use tokio::timer::Delay;
-use tokio_postgres::{NoTls, SqlState};
+use tokio_postgres::{Error, NoTls, SqlState};
 
 fn smoke_test(s: &str) {
     let mut runtime = Runtime::new().unwrap();
@@ -49,6 +55,60 @@ fn wrong_port_count() {
     runtime.block_on(f).err().unwrap();
 }
 
+#[test]
+fn hostaddr() {
+    smoke_test("host=localhost hostaddr=127.0.0.1 port=5433 user=postgres");
+    smoke_test("hostaddr=127.0.0.1 port=5433 user=postgres");
+    // the hostname isn't resolved when an address is given
+    smoke_test("host=foobar.invalid hostaddr=127.0.0.1 port=5433 user=postgres");
+}
+
+#[test]
+fn wrong_hostaddr_count() {
+    let mut runtime = Runtime::new().unwrap();
+    let f = tokio_postgres::connect(
+        "host=localhost,localhost hostaddr=127.0.0.1 port=5433 user=postgres",
+        NoTls,
+    );
+    runtime.block_on(f).err().unwrap();
+}
+
+#[test]
+fn load_balance_hosts() {
+    for _ in 0..5 {
+        smoke_test(
+            "host=foobar.invalid,localhost,127.0.0.1 port=5433 user=postgres load_balance_hosts=random",
+        );
+    }
+}
+
+#[test]
+fn tcp_options() {
+    smoke_test(
+        "host=localhost port=5433 user=postgres keepalives_idle=30 keepalives_interval=5 \
+         keepalives_retries=3 tcp_user_timeout=10000",
+    );
+}
+
+#[test]
+fn no_tls_ssl_mode() {
+    smoke_test("host=localhost port=5433 user=postgres sslmode=disable");
+    smoke_test("host=localhost port=5433 user=postgres sslmode=allow");
+    smoke_test("host=localhost port=5433 user=postgres sslmode=prefer");
+
+    let mut runtime = Runtime::new().unwrap();
+    for ssl_mode in &["require", "verify-ca", "verify-full"] {
+        let f = tokio_postgres::connect(
+            &format!(
+                "host=localhost port=5433 user=postgres sslmode={}",
+                ssl_mode
+            ),
+            NoTls,
+        );
+        runtime.block_on(f).err().unwrap();
+    }
+}
+
 #[test]
 fn target_session_attrs_ok() {
     let mut runtime = Runtime::new().unwrap();
@@ -70,6 +130,50 @@ fn target_session_attrs_err() {
     runtime.block_on(f).err().unwrap();
 }
 
+#[test]
+fn target_session_attrs_read_only() {
+    let mut runtime = Runtime::new().unwrap();
+    let f = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres target_session_attrs=read-only",
+        NoTls,
+    );
+    runtime.block_on(f).err().unwrap();
+
+    let f = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres target_session_attrs=read-only
+         options='-c default_transaction_read_only=on'",
+        NoTls,
+    );
+    let _ = runtime.block_on(f).unwrap();
+}
+
+#[test]
+fn target_session_attrs_standby() {
+    let mut runtime = Runtime::new().unwrap();
+    let f = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres target_session_attrs=primary",
+        NoTls,
+    );
+    let _ = runtime.block_on(f).unwrap();
+
+    let f = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres target_session_attrs=standby",
+        NoTls,
+    );
+    runtime.block_on(f).err().unwrap();
+}
+
+#[test]
+fn target_session_attrs_prefer_standby() {
+    let mut runtime = Runtime::new().unwrap();
+    // neither server is a standby, so the second pass accepts the first one
+    let f = tokio_postgres::connect(
+        "host=localhost,127.0.0.1 port=5433 user=postgres target_session_attrs=prefer-standby",
+        NoTls,
+    );
+    let _ = runtime.block_on(f).unwrap();
+}
+
 #[test]
 fn cancel_query() {
     let mut runtime = Runtime::new().unwrap();
@@ -98,3 +202,156 @@ fn cancel_query() {
 
     let ((), ()) = runtime.block_on(sleep.join(cancel)).unwrap();
 }
+
+#[test]
+fn query_timeout() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let connect = tokio_postgres::connect("host=localhost port=5433 user=postgres", NoTls);
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let statement = runtime
+        .block_on(client.prepare("SELECT pg_sleep($1)"))
+        .unwrap();
+
+    let execute = client
+        .execute(&statement, &[&100f64])
+        .timeout(Duration::from_millis(100));
+    let err = runtime.block_on(execute).err().unwrap();
+    assert!(err.is_timeout());
+
+    // the server has abandoned the query, so the connection is usable again
+    let query = client
+        .query(&statement, &[&0f64])
+        .timeout(Duration::from_secs(10))
+        .collect();
+    let rows = runtime.block_on(query).unwrap();
+    assert_eq!(rows.len(), 1);
+}
+
+#[test]
+fn query_timeout_concurrent() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let connect = tokio_postgres::connect("host=localhost port=5433 user=postgres", NoTls);
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let statement = runtime
+        .block_on(client.prepare("SELECT pg_sleep($1)"))
+        .unwrap();
+
+    // the second query's limit only starts once the first has finished, so it doesn't cancel the first
+    let first = client.execute(&statement, &[&1f64]);
+    let second = client
+        .execute(&statement, &[&100f64])
+        .timeout(Duration::from_millis(500))
+        .then(Ok::<_, Error>);
+    let (rows, second) = runtime.block_on(first.join(second)).unwrap();
+    assert_eq!(rows, 1);
+    assert!(second.err().unwrap().is_timeout());
+}
+
+#[test]
+fn config_query_timeout() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let connect = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres query_timeout=1",
+        NoTls,
+    );
+    let (mut client, connection) = runtime.block_on(connect).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let statement = runtime
+        .block_on(client.prepare("SELECT pg_sleep(100)"))
+        .unwrap();
+    let query = client.query(&statement, &[]).collect();
+    let err = runtime.block_on(query).err().unwrap();
+    assert!(err.is_timeout());
+
+    let statement = runtime.block_on(client.prepare("SELECT 1")).unwrap();
+    runtime.block_on(client.execute(&statement, &[])).unwrap();
+}
+
+fn write_passfile(name: &str, contents: &str, mode: u32) -> PathBuf {
+    let path = env::temp_dir().join(format!("{}-{}", name, process::id()));
+    fs::write(&path, contents).unwrap();
+    #[cfg(unix)]
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    path
+}
+
+#[test]
+fn passfile() {
+    let path = write_passfile(
+        "passfile",
+        "# comment\n\
+         otherhost:*:*:pass_user:wrong\n\
+         localhost:5432:*:pass_user:wrong\n\
+         localhost\\:5433:*:*:wrong\n\
+         *:5433:postgres:pass_user:password\n",
+        0o600,
+    );
+
+    let mut runtime = Runtime::new().unwrap();
+    let mut config = "host=localhost port=5433 user=pass_user dbname=postgres"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    config.passfile(&path);
+    let connect = config.connect(NoTls);
+    let result = runtime.block_on(connect);
+    fs::remove_file(&path).unwrap();
+    let _ = result.unwrap();
+}
+
+#[test]
+fn passfile_escapes() {
+    let path = write_passfile(
+        "passfile_escapes",
+        "localhost:5433:postgres:pass_user:pass\\word\n",
+        0o600,
+    );
+
+    let mut runtime = Runtime::new().unwrap();
+    let mut config = "host=localhost port=5433 user=pass_user dbname=postgres"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    config.passfile(&path);
+    let connect = config.connect(NoTls);
+    let result = runtime.block_on(connect);
+    fs::remove_file(&path).unwrap();
+    let _ = result.unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn passfile_insecure_permissions() {
+    let path = write_passfile("passfile_insecure", "*:*:*:*:password\n", 0o644);
+
+    let mut runtime = Runtime::new().unwrap();
+    let mut config = "host=localhost port=5433 user=pass_user dbname=postgres"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    config.passfile(&path);
+    let connect = config.connect(NoTls);
+    let result = runtime.block_on(connect);
+    fs::remove_file(&path).unwrap();
+    result.err().unwrap();
+}
+
+#[test]
+#[cfg(not(feature = "gssapi"))]
+fn gss_without_feature() {
+    let mut runtime = Runtime::new().unwrap();
+    let f = tokio_postgres::connect(
+        "host=localhost port=5433 user=gss_user dbname=postgres krbsrvname=postgres",
+        NoTls,
+    );
+    let e = runtime.block_on(f).err().unwrap();
+    assert!(e.to_string().contains("gssapi"), "{}", e);
+}
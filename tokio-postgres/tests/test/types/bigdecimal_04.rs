@@ -0,0 +1,43 @@
+use bigdecimal_04::num_bigint::BigInt;
+use bigdecimal_04::BigDecimal;
+use tokio_postgres::types::{ToSql, Type};
+
+use crate::types::test_type;
+
+#[test]
+fn test_bigdecimal_params() {
+    test_type(
+        "NUMERIC",
+        &[
+            (Some("0".parse::<BigDecimal>().unwrap()), "0"),
+            (Some("1.00".parse::<BigDecimal>().unwrap()), "1.00"),
+            (
+                Some("-12345.6789".parse::<BigDecimal>().unwrap()),
+                "-12345.6789",
+            ),
+            (Some("0.000001".parse::<BigDecimal>().unwrap()), "0.000001"),
+            (
+                Some(
+                    "123456789012345678901234567890.0987654321"
+                        .parse::<BigDecimal>()
+                        .unwrap(),
+                ),
+                "123456789012345678901234567890.0987654321",
+            ),
+            (Some("1e10".parse::<BigDecimal>().unwrap()), "10000000000"),
+            (None, "NULL"),
+        ],
+    )
+}
+
+#[test]
+fn test_bigdecimal_too_large() {
+    for &exponent in &[-1_000_000_000_000, i64::MIN] {
+        let value = BigDecimal::new(BigInt::from(1), exponent);
+        let err = value.to_sql(&Type::NUMERIC, &mut vec![]).err().unwrap();
+        assert_eq!(err.to_string(), "value too large for NUMERIC");
+    }
+
+    let zero = BigDecimal::new(BigInt::from(0), i64::MIN);
+    zero.to_sql(&Type::NUMERIC, &mut vec![]).unwrap();
+}
@@ -1,5 +1,6 @@
-use chrono_04::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
-use tokio_postgres::types::{Date, Timestamp};
+use chrono_04::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::convert::TryFrom;
+use tokio_postgres::types::{Date, Interval, Range, RangeBound, Timestamp};
 
 use crate::types::test_type;
 
@@ -146,3 +147,38 @@ fn test_time_params() {
         ],
     );
 }
+
+#[test]
+fn interval_duration() {
+    let interval = Interval::new(0, -1, 1_500_000);
+    assert_eq!(
+        Duration::try_from(interval).unwrap(),
+        Duration::days(-1) + Duration::microseconds(1_500_000)
+    );
+    assert!(Duration::try_from(Interval::new(1, 0, 0)).is_err());
+
+    assert_eq!(
+        Interval::try_from(Duration::milliseconds(-1_500)).unwrap(),
+        Interval::new(0, 0, -1_500_000)
+    );
+    assert!(Interval::try_from(Duration::nanoseconds(1)).is_err());
+}
+
+#[test]
+fn test_date_range_params() {
+    let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    test_type(
+        "DATERANGE",
+        &[
+            (
+                Some(Range::Nonempty(
+                    RangeBound::Inclusive(date("2010-02-09")),
+                    RangeBound::Exclusive(date("2010-03-01")),
+                )),
+                "'[2010-02-09,2010-03-01)'",
+            ),
+            (Some(Range::Empty), "'empty'"),
+            (None, "NULL"),
+        ],
+    );
+}
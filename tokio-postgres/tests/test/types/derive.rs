@@ -0,0 +1,186 @@
+use futures::{Future, Stream};
+use tokio::runtime::current_thread::Runtime;
+use tokio_postgres::types::{FromSql, ToSql, Type};
+
+use crate::connect;
+
+#[test]
+fn enum_() {
+    #[derive(Debug, PartialEq, ToSql, FromSql)]
+    #[postgres(name = "mood")]
+    enum Mood {
+        #[postgres(name = "sad")]
+        Sad,
+        #[postgres(name = "ok")]
+        Ok,
+        #[postgres(name = "happy")]
+        Happy,
+    }
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let handshake = connect("user=postgres");
+    let (mut client, connection) = runtime.block_on(handshake).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let batch = client.batch_execute("CREATE TYPE pg_temp.mood AS ENUM ('sad', 'ok', 'happy')");
+    runtime.block_on(batch).unwrap();
+
+    for (value, repr) in &[(Mood::Sad, "sad"), (Mood::Ok, "ok"), (Mood::Happy, "happy")] {
+        let prepare = client.prepare("SELECT $1::mood, $2::TEXT::mood");
+        let stmt = runtime.block_on(prepare).unwrap();
+        let query = client.query(&stmt, &[value, repr]).collect();
+        let rows = runtime.block_on(query).unwrap();
+        assert_eq!(value, &rows[0].get::<_, Mood>(0));
+        assert_eq!(value, &rows[0].get::<_, Mood>(1));
+    }
+}
+
+#[test]
+fn enum_wrong_variants() {
+    #[derive(Debug, PartialEq, ToSql, FromSql)]
+    #[postgres(name = "mood")]
+    enum Mood {
+        #[postgres(name = "sad")]
+        Sad,
+        #[postgres(name = "happy")]
+        Happy,
+    }
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let handshake = connect("user=postgres");
+    let (mut client, connection) = runtime.block_on(handshake).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let batch = client.batch_execute("CREATE TYPE pg_temp.mood AS ENUM ('sad', 'ok', 'happy')");
+    runtime.block_on(batch).unwrap();
+
+    let prepare = client.prepare("SELECT $1::mood");
+    let stmt = runtime.block_on(prepare).unwrap();
+    let type_ = &stmt.params()[0];
+    assert!(!<Mood as ToSql>::accepts(type_));
+    assert!(!<Mood as FromSql>::accepts(type_));
+}
+
+#[test]
+fn domain() {
+    #[derive(Debug, PartialEq, ToSql, FromSql)]
+    #[postgres(name = "session_id")]
+    struct SessionId(Vec<u8>);
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let handshake = connect("user=postgres");
+    let (mut client, connection) = runtime.block_on(handshake).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let batch = client.batch_execute(
+        "CREATE DOMAIN pg_temp.session_id AS bytea CHECK(octet_length(VALUE) = 16);
+         CREATE TABLE pg_temp.foo (id pg_temp.session_id);",
+    );
+    runtime.block_on(batch).unwrap();
+
+    let id = SessionId(b"0123456789abcdef".to_vec());
+
+    let prepare = client.prepare("INSERT INTO pg_temp.foo (id) VALUES ($1)");
+    let stmt = runtime.block_on(prepare).unwrap();
+    let execute = client.execute(&stmt, &[&id]);
+    runtime.block_on(execute).unwrap();
+
+    let prepare = client.prepare("SELECT id FROM pg_temp.foo");
+    let stmt = runtime.block_on(prepare).unwrap();
+    let query = client.query(&stmt, &[]).collect();
+    let rows = runtime.block_on(query).unwrap();
+    assert_eq!(id, rows[0].get(0));
+}
+
+#[test]
+fn composite() {
+    #[derive(Debug, PartialEq, ToSql, FromSql)]
+    #[postgres(name = "inventory_item")]
+    struct InventoryItem {
+        name: String,
+        #[postgres(name = "supplier")]
+        supplier_id: i32,
+        price: Option<f64>,
+    }
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let handshake = connect("user=postgres");
+    let (mut client, connection) = runtime.block_on(handshake).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let batch = client.batch_execute(
+        "CREATE TYPE pg_temp.inventory_item AS (
+            name TEXT,
+            supplier INTEGER,
+            price DOUBLE PRECISION
+        )",
+    );
+    runtime.block_on(batch).unwrap();
+
+    let items = [
+        InventoryItem {
+            name: "foobar".to_owned(),
+            supplier_id: 100,
+            price: Some(15.50),
+        },
+        InventoryItem {
+            name: "foobar".to_owned(),
+            supplier_id: 100,
+            price: None,
+        },
+    ];
+
+    let prepare = client.prepare("SELECT $1::inventory_item");
+    let stmt = runtime.block_on(prepare).unwrap();
+    for item in &items {
+        let query = client.query(&stmt, &[item]).collect();
+        let rows = runtime.block_on(query).unwrap();
+        assert_eq!(item, &rows[0].get::<_, InventoryItem>(0));
+    }
+
+    let prepare = client.prepare("SELECT ROW('foobar', 100, NULL)::inventory_item");
+    let stmt = runtime.block_on(prepare).unwrap();
+    let query = client.query(&stmt, &[]).collect();
+    let rows = runtime.block_on(query).unwrap();
+    assert_eq!(items[1], rows[0].get::<_, InventoryItem>(0));
+}
+
+#[test]
+fn composite_accepts() {
+    #[derive(Debug, PartialEq, ToSql, FromSql)]
+    #[postgres(name = "inventory_item")]
+    struct InventoryItem {
+        name: String,
+        supplier: i32,
+    }
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let handshake = connect("user=postgres");
+    let (mut client, connection) = runtime.block_on(handshake).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let batch = client.batch_execute(
+        "CREATE TYPE pg_temp.inventory_item AS (
+            name TEXT,
+            supplier TEXT
+        )",
+    );
+    runtime.block_on(batch).unwrap();
+
+    let prepare = client.prepare("SELECT $1::inventory_item");
+    let stmt = runtime.block_on(prepare).unwrap();
+    let type_ = &stmt.params()[0];
+    assert!(!<InventoryItem as ToSql>::accepts(type_));
+    assert!(!<InventoryItem as FromSql>::accepts(type_));
+    assert!(!<InventoryItem as ToSql>::accepts(&Type::TEXT));
+}
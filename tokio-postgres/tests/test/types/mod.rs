@@ -1,25 +1,36 @@
 use futures::{Future, Stream};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::f32;
 use std::f64;
 use std::fmt;
+use std::net::IpAddr;
 use std::result;
 use std::time::{Duration, UNIX_EPOCH};
 use tokio::runtime::current_thread::Runtime;
 use tokio_postgres::to_sql_checked;
-use tokio_postgres::types::{FromSql, FromSqlOwned, IsNull, Kind, ToSql, Type, WrongType};
+use tokio_postgres::types::{
+    FromSql, FromSqlOwned, Interval, IpNetwork, IsNull, Kind, Lsn, Range, RangeBound, ToSql, Type,
+    WrongType,
+};
 
 use crate::connect;
 
+#[cfg(feature = "with-bigdecimal-0.4")]
+mod bigdecimal_04;
 #[cfg(feature = "with-bit-vec-0.7")]
 mod bit_vec_07;
 #[cfg(feature = "with-chrono-0.4")]
 mod chrono_04;
+#[cfg(feature = "derive")]
+mod derive;
 #[cfg(feature = "with-eui48-0.4")]
 mod eui48_04;
 #[cfg(feature = "with-geo-0.10")]
 mod geo_010;
+#[cfg(feature = "with-rust_decimal-1")]
+mod rust_decimal_1;
 #[cfg(feature = "with-serde_json-1")]
 mod serde_json_1;
 #[cfg(feature = "with-uuid-0.7")]
@@ -610,3 +621,209 @@ fn system_time() {
         ],
     );
 }
+
+#[test]
+fn inet() {
+    test_type(
+        "INET",
+        &[
+            (Some("127.0.0.1".parse::<IpAddr>().unwrap()), "'127.0.0.1'"),
+            (
+                Some(
+                    "2001:4f8:3:ba:2e0:81ff:fe22:d1f1"
+                        .parse::<IpAddr>()
+                        .unwrap(),
+                ),
+                "'2001:4f8:3:ba:2e0:81ff:fe22:d1f1'",
+            ),
+            (None, "NULL"),
+        ],
+    );
+}
+
+#[test]
+fn ip_network() {
+    test_type(
+        "INET",
+        &[
+            (
+                Some(IpNetwork::new("192.168.0.1".parse().unwrap(), 24)),
+                "'192.168.0.1/24'",
+            ),
+            (
+                Some(IpNetwork::from("::1".parse::<IpAddr>().unwrap())),
+                "'::1'",
+            ),
+            (None, "NULL"),
+        ],
+    );
+    test_type(
+        "CIDR",
+        &[
+            (
+                Some(IpNetwork::new("10.0.0.0".parse().unwrap(), 8)),
+                "'10.0.0.0/8'",
+            ),
+            (
+                Some(IpNetwork::new("2001:4f8:3:ba::".parse().unwrap(), 64)),
+                "'2001:4f8:3:ba::/64'",
+            ),
+            (None, "NULL"),
+        ],
+    );
+}
+
+#[test]
+fn lsn() {
+    test_type(
+        "PG_LSN",
+        &[
+            (Some(Lsn::new(0x16_B374_D848)), "'16/B374D848'"),
+            (Some(Lsn::new(0)), "'0/0'"),
+            (None, "NULL"),
+        ],
+    );
+}
+
+#[test]
+fn lsn_string() {
+    let lsn = "16/B374D848".parse::<Lsn>().unwrap();
+    assert_eq!(lsn, Lsn::new(0x16_B374_D848));
+    assert_eq!(lsn.to_string(), "16/B374D848");
+    assert_eq!(Lsn::new(0).to_string(), "0/0");
+    assert!("16B374D848".parse::<Lsn>().is_err());
+    assert!("16/".parse::<Lsn>().is_err());
+    assert!("1/123456789".parse::<Lsn>().is_err());
+}
+
+#[test]
+fn interval() {
+    test_type(
+        "INTERVAL",
+        &[
+            (Some(Interval::new(0, 0, 0)), "'0'"),
+            (
+                Some(Interval::new(14, 3, 4 * 3_600_000_000 + 5)),
+                "'1 year 2 months 3 days 04:00:00.000005'",
+            ),
+            (
+                Some(Interval::new(-1, -2, -3_000_000)),
+                "'-1 month -2 days -00:00:03'",
+            ),
+            (None, "NULL"),
+        ],
+    );
+}
+
+#[test]
+fn interval_duration() {
+    let interval = Interval::new(0, 1, 1_500_000);
+    assert_eq!(
+        Duration::try_from(interval).unwrap(),
+        Duration::from_micros(86_401_500_000)
+    );
+    assert!(Duration::try_from(Interval::new(1, 0, 0)).is_err());
+    assert!(Duration::try_from(Interval::new(0, 0, -1)).is_err());
+
+    assert_eq!(
+        Interval::try_from(Duration::from_micros(1_500_000)).unwrap(),
+        Interval::new(0, 0, 1_500_000)
+    );
+    assert!(Interval::try_from(Duration::from_nanos(1)).is_err());
+}
+
+#[test]
+fn int4range() {
+    test_type(
+        "INT4RANGE",
+        &[
+            (
+                Some(Range::Nonempty(
+                    RangeBound::Inclusive(1i32),
+                    RangeBound::Exclusive(10i32),
+                )),
+                "'[1,10)'",
+            ),
+            (
+                Some(Range::Nonempty(
+                    RangeBound::Unbounded,
+                    RangeBound::Exclusive(10i32),
+                )),
+                "'(,10)'",
+            ),
+            (
+                Some(Range::Nonempty(
+                    RangeBound::Inclusive(1i32),
+                    RangeBound::Unbounded,
+                )),
+                "'[1,)'",
+            ),
+            (Some(Range::Empty), "'empty'"),
+            (None, "NULL"),
+        ],
+    );
+}
+
+#[test]
+fn int8range() {
+    test_type(
+        "INT8RANGE",
+        &[
+            (
+                Some(Range::Nonempty(
+                    RangeBound::Inclusive(-1i64),
+                    RangeBound::Exclusive(1i64 << 40),
+                )),
+                "'[-1,1099511627776)'",
+            ),
+            (None, "NULL"),
+        ],
+    );
+}
+
+#[test]
+fn tsrange() {
+    test_type(
+        "TSRANGE",
+        &[
+            (
+                Some(Range::Nonempty(
+                    RangeBound::Inclusive(UNIX_EPOCH + Duration::from_millis(1_010)),
+                    RangeBound::Inclusive(UNIX_EPOCH + Duration::from_millis(2_020)),
+                )),
+                "'[1970-01-01 00:00:01.01,1970-01-01 00:00:02.02]'",
+            ),
+            (None, "NULL"),
+        ],
+    );
+}
+
+#[test]
+fn custom_range() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let handshake = connect("user=postgres");
+    let (mut client, connection) = runtime.block_on(handshake).unwrap();
+    let connection = connection.map_err(|e| panic!("{}", e));
+    runtime.spawn(connection);
+
+    let batch = client.batch_execute(
+        "CREATE TYPE pg_temp.floatrange AS RANGE (
+            subtype = float8,
+            subtype_diff = float8mi
+        )",
+    );
+    runtime.block_on(batch).unwrap();
+
+    let prepare = client.prepare("SELECT $1::floatrange");
+    let stmt = runtime.block_on(prepare).unwrap();
+    let type_ = &stmt.params()[0];
+    assert_eq!(type_.name(), "floatrange");
+    assert_eq!(type_.kind(), &Kind::Range(Type::FLOAT8));
+
+    let range = Range::Nonempty(RangeBound::Exclusive(1.5f64), RangeBound::Inclusive(2.5f64));
+    let query = client.query(&stmt, &[&range]).collect();
+    let rows = runtime.block_on(query).unwrap();
+    let result: Range<f64> = rows[0].get(0);
+    assert_eq!(result, range);
+}
@@ -0,0 +1,25 @@
+use rust_decimal_1::Decimal;
+
+use crate::types::test_type;
+
+#[test]
+fn test_decimal_params() {
+    test_type(
+        "NUMERIC",
+        &[
+            (Some("0".parse::<Decimal>().unwrap()), "0"),
+            (Some("1.00".parse::<Decimal>().unwrap()), "1.00"),
+            (
+                Some("-12345.6789".parse::<Decimal>().unwrap()),
+                "-12345.6789",
+            ),
+            (Some("0.000001".parse::<Decimal>().unwrap()), "0.000001"),
+            (
+                Some("79228162514264337593543950335".parse::<Decimal>().unwrap()),
+                "79228162514264337593543950335",
+            ),
+            (Some("1000000000".parse::<Decimal>().unwrap()), "1000000000"),
+            (None, "NULL"),
+        ],
+    )
+}